@@ -8,9 +8,9 @@ pub async fn run() -> Result<()> {
             println!("Starting Holger {:?}",config);
 
             let mut holger = holger_ron::read_ron_config(config)?;
-            let backend=holger.instantiate_backends();
+            holger.instantiate_backends()?;
 
-            let r=wire_holger(&mut holger);
+            wire_holger(&mut holger)?;
             print_wiring_summary(&holger);
             let cfg = PrettyConfig::new()
                 .depth_limit(4)
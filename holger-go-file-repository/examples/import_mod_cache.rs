@@ -0,0 +1,37 @@
+use holger_go_file_repository::GoRepoFile;
+
+/// cargo run --example import_mod_cache -- /var/lib/holger/go-prod /airgap/go app/go.sum lib/go.sum
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(root), Some(cache_dir)) = (args.next(), args.next()) else {
+        anyhow::bail!("usage: import_mod_cache <repository root> <GOMODCACHE dir> [go.sum...]");
+    };
+    let go_sum_files: Vec<_> = args.map(Into::into).collect();
+    let repo = GoRepoFile::new(
+        "go-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/go-prod".into(),
+    )?;
+    let report = repo.import_mod_cache(cache_dir.as_ref(), &go_sum_files)?;
+
+    for id in &report.verified {
+        println!("verified {}@{}", id.name, id.version);
+    }
+    for id in &report.unverified {
+        println!("unverified {}@{}", id.name, id.version);
+    }
+    for (id, reason) in &report.mismatched {
+        println!("mismatched {}@{}: {}", id.name, id.version, reason);
+    }
+    for (path, reason) in &report.rejected {
+        println!("rejected {}: {}", path.display(), reason);
+    }
+    println!(
+        "{} verified, {} unverified, {} mismatched, {} files rejected",
+        report.verified.len(),
+        report.unverified.len(),
+        report.mismatched.len(),
+        report.rejected.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::io::Read;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+/// The `h1:` hash go.sum records: SHA-256 over a summary line
+/// `<sha256 hex>  <name>\n` per file, sorted by name, in base64.
+pub fn hash1<'a>(files: impl IntoIterator<Item = (&'a str, &'a [u8])>) -> String {
+    let mut files: Vec<(&str, &[u8])> = files.into_iter().collect();
+    files.sort_by_key(|(name, _)| *name);
+    let summary: String = files
+        .iter()
+        .map(|(name, data)| format!("{}  {}\n", hex::encode(Sha256::digest(data)), name))
+        .collect();
+    format!("h1:{}", STANDARD.encode(Sha256::digest(summary)))
+}
+
+/// Hash of a module's `go.mod`, recorded in go.sum as `<module> <version>/go.mod h1:...`.
+pub fn hash_go_mod(data: &[u8]) -> String {
+    hash1([("go.mod", data)])
+}
+
+/// Hash of a module zip. Every entry must sit below `prefix`, the
+/// `module@version/` directory module zips are built around.
+pub fn hash_zip(data: &[u8], prefix: &str) -> anyhow::Result<String> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+    let mut files = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let name = entry.name().to_string();
+        if !name.starts_with(prefix) || name.contains('\n') {
+            anyhow::bail!("zip entry `{}` is not below {}", name, prefix);
+        }
+        let mut content = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut content)?;
+        files.push((name, content));
+    }
+    Ok(hash1(files.iter().map(|(name, content)| {
+        (name.as_str(), content.as_slice())
+    })))
+}
@@ -0,0 +1,50 @@
+/// Case-encode a module path or version for URLs and file names: every
+/// uppercase letter becomes `!` and its lowercase form, so
+/// `github.com/BurntSushi/toml` is served as `github.com/!burnt!sushi/toml`.
+/// `None` if the text already contains `!`.
+pub fn escape(text: &str) -> Option<String> {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '!' => return None,
+            c if c.is_ascii_uppercase() => {
+                escaped.push('!');
+                escaped.push(c.to_ascii_lowercase());
+            }
+            c => escaped.push(c),
+        }
+    }
+    Some(escaped)
+}
+
+/// Undo [`escape`]. `None` for uppercase letters, which escaped text never
+/// holds, and for `!` not followed by a lowercase letter.
+pub fn unescape(escaped: &str) -> Option<String> {
+    let mut text = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '!' => match chars.next() {
+                Some(c) if c.is_ascii_lowercase() => text.push(c.to_ascii_uppercase()),
+                _ => return None,
+            },
+            c if c.is_ascii_uppercase() => return None,
+            c => text.push(c),
+        }
+    }
+    Some(text)
+}
+
+/// Module paths as the go command accepts them: `/`-separated elements of
+/// letters, digits and `-._~`, none empty or starting or ending with a dot.
+pub fn is_valid_module_path(path: &str) -> bool {
+    !path.is_empty()
+        && path.split('/').all(|element| {
+            !element.is_empty()
+                && !element.starts_with('.')
+                && !element.ends_with('.')
+                && element
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-._~".contains(c))
+        })
+}
@@ -0,0 +1,50 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Hashes collected from go.sum files, keyed by module path and version;
+/// `go.mod` hashes use the `v1.2.3/go.mod` version form go.sum uses.
+#[derive(Debug, Default)]
+pub struct GoSum {
+    hashes: BTreeMap<(String, String), BTreeSet<String>>,
+}
+
+/// How a computed hash compares to the go.sum files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    Verified,
+    Unknown,
+    /// The recorded hashes, which the computed one is not among or which disagree
+    Mismatch(Vec<String>),
+}
+
+impl GoSum {
+    /// Add the lines of one go.sum file. Lines that are not
+    /// `<module> <version>[/go.mod] <hash>` are ignored.
+    pub fn add(&mut self, content: &str) {
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            if let (Some(module), Some(version), Some(hash), None) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            {
+                self.hashes
+                    .entry((module.to_string(), version.to_string()))
+                    .or_default()
+                    .insert(hash.to_string());
+            }
+        }
+    }
+
+    /// Check a computed hash; `version` is `v1.2.3` for zips and `v1.2.3/go.mod` for go.mod files.
+    pub fn verify(&self, module: &str, version: &str, hash: &str) -> Verification {
+        match self.hashes.get(&(module.to_string(), version.to_string())) {
+            None => Verification::Unknown,
+            Some(recorded) if recorded.len() == 1 && recorded.contains(hash) => {
+                Verification::Verified
+            }
+            Some(recorded) => Verification::Mismatch(recorded.iter().cloned().collect()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+}
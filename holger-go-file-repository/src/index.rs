@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::escape::{is_valid_module_path, unescape};
+use crate::semver::Version;
+
+/// Files the GOPROXY protocol serves per module version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Info,
+    Mod,
+    Zip,
+}
+
+impl FileKind {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "info" => Some(FileKind::Info),
+            "mod" => Some(FileKind::Mod),
+            "zip" => Some(FileKind::Zip),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            FileKind::Info => "info",
+            FileKind::Mod => "mod",
+            FileKind::Zip => "zip",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            FileKind::Info => "application/json",
+            FileKind::Mod => "text/plain; charset=utf-8",
+            FileKind::Zip => "application/zip",
+        }
+    }
+}
+
+/// The stored files of one module version.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleVersion {
+    pub version: String,
+    pub info: Option<PathBuf>,
+    pub go_mod: Option<PathBuf>,
+    pub zip: Option<PathBuf>,
+}
+
+impl ModuleVersion {
+    pub fn file(&self, kind: FileKind) -> Option<&PathBuf> {
+        match kind {
+            FileKind::Info => self.info.as_ref(),
+            FileKind::Mod => self.go_mod.as_ref(),
+            FileKind::Zip => self.zip.as_ref(),
+        }
+    }
+}
+
+/// In-memory view of the stored modules, keyed by unescaped module path and version.
+#[derive(Debug, Default)]
+pub struct ModuleIndex {
+    modules: BTreeMap<String, BTreeMap<String, ModuleVersion>>,
+}
+
+impl ModuleIndex {
+    /// Record one stored file, replacing an earlier one of the same kind.
+    pub fn insert(&mut self, module: &str, version: &str, kind: FileKind, path: PathBuf) {
+        let entry = self
+            .modules
+            .entry(module.to_string())
+            .or_default()
+            .entry(version.to_string())
+            .or_insert_with(|| ModuleVersion {
+                version: version.to_string(),
+                ..ModuleVersion::default()
+            });
+        match kind {
+            FileKind::Info => entry.info = Some(path),
+            FileKind::Mod => entry.go_mod = Some(path),
+            FileKind::Zip => entry.zip = Some(path),
+        }
+    }
+
+    pub fn get(&self, module: &str, version: &str) -> Option<&ModuleVersion> {
+        self.modules.get(module)?.get(version)
+    }
+
+    /// Versions of a module in semver order, oldest first.
+    pub fn versions(&self, module: &str) -> Vec<&ModuleVersion> {
+        let mut versions: Vec<&ModuleVersion> = self
+            .modules
+            .get(module)
+            .map(|v| v.values().collect())
+            .unwrap_or_default();
+        versions.sort_by_cached_key(|v| Version::parse(&v.version));
+        versions
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ModuleVersion)> {
+        self.modules
+            .iter()
+            .flat_map(|(module, versions)| versions.values().map(move |v| (module.as_str(), v)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+/// Split a download path such as `github.com/!burnt!sushi/toml/@v/v1.3.2.mod`,
+/// relative to the repository root, into module path, version and file kind.
+pub fn parse_download_path(rel: &str) -> Option<(String, String, FileKind)> {
+    let (module, filename) = rel.split_once("/@v/")?;
+    let (version, extension) = filename.rsplit_once('.')?;
+    let module = unescape(module).filter(|m| is_valid_module_path(m))?;
+    let version = unescape(version).filter(|v| Version::parse(v).is_some())?;
+    Some((module, version, FileKind::from_extension(extension)?))
+}
@@ -0,0 +1,658 @@
+use holger_traits::util::{find_files, format_rfc3339};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::UNIX_EPOCH;
+
+pub mod dirhash;
+pub mod escape;
+pub mod gosum;
+pub mod index;
+pub mod modcache;
+pub mod semver;
+
+use escape::{is_valid_module_path, unescape};
+use index::{parse_download_path, FileKind, ModuleIndex, ModuleVersion};
+use semver::Version;
+
+/// Go module proxy backed by a directory in the GOPROXY layout, the one of
+/// `$GOMODCACHE/cache/download`: `<escaped module>/@v/<escaped version>.{info,mod,zip}`.
+///
+/// Artifacts are addressed by module path: `ArtifactId { namespace: None,
+/// name: "golang.org/x/text", version: "v0.14.0" }` names the module zip.
+pub struct GoRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/go-prod
+    pub index: RwLock<ModuleIndex>,
+}
+
+/// The `.info` document of a module version.
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Info<'a> {
+    version: &'a str,
+    time: String,
+}
+
+impl GoRepoFile {
+    /// Open a repository rooted at `root`, indexing every module file below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = GoRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            index: RwLock::new(ModuleIndex::default()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the files on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = ModuleIndex::default();
+        for (rel, path) in find_files(&self.root)? {
+            match parse_download_path(&rel) {
+                Some((module, version, kind)) => index.insert(&module, &version, kind, path),
+                None => println!("Skipping non-module file {}", rel),
+            }
+        }
+        self.artifacts = index
+            .iter()
+            .filter(|(_, v)| v.zip.is_some())
+            .map(|(module, v)| ArtifactId {
+                namespace: None,
+                name: module.to_string(),
+                version: v.version.clone(),
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, ModuleIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, ModuleIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// `@v/list`: tagged versions with a `go.mod`, oldest first, one per line.
+    /// Pseudo-versions are left out, as the go command expects. `None` for
+    /// unknown modules.
+    pub fn list(&self, module: &str) -> Option<String> {
+        let index = self.index();
+        let versions = index.versions(module);
+        if versions.is_empty() {
+            return None;
+        }
+        Some(
+            versions
+                .iter()
+                .filter(|v| v.go_mod.is_some())
+                .filter(|v| Version::parse(&v.version).is_some_and(|v| !v.is_pseudo()))
+                .map(|v| format!("{}\n", v.version))
+                .collect(),
+        )
+    }
+
+    /// `@latest`: the newest release, else the newest prerelease, else the
+    /// newest pseudo-version.
+    pub fn latest(&self, module: &str) -> Option<ModuleVersion> {
+        let index = self.index();
+        index
+            .versions(module)
+            .into_iter()
+            .filter(|v| v.go_mod.is_some())
+            .filter_map(|v| Some((Version::parse(&v.version)?, v)))
+            .max_by(|(a, _), (b, _)| {
+                let rank = |v: &Version| (!v.is_pseudo(), !v.is_prerelease());
+                rank(a).cmp(&rank(b)).then_with(|| a.cmp(b))
+            })
+            .map(|(_, v)| v.clone())
+    }
+
+    /// The stored `.info` document, or one dated by the version's files.
+    pub fn info(&self, version: &ModuleVersion) -> anyhow::Result<Vec<u8>> {
+        if let Some(path) = &version.info {
+            return Ok(std::fs::read(path)?);
+        }
+        let modified = [&version.go_mod, &version.zip]
+            .into_iter()
+            .flatten()
+            .filter_map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+            .max()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        Ok(serde_json::to_vec(&Info {
+            version: &version.version,
+            time: format_rfc3339(modified),
+        })?)
+    }
+
+    fn info_response(&self, version: &ModuleVersion) -> anyhow::Result<Http2Response> {
+        Ok((
+            200,
+            vec![("Content-Type".into(), FileKind::Info.content_type().into())],
+            self.info(version)?,
+        ))
+    }
+}
+
+/// Unescaped module path of the URL segments before `@v` or `@latest`.
+fn module_path(segments: &[&str]) -> Option<String> {
+    unescape(&segments.join("/")).filter(|m| is_valid_module_path(m))
+}
+
+impl RepositoryBackendTrait for GoRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        _headers: &[(String, String)],
+        _body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Go repo handle_http2_request.suburl={}", suburl);
+
+        let (path, _query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let not_found = || Ok((404, Vec::new(), b"Not found".to_vec()));
+
+        match parts.as_slice() {
+            [repo, ..] if *repo == self.name() && method != "GET" && method != "HEAD" => {
+                Ok((405, Vec::new(), b"Method not allowed".to_vec()))
+            }
+
+            // Version list → /go-prod/github.com/!burnt!sushi/toml/@v/list
+            [repo, module @ .., "@v", "list"] if *repo == self.name() && !module.is_empty() => {
+                match module_path(module).and_then(|m| self.list(&m)) {
+                    Some(list) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "text/plain; charset=utf-8".into())],
+                        list.into_bytes(),
+                    )),
+                    None => not_found(),
+                }
+            }
+
+            // Version files → /go-prod/golang.org/x/text/@v/v0.14.0.{info,mod,zip}
+            [repo, module @ .., "@v", file] if *repo == self.name() && !module.is_empty() => {
+                let Some(module) = module_path(module) else {
+                    return not_found();
+                };
+                let Some((version, kind)) = file
+                    .rsplit_once('.')
+                    .and_then(|(v, ext)| Some((unescape(v)?, FileKind::from_extension(ext)?)))
+                else {
+                    return not_found();
+                };
+                let Some(stored) = self.index().get(&module, &version).cloned() else {
+                    return not_found();
+                };
+                match (kind, stored.file(kind)) {
+                    (FileKind::Info, _) if stored.go_mod.is_some() => self.info_response(&stored),
+                    (FileKind::Info, _) => not_found(),
+                    (_, Some(path)) => Ok((
+                        200,
+                        vec![("Content-Type".into(), kind.content_type().into())],
+                        std::fs::read(path)?,
+                    )),
+                    (_, None) => not_found(),
+                }
+            }
+
+            // Latest version → /go-prod/golang.org/x/text/@latest
+            [repo, module @ .., "@latest"] if *repo == self.name() && !module.is_empty() => {
+                match module_path(module).and_then(|m| self.latest(&m)) {
+                    Some(latest) => self.info_response(&latest),
+                    None => not_found(),
+                }
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                not_found()
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Go
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self
+            .index()
+            .get(&id.name, &id.version)
+            .and_then(|v| v.zip.clone());
+        match path {
+            Some(path) => Ok(Some(std::fs::read(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, id: &ArtifactId, _data: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "repository {} does not accept uploads, cannot store {} {}",
+            self.name,
+            id.name,
+            id.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn store(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    fn get(repo: &GoRepoFile, suburl: &str) -> Http2Response {
+        repo.handle_http2_request("GET", suburl, &[], b"").unwrap()
+    }
+
+    #[test]
+    fn escapes_module_paths() {
+        use escape::{escape, unescape};
+
+        assert_eq!(
+            escape("github.com/BurntSushi/toml").unwrap(),
+            "github.com/!burnt!sushi/toml"
+        );
+        assert_eq!(escape("v1.0.0-RC1").unwrap(), "v1.0.0-!r!c1");
+        assert_eq!(escape("example.com/a!b"), None);
+        assert_eq!(
+            unescape("github.com/!burnt!sushi/toml").unwrap(),
+            "github.com/BurntSushi/toml"
+        );
+        for invalid in ["github.com/BurntSushi/toml", "a/!", "a/!1", "a/!!b"] {
+            assert_eq!(unescape(invalid), None, "{invalid}");
+        }
+        assert!(is_valid_module_path("golang.org/x/text"));
+        for invalid in ["", "a//b", "a/../b", "a/.b", "a/b.", "a b", "a\\b"] {
+            assert!(!is_valid_module_path(invalid), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn orders_semantic_versions() {
+        let ordered = [
+            "v0.0.0-20191109021931-daa7c04131f5",
+            "v0.1.0-alpha",
+            "v0.1.0-alpha.1",
+            "v0.1.0-alpha.beta",
+            "v0.1.0-beta.2",
+            "v0.1.0-beta.11",
+            "v0.1.0-rc.1",
+            "v0.1.0",
+            "v0.1.1-0.20191109021931-daa7c04131f5",
+            "v0.1.1",
+            "v0.10.0",
+            "v2.0.0+incompatible",
+        ];
+        for pair in ordered.windows(2) {
+            let (a, b) = (
+                Version::parse(pair[0]).unwrap(),
+                Version::parse(pair[1]).unwrap(),
+            );
+            assert!(a < b, "{pair:?}");
+        }
+        for invalid in [
+            "1.0.0",
+            "v1.0",
+            "v01.0.0",
+            "v1.0.0-",
+            "v1.0.0-a..b",
+            "vx.y.z",
+        ] {
+            assert_eq!(Version::parse(invalid), None, "{invalid}");
+        }
+        let pseudo = |v: &str| Version::parse(v).unwrap().is_pseudo();
+        assert!(pseudo("v0.0.0-20191109021931-daa7c04131f5"));
+        assert!(pseudo("v1.2.4-0.20191109021931-daa7c04131f5"));
+        assert!(pseudo(
+            "v1.2.3-pre.0.20191109021931-daa7c04131f5+incompatible"
+        ));
+        assert!(!pseudo("v1.2.3-rc.1"));
+        assert!(!pseudo("v1.2.3"));
+    }
+
+    #[test]
+    fn serves_goproxy_protocol() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let toml = "github.com/!burnt!sushi/toml/@v";
+        for version in ["v1.3.2", "v1.2.1", "v1.4.0-rc.1"] {
+            store(
+                root,
+                &format!("{toml}/{version}.mod"),
+                b"module github.com/BurntSushi/toml\n",
+            );
+            store(root, &format!("{toml}/{version}.zip"), version.as_bytes());
+        }
+        store(
+            root,
+            &format!("{toml}/v1.3.2.info"),
+            br#"{"Version":"v1.3.2","Time":"2023-06-08T06:13:57Z"}"#,
+        );
+        // Only pseudo-versions and prereleases
+        let tools = "golang.org/x/tools/@v";
+        for version in [
+            "v0.0.0-20191109021931-daa7c04131f5",
+            "v0.0.0-20200101000000-0123456789ab",
+        ] {
+            store(
+                root,
+                &format!("{tools}/{version}.mod"),
+                b"module golang.org/x/tools\n",
+            );
+        }
+        store(root, "golang.org/x/tools/@v/list", b"stale\n");
+        store(root, "golang.org/x/tools/@v/v0.1.0.ziphash", b"h1:...");
+
+        let repo = GoRepoFile::new(
+            "go-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/go-prod".into(),
+        )
+        .unwrap();
+        assert_eq!(repo.artifacts.len(), 3);
+
+        let (status, headers, body) = get(&repo, "/go-prod/github.com/!burnt!sushi/toml/@v/list");
+        assert_eq!(status, 200);
+        assert_eq!(headers[0].1, "text/plain; charset=utf-8");
+        assert_eq!(body, b"v1.2.1\nv1.3.2\nv1.4.0-rc.1\n");
+
+        let (status, headers, body) = get(
+            &repo,
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v1.3.2.info",
+        );
+        assert_eq!((status, headers[0].1.as_str()), (200, "application/json"));
+        assert_eq!(
+            body,
+            br#"{"Version":"v1.3.2","Time":"2023-06-08T06:13:57Z"}"#
+        );
+        let (_, _, body) = get(
+            &repo,
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v1.2.1.info",
+        );
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info["Version"], "v1.2.1");
+        assert!(info["Time"].as_str().unwrap().ends_with('Z'));
+
+        let (status, headers, body) =
+            get(&repo, "/go-prod/github.com/!burnt!sushi/toml/@v/v1.2.1.mod");
+        assert_eq!(
+            (status, headers[0].1.as_str()),
+            (200, "text/plain; charset=utf-8")
+        );
+        assert_eq!(body, b"module github.com/BurntSushi/toml\n");
+        let (status, headers, body) = get(
+            &repo,
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v1.4.0-rc.1.zip",
+        );
+        assert_eq!((status, headers[0].1.as_str()), (200, "application/zip"));
+        assert_eq!(body, b"v1.4.0-rc.1");
+
+        // @latest prefers releases, then prereleases, then pseudo-versions
+        let (status, _, body) = get(&repo, "/go-prod/github.com/!burnt!sushi/toml/@latest");
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            br#"{"Version":"v1.3.2","Time":"2023-06-08T06:13:57Z"}"#
+        );
+        let (_, _, body) = get(&repo, "/go-prod/golang.org/x/tools/@latest");
+        let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(info["Version"], "v0.0.0-20200101000000-0123456789ab");
+        assert_eq!(get(&repo, "/go-prod/golang.org/x/tools/@v/list").2, b"");
+
+        for missing in [
+            "/go-prod/github.com/BurntSushi/toml/@v/list",
+            "/go-prod/github.com/burntsushi/toml/@v/list",
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v9.9.9.mod",
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v1.3.2.ziphash",
+            "/go-prod/github.com/!burnt!sushi/toml/@v/v1.3.2",
+            "/go-prod/golang.org/x/tools/@v/v0.0.0-20191109021931-daa7c04131f5.zip",
+            "/go-prod/golang.org/x/net/@latest",
+            "/go-prod/@v/list",
+            "/go-prod/sumdb/sum.golang.org/supported",
+        ] {
+            assert_eq!(get(&repo, missing).0, 404, "{missing}");
+        }
+        let (status, _, _) = repo
+            .handle_http2_request(
+                "PUT",
+                "/go-prod/golang.org/x/net/@v/v1.0.0.zip",
+                &[],
+                b"zip",
+            )
+            .unwrap();
+        assert_eq!(status, 405);
+
+        let id = ArtifactId {
+            namespace: None,
+            name: "github.com/BurntSushi/toml".into(),
+            version: "v1.3.2".into(),
+        };
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), b"v1.3.2");
+    }
+
+    /// A module zip holding `files` below `module@version/`.
+    fn module_zip(prefix: &str, files: &[(&str, &[u8])]) -> Vec<u8> {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, data) in files {
+            writer
+                .start_file(
+                    format!("{prefix}{name}"),
+                    zip::write::SimpleFileOptions::default(),
+                )
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn imports_mod_cache_with_go_sum_verification() {
+        use dirhash::{hash1, hash_go_mod, hash_zip};
+
+        // Recorded in go.sum files everywhere
+        assert_eq!(
+            hash_go_mod(b"module golang.org/x/text\n"),
+            "h1:NqM8EUOU14njkJ3fqMW+pc6Ldnwhi/IjpwHt7yyuwOQ="
+        );
+
+        let cache = tempfile::tempdir().unwrap();
+        let download = cache.path().join("cache/download");
+        let text_zip = module_zip(
+            "golang.org/x/text@v0.3.0/",
+            &[("doc.go", b"package text\n"), ("LICENSE", b"BSD")],
+        );
+        let text_zip_hash = hash_zip(&text_zip, "golang.org/x/text@v0.3.0/").unwrap();
+        assert_eq!(
+            text_zip_hash,
+            hash1([
+                ("golang.org/x/text@v0.3.0/LICENSE", &b"BSD"[..]),
+                ("golang.org/x/text@v0.3.0/doc.go", b"package text\n"),
+            ])
+        );
+        let evil_zip = module_zip(
+            "example.com/evil@v1.0.0/",
+            &[("evil.go", b"package evil\n")],
+        );
+        let stray_zip = module_zip("example.com/other@v1.0.0/", &[("a.go", b"package a\n")]);
+        for (rel, data) in [
+            (
+                "golang.org/x/text/@v/v0.3.0.mod",
+                &b"module golang.org/x/text\n"[..],
+            ),
+            (
+                "golang.org/x/text/@v/v0.3.0.info",
+                br#"{"Version":"v0.3.0","Time":"2017-12-14T13:08:43Z"}"#,
+            ),
+            ("golang.org/x/text/@v/v0.3.0.zip", &text_zip),
+            (
+                "golang.org/x/text/@v/v0.3.0.ziphash",
+                text_zip_hash.as_bytes(),
+            ),
+            ("golang.org/x/text/@v/list", b"v0.3.0\n"),
+            ("golang.org/x/text/@v/v0.3.0.lock", b""),
+            ("golang.org/x/text/@v/v0.4.0.zip.partial", b"PK"),
+            (
+                "github.com/!burnt!sushi/toml/@v/v1.3.2.mod",
+                b"module github.com/BurntSushi/toml\n",
+            ),
+            (
+                "example.com/evil/@v/v1.0.0.mod",
+                b"module example.com/evil\n",
+            ),
+            ("example.com/evil/@v/v1.0.0.zip", &evil_zip),
+            (
+                "example.com/corrupt/@v/v1.0.0.mod",
+                b"module example.com/corrupt\n",
+            ),
+            (
+                "example.com/corrupt/@v/v1.0.0.zip",
+                &module_zip("example.com/corrupt@v1.0.0/", &[("c.go", b"package c\n")]),
+            ),
+            (
+                "example.com/corrupt/@v/v1.0.0.ziphash",
+                b"h1:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=\n",
+            ),
+            (
+                "example.com/stray/@v/v1.0.0.mod",
+                b"module example.com/stray\n",
+            ),
+            ("example.com/stray/@v/v1.0.0.zip", &stray_zip),
+            ("example.com/nomod/@v/v1.0.0.zip", b"PK"),
+            (
+                "sumdb/sum.golang.org/lookup/golang.org/x/text@v0.3.0",
+                b"...",
+            ),
+        ] {
+            store(&download, rel, data);
+        }
+        let go_sum = cache.path().join("go.sum");
+        std::fs::write(
+            &go_sum,
+            format!(
+                "golang.org/x/text v0.3.0 {text_zip_hash}\n\
+                 golang.org/x/text v0.3.0/go.mod h1:NqM8EUOU14njkJ3fqMW+pc6Ldnwhi/IjpwHt7yyuwOQ=\n\
+                 example.com/evil v1.0.0 {}\n\
+                 example.com/evil v1.0.0/go.mod {}\n",
+                hash1([("example.com/evil@v1.0.0/evil.go", &b"package good\n"[..])]),
+                hash_go_mod(b"module example.com/evil\n"),
+            ),
+        )
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = GoRepoFile::new(
+            "go-prod".into(),
+            dir.path().into(),
+            "https://10.101.1.9:8443/go-prod".into(),
+        )
+        .unwrap();
+        let report = repo
+            .import_mod_cache(cache.path(), std::slice::from_ref(&go_sum))
+            .unwrap();
+        let ids = |ids: &[ArtifactId]| {
+            ids.iter()
+                .map(|id| format!("{}@{}", id.name, id.version))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&report.verified), ["golang.org/x/text@v0.3.0"]);
+        assert_eq!(
+            ids(&report.unverified),
+            ["github.com/BurntSushi/toml@v1.3.2"]
+        );
+        let mismatched: Vec<(String, &str)> = report
+            .mismatched
+            .iter()
+            .map(|(id, reason)| (format!("{}@{}", id.name, id.version), reason.as_str()))
+            .collect();
+        assert_eq!(mismatched.len(), 3, "{mismatched:?}");
+        assert!(
+            mismatched[0].0 == "example.com/corrupt@v1.0.0"
+                && mismatched[0].1.contains("its .ziphash records h1:AAAA")
+        );
+        assert!(
+            mismatched[1].0 == "example.com/evil@v1.0.0"
+                && mismatched[1].1.starts_with("zip hashes to h1:")
+        );
+        assert!(
+            mismatched[2].0 == "example.com/stray@v1.0.0"
+                && mismatched[2]
+                    .1
+                    .contains("is not below example.com/stray@v1.0.0/")
+        );
+        let rejected: Vec<String> = report
+            .rejected
+            .iter()
+            .map(|(path, reason)| {
+                format!("{}: {reason}", path.file_name().unwrap().to_string_lossy())
+            })
+            .collect();
+        assert_eq!(
+            rejected,
+            [
+                "v0.4.0.zip.partial: partial download",
+                "v1.0.0.zip: example.com/nomod@v1.0.0 has no go.mod"
+            ]
+        );
+
+        assert_eq!(
+            get(&repo, "/go-prod/golang.org/x/text/@v/list").2,
+            b"v0.3.0\n"
+        );
+        assert_eq!(
+            get(&repo, "/go-prod/golang.org/x/text/@v/v0.3.0.zip").2,
+            text_zip
+        );
+        assert_eq!(
+            get(&repo, "/go-prod/github.com/!burnt!sushi/toml/@v/v1.3.2.mod").0,
+            200
+        );
+        assert!(dir
+            .path()
+            .join("github.com/!burnt!sushi/toml/@v/v1.3.2.mod")
+            .exists());
+        assert_eq!(get(&repo, "/go-prod/example.com/evil/@v/list").0, 404);
+        assert_eq!(
+            get(&repo, "/go-prod/example.com/nomod/@v/v1.0.0.zip").0,
+            404
+        );
+
+        // Importing again, from cache/download itself, changes nothing
+        let again = repo.import_mod_cache(&download, &[go_sum]).unwrap();
+        assert_eq!(ids(&again.verified), ids(&report.verified));
+        assert_eq!(again.mismatched.len(), 3);
+        let reloaded = GoRepoFile::new(
+            "go-prod".into(),
+            dir.path().into(),
+            "https://10.101.1.9:8443/go-prod".into(),
+        )
+        .unwrap();
+        assert_eq!(reloaded.artifacts.len(), 1);
+    }
+}
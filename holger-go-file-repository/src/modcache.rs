@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use holger_traits::util::{find_files, write_atomic};
+use holger_traits::ArtifactId;
+
+use crate::dirhash::{hash_go_mod, hash_zip};
+use crate::escape::escape;
+use crate::gosum::{GoSum, Verification};
+use crate::index::{parse_download_path, FileKind, ModuleVersion};
+use crate::GoRepoFile;
+
+/// Outcome of importing a `$GOMODCACHE/cache/download` tree.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Stored module versions whose go.mod and zip hashes match the go.sum files
+    pub verified: Vec<ArtifactId>,
+    /// Stored module versions the go.sum files do not vouch for completely
+    pub unverified: Vec<ArtifactId>,
+    /// Module versions that were not stored because a hash disagrees, with the reason
+    pub mismatched: Vec<(ArtifactId, String)>,
+    /// Files that were not stored, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+impl GoRepoFile {
+    /// Store every module version of a module download cache, given either
+    /// `$GOMODCACHE` or its `cache/download` directory.
+    ///
+    /// The `h1:` hashes of each go.mod and module zip are recomputed and
+    /// checked against `go_sum_files` and the cache's own `.ziphash` files,
+    /// the way `GOSUMDB` checks downloads online. Versions with a mismatching
+    /// hash are not stored.
+    pub fn import_mod_cache(
+        &self,
+        cache_dir: &Path,
+        go_sum_files: &[PathBuf],
+    ) -> anyhow::Result<IngestReport> {
+        let mut go_sum = GoSum::default();
+        for path in go_sum_files {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            go_sum.add(&content);
+        }
+        let download_dir = cache_dir.join("cache").join("download");
+        let download_dir = if download_dir.is_dir() {
+            download_dir
+        } else {
+            cache_dir.to_path_buf()
+        };
+
+        let mut report = IngestReport::default();
+        let mut versions: BTreeMap<(String, String), ModuleVersion> = BTreeMap::new();
+        let mut zip_hashes: BTreeMap<(String, String), PathBuf> = BTreeMap::new();
+        for (rel, path) in find_files(&download_dir)? {
+            let filename = rel.rsplit('/').next().unwrap_or_default();
+            if rel.starts_with("sumdb/") || filename == "list" || filename.ends_with(".lock") {
+                continue;
+            }
+            if filename.ends_with(".partial") || filename.ends_with(".tmp") {
+                report.rejected.push((path, "partial download".into()));
+                continue;
+            }
+            if let Some(zip_rel) = rel.strip_suffix(".ziphash") {
+                if let Some((module, version, _)) = parse_download_path(&format!("{zip_rel}.zip")) {
+                    zip_hashes.insert((module, version), path);
+                    continue;
+                }
+            }
+            match parse_download_path(&rel) {
+                Some((module, version, kind)) => {
+                    let entry = versions
+                        .entry((module, version.clone()))
+                        .or_insert_with(|| ModuleVersion {
+                            version,
+                            ..ModuleVersion::default()
+                        });
+                    match kind {
+                        FileKind::Info => entry.info = Some(path),
+                        FileKind::Mod => entry.go_mod = Some(path),
+                        FileKind::Zip => entry.zip = Some(path),
+                    }
+                }
+                None => report
+                    .rejected
+                    .push((path, "not a module download file".into())),
+            }
+        }
+
+        for ((module, version), files) in versions {
+            let zip_hash = zip_hashes.get(&(module.clone(), version.clone()));
+            self.import_version(&module, files, zip_hash, &go_sum, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    fn import_version(
+        &self,
+        module: &str,
+        files: ModuleVersion,
+        zip_hash: Option<&PathBuf>,
+        go_sum: &GoSum,
+        report: &mut IngestReport,
+    ) -> anyhow::Result<()> {
+        let version = files.version.clone();
+        let id = ArtifactId {
+            namespace: None,
+            name: module.to_string(),
+            version: version.clone(),
+        };
+        let Some(go_mod) = &files.go_mod else {
+            for path in [files.info, files.zip].into_iter().flatten() {
+                report
+                    .rejected
+                    .push((path, format!("{module}@{version} has no go.mod")));
+            }
+            return Ok(());
+        };
+
+        let mod_hash = hash_go_mod(&std::fs::read(go_mod)?);
+        let mut checks = vec![(
+            "go.mod",
+            go_sum.verify(module, &format!("{version}/go.mod"), &mod_hash),
+            mod_hash,
+        )];
+        if let Some(zip) = &files.zip {
+            let hash = match hash_zip(&std::fs::read(zip)?, &format!("{module}@{version}/")) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    report
+                        .mismatched
+                        .push((id, format!("unreadable zip: {e:#}")));
+                    return Ok(());
+                }
+            };
+            if let Some(path) = zip_hash {
+                let recorded = std::fs::read_to_string(path)?;
+                if recorded.trim() != hash {
+                    report.mismatched.push((
+                        id,
+                        format!(
+                            "zip hashes to {hash}, its .ziphash records {}",
+                            recorded.trim()
+                        ),
+                    ));
+                    return Ok(());
+                }
+            }
+            checks.push(("zip", go_sum.verify(module, &version, &hash), hash));
+        }
+        let mismatches: Vec<String> = checks
+            .iter()
+            .filter_map(|(file, check, hash)| match check {
+                Verification::Mismatch(recorded) => Some(format!(
+                    "{file} hashes to {hash}, go.sum records {}",
+                    recorded.join(", ")
+                )),
+                _ => None,
+            })
+            .collect();
+        if !mismatches.is_empty() {
+            report.mismatched.push((id, mismatches.join("; ")));
+            return Ok(());
+        }
+
+        // Store every file of the version, or none of them
+        let dir = self
+            .root
+            .join(escape(module).unwrap_or_default())
+            .join("@v");
+        let escaped_version = escape(&version).unwrap_or_default();
+        let mut pending = Vec::new();
+        for kind in [FileKind::Info, FileKind::Mod, FileKind::Zip] {
+            let Some(source) = files.file(kind) else {
+                continue;
+            };
+            let data = std::fs::read(source)?;
+            let stored = self
+                .index()
+                .get(module, &version)
+                .and_then(|v| v.file(kind).cloned());
+            match stored {
+                // .info files differ between go versions; the first one stays
+                Some(_) if kind == FileKind::Info => {}
+                Some(stored) if std::fs::read(&stored)? == data => {}
+                Some(_) => {
+                    report.mismatched.push((
+                        id,
+                        format!("{} is already stored with other content", kind.extension()),
+                    ));
+                    return Ok(());
+                }
+                None => {
+                    let target = dir.join(format!("{escaped_version}.{}", kind.extension()));
+                    pending.push((kind, target, data));
+                }
+            }
+        }
+        let mut index = self.index_mut();
+        for (kind, target, data) in pending {
+            write_atomic(&target, &data)?;
+            index.insert(module, &version, kind, target);
+        }
+
+        if checks
+            .iter()
+            .all(|(_, check, _)| *check == Verification::Verified)
+        {
+            report.verified.push(id);
+        } else {
+            report.unverified.push(id);
+        }
+        Ok(())
+    }
+}
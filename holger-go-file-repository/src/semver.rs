@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+
+/// A canonical module version, `vMAJOR.MINOR.PATCH[-prerelease][+build]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version<'a> {
+    numbers: [u64; 3],
+    prerelease: Option<&'a str>,
+    build: Option<&'a str>,
+}
+
+impl<'a> Version<'a> {
+    pub fn parse(version: &'a str) -> Option<Self> {
+        let rest = version.strip_prefix('v')?;
+        let (rest, build) = match rest.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (rest, None),
+        };
+        let (core, prerelease) = match rest.split_once('-') {
+            Some((core, prerelease)) => (core, Some(prerelease)),
+            None => (rest, None),
+        };
+        let mut numbers = [0; 3];
+        let mut parts = core.split('.');
+        for number in &mut numbers {
+            let part = parts.next()?;
+            if !is_numeric(part) || (part.len() > 1 && part.starts_with('0')) {
+                return None;
+            }
+            *number = part.parse().ok()?;
+        }
+        let identifiers_ok = |s: &str| {
+            s.split('.').all(|id| {
+                !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            })
+        };
+        if parts.next().is_some()
+            || !prerelease.is_none_or(identifiers_ok)
+            || !build.is_none_or(identifiers_ok)
+        {
+            return None;
+        }
+        Some(Version {
+            numbers,
+            prerelease,
+            build,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.prerelease.is_some()
+    }
+
+    /// Pseudo-versions name an untagged commit, e.g.
+    /// `v0.0.0-20191109021931-daa7c04131f5` or `v1.2.4-0.20191109021931-daa7c04131f5`.
+    pub fn is_pseudo(&self) -> bool {
+        let Some((head, revision)) = self.prerelease.and_then(|p| p.rsplit_once('-')) else {
+            return false;
+        };
+        let timestamp = head.rsplit_once('.').map_or(head, |(_, t)| t);
+        timestamp.len() == 14
+            && is_numeric(timestamp)
+            && !revision.is_empty()
+            && revision.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+}
+
+impl Ord for Version<'_> {
+    /// Semantic version precedence; build metadata such as `+incompatible`
+    /// only breaks ties.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.numbers
+            .cmp(&other.numbers)
+            .then_with(|| match (self.prerelease, other.prerelease) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            })
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for Version<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn is_numeric(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.split('.'), b.split('.'));
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(x), Some(y)) => match (is_numeric(x), is_numeric(y)) {
+                (true, true) => x.len().cmp(&y.len()).then_with(|| x.cmp(y)),
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => x.cmp(y),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
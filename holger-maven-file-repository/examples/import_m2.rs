@@ -0,0 +1,33 @@
+use holger_maven_file_repository::MavenRepoFile;
+
+/// cargo run --example import_m2 -- /var/lib/holger/maven-prod /airgap/java
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(root), Some(m2_dir)) = (args.next(), args.next()) else {
+        anyhow::bail!("usage: import_m2 <repository root> <local repository dir>");
+    };
+    let repo = MavenRepoFile::new(
+        "maven-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/maven-prod".into(),
+    )?;
+    let report = repo.import_m2(m2_dir.as_ref())?;
+
+    for id in &report.imported {
+        println!("imported {}", id.name);
+    }
+    for id in &report.existing {
+        println!("already stored {}", id.name);
+    }
+    for (path, reason) in &report.rejected {
+        println!("rejected {}: {}", path.display(), reason);
+    }
+    println!(
+        "{} imported, {} already stored, {} rejected, {} resolver files dropped",
+        report.imported.len(),
+        report.existing.len(),
+        report.rejected.len(),
+        report.dropped.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+use holger_traits::util::write_atomic;
+
+/// Directory below the repository root where computed checksums are cached,
+/// mirroring the layout path of the file they belong to.
+pub const CHECKSUM_CACHE_DIR: &str = ".checksums";
+
+/// Checksum sidecars Maven and Gradle request next to every file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 4] = [
+        Algorithm::Md5,
+        Algorithm::Sha1,
+        Algorithm::Sha256,
+        Algorithm::Sha512,
+    ];
+
+    /// File extension of the sidecar, e.g. `sha1` for `app-1.0.jar.sha1`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.extension() == extension)
+    }
+
+    /// Lowercase hex digest of `data`.
+    pub fn digest_hex(self, data: &[u8]) -> String {
+        match self {
+            Algorithm::Md5 => hex::encode(Md5::digest(data)),
+            Algorithm::Sha1 => hex::encode(Sha1::digest(data)),
+            Algorithm::Sha256 => hex::encode(Sha256::digest(data)),
+            Algorithm::Sha512 => hex::encode(Sha512::digest(data)),
+        }
+    }
+
+    fn hex_len(self) -> usize {
+        match self {
+            Algorithm::Md5 => 32,
+            Algorithm::Sha1 => 40,
+            Algorithm::Sha256 => 64,
+            Algorithm::Sha512 => 128,
+        }
+    }
+}
+
+/// Split `app-1.0.jar.sha1` into `app-1.0.jar` and its algorithm.
+pub fn split_sidecar(filename: &str) -> Option<(&str, Algorithm)> {
+    let (base, extension) = filename.rsplit_once('.')?;
+    Some((base, Algorithm::from_extension(extension)?))
+}
+
+/// The digest in a sidecar file. Besides the bare hex digest, accepts the
+/// `<digest>  <file>` form of `sha1sum` and the `SHA1 (<file>) = <digest>` one.
+pub fn parse_sidecar(content: &[u8], algorithm: Algorithm) -> Option<String> {
+    std::str::from_utf8(content)
+        .ok()?
+        .split_whitespace()
+        .find(|token| {
+            token.len() == algorithm.hex_len() && token.bytes().all(|b| b.is_ascii_hexdigit())
+        })
+        .map(str::to_ascii_lowercase)
+}
+
+/// Digest of the file at `path`, whose layout path is `rel`, from the cache
+/// below `cache_dir` while the cached digest is newer than the file;
+/// computed and cached otherwise.
+pub fn cached_digest(
+    cache_dir: &Path,
+    rel: &str,
+    path: &Path,
+    algorithm: Algorithm,
+) -> anyhow::Result<String> {
+    let cached = cache_dir.join(format!("{rel}.{}", algorithm.extension()));
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    if let (Some(cache_time), Some(file_time)) = (modified(&cached), modified(path)) {
+        if cache_time >= file_time {
+            if let Some(digest) = parse_sidecar(&std::fs::read(&cached)?, algorithm) {
+                return Ok(digest);
+            }
+        }
+    }
+    let digest = algorithm.digest_hex(&std::fs::read(path)?);
+    write_atomic(&cached, digest.as_bytes())?;
+    Ok(digest)
+}
+
+/// Layout paths of artifacts whose stored sidecars disagree with their
+/// content, with the reason. `files` maps layout paths to stored files; the
+/// rejected artifacts' sidecars are included so callers can drop them too.
+///
+/// With a `cache_dir`, digests come from [`cached_digest`], so files that
+/// were verified before are not read again. Files not yet published must be
+/// checked without one, since the cache is keyed by layout path.
+pub fn mismatched_sidecars(
+    files: &BTreeMap<String, PathBuf>,
+    cache_dir: Option<&Path>,
+) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut sidecars: BTreeMap<&str, Vec<(Algorithm, &str)>> = BTreeMap::new();
+    for rel in files.keys() {
+        if let Some((base, algorithm)) = split_sidecar(rel) {
+            if files.contains_key(base) {
+                sidecars.entry(base).or_default().push((algorithm, rel));
+            }
+        }
+    }
+
+    let mut rejected = BTreeMap::new();
+    for (base, checks) in sidecars {
+        let mut data = None;
+        let mut reasons = BTreeSet::new();
+        for (algorithm, sidecar) in &checks {
+            let expected = parse_sidecar(&std::fs::read(&files[*sidecar])?, *algorithm);
+            let actual = match cache_dir {
+                Some(cache_dir) => cached_digest(cache_dir, base, &files[base], *algorithm)?,
+                None => {
+                    if data.is_none() {
+                        data = Some(std::fs::read(&files[base])?);
+                    }
+                    algorithm.digest_hex(data.as_deref().unwrap_or_default())
+                }
+            };
+            match expected {
+                Some(expected) if expected == actual => {}
+                Some(expected) => {
+                    reasons.insert(format!(
+                        "{} mismatch: {sidecar} says {expected}, content is {actual}",
+                        algorithm.extension()
+                    ));
+                }
+                None => {
+                    reasons.insert(format!(
+                        "{sidecar} holds no {} digest",
+                        algorithm.extension()
+                    ));
+                }
+            }
+        }
+        if !reasons.is_empty() {
+            let reason = reasons.into_iter().collect::<Vec<_>>().join("; ");
+            for (_, sidecar) in &checks {
+                rejected.insert(sidecar.to_string(), reason.clone());
+            }
+            rejected.insert(base.to_string(), reason);
+        }
+    }
+    Ok(rejected)
+}
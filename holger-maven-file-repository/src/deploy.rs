@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use crate::index::StoredFile;
+use crate::layout::artifact_dir;
+
+/// Directory below the repository root where deployed files wait for their
+/// release to be committed, mirroring their layout paths.
+pub const STAGING_DIR: &str = ".staging";
+
+/// Files of deploys in progress, keyed by layout path. They stay out of the
+/// index until the deploy uploads `maven-metadata.xml`, which Maven does after
+/// every artifact file of the release.
+#[derive(Debug, Default)]
+pub struct Staging {
+    files: BTreeMap<String, StoredFile>,
+}
+
+impl Staging {
+    /// Add a staged file, replacing an earlier upload of the same path.
+    pub fn insert(&mut self, file: StoredFile) {
+        self.files.insert(file.artifact.relative_path(), file);
+    }
+
+    pub fn get(&self, relative_path: &str) -> Option<&StoredFile> {
+        self.files.get(relative_path)
+    }
+
+    /// Remove and return the release a metadata upload at `dir` completes:
+    /// every staged version of the artifact for `group/artifact`, or one
+    /// version for `group/artifact/version`.
+    pub fn take(&mut self, dir: &str) -> Vec<StoredFile> {
+        let (taken, kept) = std::mem::take(&mut self.files)
+            .into_iter()
+            .partition(|(_, f)| {
+                let artifact = artifact_dir(&f.artifact.group_id, &f.artifact.artifact_id);
+                artifact == dir || format!("{artifact}/{}", f.artifact.version) == dir
+            });
+        self.files = kept;
+        taken.into_values().collect()
+    }
+
+    /// Remove and return every staged file of one version.
+    pub fn take_version(
+        &mut self,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+    ) -> Vec<StoredFile> {
+        self.take(&format!(
+            "{}/{version}",
+            artifact_dir(group_id, artifact_id)
+        ))
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::PathBuf;
+
+use crate::layout::{artifact_dir, ArtifactPath};
+
+/// One stored repository file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredFile {
+    pub artifact: ArtifactPath,
+    pub path: PathBuf,
+}
+
+/// In-memory view of the stored files, keyed by layout path relative to the root.
+#[derive(Debug, Default)]
+pub struct MavenIndex {
+    files: BTreeMap<String, StoredFile>,
+}
+
+impl MavenIndex {
+    /// Add a file, replacing an earlier one at the same path.
+    pub fn insert(&mut self, file: StoredFile) {
+        self.files.insert(file.artifact.relative_path(), file);
+    }
+
+    /// Look up a file by its layout path, e.g. `com/example/app/1.0/app-1.0.jar`.
+    pub fn get(&self, relative_path: &str) -> Option<&StoredFile> {
+        self.files.get(relative_path)
+    }
+
+    /// Every stored file of one artifact, across all versions, in path order.
+    pub fn artifact_files(&self, group_id: &str, artifact_id: &str) -> Vec<&StoredFile> {
+        let dir = format!("{}/", artifact_dir(group_id, artifact_id));
+        self.files
+            .range::<str, _>((Bound::Included(dir.as_str()), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&dir))
+            .map(|(_, file)| file)
+            // Skip nested artifacts whose groupId continues with this artifactId
+            .filter(|f| f.artifact.group_id == group_id && f.artifact.artifact_id == artifact_id)
+            .collect()
+    }
+
+    /// Every stored file of the artifacts directly in one group, in path order.
+    pub fn group_files(&self, group_id: &str) -> Vec<&StoredFile> {
+        let dir = format!("{}/", group_id.replace('.', "/"));
+        self.files
+            .range::<str, _>((Bound::Included(dir.as_str()), Bound::Unbounded))
+            .take_while(|(key, _)| key.starts_with(&dir))
+            .map(|(_, file)| file)
+            .filter(|f| f.artifact.group_id == group_id)
+            .collect()
+    }
+
+    /// Stored files of one artifact version.
+    pub fn version_files(
+        &self,
+        group_id: &str,
+        artifact_id: &str,
+        version: &str,
+    ) -> Vec<&StoredFile> {
+        self.artifact_files(group_id, artifact_id)
+            .into_iter()
+            .filter(|f| f.artifact.version == version)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StoredFile> {
+        self.files.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
@@ -0,0 +1,132 @@
+use holger_traits::ArtifactId;
+
+/// Version suffix of Maven snapshot versions.
+pub const SNAPSHOT_SUFFIX: &str = "-SNAPSHOT";
+
+/// A file in the standard repository layout:
+/// `groupId/artifactId/version/artifactId-version[-classifier].ext`, with the
+/// groupId's dots turned into directories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactPath {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Version directory, e.g. `1.0-SNAPSHOT`
+    pub version: String,
+    /// Version as written in the file name; `1.0-20240101.120000-1` for timestamped snapshots
+    pub file_version: String,
+    pub classifier: Option<String>,
+    /// Everything after the first dot, e.g. `jar`, `tar.gz` or `jar.sha1`
+    pub extension: String,
+    pub filename: String,
+}
+
+impl ArtifactPath {
+    /// Parse a `/`-separated path relative to the repository root.
+    pub fn parse(path: &str) -> Option<Self> {
+        let segments: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        Self::from_segments(&segments)
+    }
+
+    /// Parse path segments: at least one group segment, then artifactId,
+    /// version and file name.
+    pub fn from_segments(segments: &[&str]) -> Option<Self> {
+        if segments.len() < 4 || !segments.iter().all(|s| valid_segment(s)) {
+            return None;
+        }
+        let [group @ .., artifact_id, version, filename] = segments else {
+            return None;
+        };
+        let rest = filename.strip_prefix(artifact_id)?.strip_prefix('-')?;
+        let file_version = file_version(rest, version)?;
+        let rest = &rest[file_version.len()..];
+
+        let (classifier, extension) = match rest.strip_prefix('-') {
+            Some(tail) => {
+                let (classifier, extension) = tail.split_once('.')?;
+                (Some(classifier), extension)
+            }
+            None => (None, rest.strip_prefix('.')?),
+        };
+        if classifier.is_some_and(str::is_empty) || extension.is_empty() {
+            return None;
+        }
+        Some(ArtifactPath {
+            group_id: group.join("."),
+            artifact_id: artifact_id.to_string(),
+            version: version.to_string(),
+            file_version: file_version.to_string(),
+            classifier: classifier.map(str::to_string),
+            extension: extension.to_string(),
+            filename: filename.to_string(),
+        })
+    }
+
+    /// `com/example/app/1.0/app-1.0.jar`
+    pub fn relative_path(&self) -> String {
+        format!(
+            "{}/{}/{}",
+            artifact_dir(&self.group_id, &self.artifact_id),
+            self.version,
+            self.filename
+        )
+    }
+
+    /// `ArtifactId { namespace: Some(groupId), name: filename, version }`
+    pub fn artifact_id(&self) -> ArtifactId {
+        ArtifactId {
+            namespace: Some(self.group_id.clone()),
+            name: self.filename.clone(),
+            version: self.version.clone(),
+        }
+    }
+
+    pub fn is_snapshot(&self) -> bool {
+        self.version.ends_with(SNAPSHOT_SUFFIX)
+    }
+}
+
+/// `com/example/app`: the directory holding every version of an artifact.
+pub fn artifact_dir(group_id: &str, artifact_id: &str) -> String {
+    format!("{}/{}", group_id.replace('.', "/"), artifact_id)
+}
+
+/// The version at the start of `rest`: the directory version itself or, for
+/// snapshots, a `base-yyyyMMdd.HHmmss-build` timestamped version.
+fn file_version<'a>(rest: &'a str, version: &str) -> Option<&'a str> {
+    if rest.starts_with(version) {
+        return Some(&rest[..version.len()]);
+    }
+    let base = version.strip_suffix(SNAPSHOT_SUFFIX)?;
+    let stamp = rest.strip_prefix(base)?.strip_prefix('-')?;
+    let (date, stamp) = stamp.split_at_checked(8)?;
+    let (time, stamp) = stamp.strip_prefix('.')?.split_at_checked(6)?;
+    let build = stamp.strip_prefix('-')?;
+    let build_len = build
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(build.len());
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(date) || !digits(time) || build_len == 0 {
+        return None;
+    }
+    // base, '-', date, '.', time, '-', build
+    Some(&rest[..base.len() + 1 + 8 + 1 + 6 + 1 + build_len])
+}
+
+/// Path segments Holger will map onto storage.
+fn valid_segment(segment: &str) -> bool {
+    !segment.is_empty() && !segment.starts_with('.') && !segment.contains('\\')
+}
+
+/// Content type for a repository file, by its last extension.
+pub fn content_type(filename: &str) -> &'static str {
+    match filename.rsplit('.').next().unwrap_or_default() {
+        "pom" | "xml" => "application/xml",
+        "jar" | "war" | "ear" => "application/java-archive",
+        "module" | "json" => "application/json",
+        "md5" | "sha1" | "sha256" | "sha512" => "text/plain",
+        "asc" => "application/pgp-signature",
+        "zip" => "application/zip",
+        "gz" | "tgz" => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
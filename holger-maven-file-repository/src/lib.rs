@@ -0,0 +1,1419 @@
+use holger_traits::util::{find_files, relative_path, write_atomic};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub mod checksum;
+pub mod deploy;
+pub mod index;
+pub mod layout;
+pub mod m2;
+pub mod metadata;
+pub mod pom;
+pub mod version;
+
+use anyhow::bail;
+use checksum::{
+    cached_digest, mismatched_sidecars, parse_sidecar, split_sidecar, CHECKSUM_CACHE_DIR,
+};
+use deploy::{Staging, STAGING_DIR};
+use index::{MavenIndex, StoredFile};
+use layout::{content_type, ArtifactPath, SNAPSHOT_SUFFIX};
+use metadata::{
+    artifact_metadata_xml, group_metadata_xml, resolve_snapshot, snapshot_metadata_xml,
+    PluginPrefix, METADATA_FILE,
+};
+use pom::{default_goal_prefix, Pom};
+use version::compare_versions;
+
+/// Maven 3 repository backed by a standard-layout directory tree.
+///
+/// Artifacts are addressed by group and file name: `ArtifactId { namespace:
+/// Some("com.google.guava"), name: "guava-33.0.0-jre.jar", version: "33.0.0-jre" }`.
+pub struct MavenRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/maven-prod
+    /// Accept `mvn deploy`
+    pub writable: bool,
+    pub index: RwLock<MavenIndex>,
+    pub staging: Mutex<Staging>,
+}
+
+impl MavenRepoFile {
+    /// Open a repository rooted at `root`, indexing every layout file below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = MavenRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            writable: false,
+            index: RwLock::new(MavenIndex::default()),
+            staging: Mutex::new(Staging::default()),
+        };
+        // Deploys interrupted by a restart never complete
+        let staging_dir = repo.root.join(STAGING_DIR);
+        if staging_dir.exists() {
+            std::fs::remove_dir_all(&staging_dir)?;
+        }
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the files on disk. Artifacts whose
+    /// checksum sidecars disagree with their content are left out, sidecars
+    /// included. Each file is hashed once; later loads use the cached digests.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let found: BTreeMap<String, PathBuf> = find_files(&self.root)?.into_iter().collect();
+        let rejected = mismatched_sidecars(&found, Some(&self.root.join(CHECKSUM_CACHE_DIR)))?;
+        let mut index = MavenIndex::default();
+        for (rel, path) in found {
+            if let Some(reason) = rejected.get(&rel) {
+                println!("Rejecting {}: {}", rel, reason);
+                continue;
+            }
+            match ArtifactPath::parse(&rel) {
+                Some(artifact) => index.insert(StoredFile { artifact, path }),
+                None => println!("Skipping non-layout file {}", rel),
+            }
+        }
+        self.artifacts = index.iter().map(|f| f.artifact.artifact_id()).collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, MavenIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, MavenIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn staging(&self) -> MutexGuard<'_, Staging> {
+        self.staging.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether `version` is a release that already has committed files.
+    /// Releases are immutable; SNAPSHOT versions may be deployed again.
+    pub fn is_released(&self, group_id: &str, artifact_id: &str, version: &str) -> bool {
+        !version.ends_with(SNAPSHOT_SUFFIX)
+            && !self
+                .index()
+                .version_files(group_id, artifact_id, version)
+                .is_empty()
+    }
+
+    /// Stage one deployed file until its release is committed. An uploaded
+    /// checksum is verified against the staged file it belongs to; on a
+    /// mismatch the whole staged version is dropped.
+    pub fn stage(&self, artifact: &ArtifactPath, data: &[u8]) -> anyhow::Result<()> {
+        let rel = artifact.relative_path();
+        let mut staging = self.staging();
+        if let Some((base_name, algorithm)) = split_sidecar(&artifact.filename) {
+            let (dir, _) = rel.rsplit_once('/').unwrap_or_default();
+            if let Some(base) = staging.get(&format!("{dir}/{base_name}")) {
+                let actual = algorithm.digest_hex(&std::fs::read(&base.path)?);
+                if parse_sidecar(data, algorithm).as_deref() != Some(actual.as_str()) {
+                    discard(staging.take_version(
+                        &artifact.group_id,
+                        &artifact.artifact_id,
+                        &artifact.version,
+                    ));
+                    bail!(
+                        "{} does not match the uploaded {}, whose {} is {}",
+                        artifact.filename,
+                        base_name,
+                        algorithm.extension(),
+                        actual
+                    );
+                }
+            }
+        }
+        let path = self.root.join(STAGING_DIR).join(&rel);
+        write_atomic(&path, data)?;
+        staging.insert(StoredFile {
+            artifact: artifact.clone(),
+            path,
+        });
+        Ok(())
+    }
+
+    /// Publish the staged release a `maven-metadata.xml` upload at `dir`
+    /// completes, all files at once, and return how many there were. Nothing
+    /// is published if a staged checksum mismatches or a release already exists.
+    pub fn commit(&self, dir: &str) -> anyhow::Result<usize> {
+        let staged = self.staging().take(dir);
+        let paths: BTreeMap<String, PathBuf> = staged
+            .iter()
+            .map(|f| (f.artifact.relative_path(), f.path.clone()))
+            .collect();
+        if let Some((rel, reason)) = mismatched_sidecars(&paths, None)?.pop_first() {
+            discard(staged);
+            bail!("rejecting {}: {}", rel, reason);
+        }
+
+        // Hold the write lock across the release check, the moves and the index update
+        let mut index = self.index_mut();
+        let released = staged.iter().find(|f| {
+            !f.artifact.is_snapshot()
+                && !index
+                    .version_files(
+                        &f.artifact.group_id,
+                        &f.artifact.artifact_id,
+                        &f.artifact.version,
+                    )
+                    .is_empty()
+        });
+        if let Some(file) = released {
+            let message = format!(
+                "{}:{}:{} is already released",
+                file.artifact.group_id, file.artifact.artifact_id, file.artifact.version
+            );
+            discard(staged);
+            bail!(message);
+        }
+        let count = staged.len();
+        Self::publish(&self.root, &mut index, staged)?;
+        Ok(count)
+    }
+
+    /// Move staged files to their layout paths and index them. Callers hold
+    /// the index write lock, so readers see all of them or none.
+    fn publish(root: &Path, index: &mut MavenIndex, staged: Vec<StoredFile>) -> anyhow::Result<()> {
+        for file in staged {
+            let path = root.join(file.artifact.relative_path());
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(&file.path, &path)?;
+            index.insert(StoredFile {
+                artifact: file.artifact,
+                path,
+            });
+        }
+        Ok(())
+    }
+
+    /// Layout path of an artifact id. The artifactId is the part of the file
+    /// name before its version.
+    pub fn artifact_path(id: &ArtifactId) -> Option<ArtifactPath> {
+        let group = id.namespace.as_deref()?;
+        let base = id
+            .version
+            .strip_suffix(SNAPSHOT_SUFFIX)
+            .unwrap_or(&id.version);
+        let artifact_id = &id.name[..id.name.find(&format!("-{base}"))?];
+        let mut segments: Vec<&str> = group.split('.').collect();
+        segments.extend([artifact_id, id.version.as_str(), id.name.as_str()]);
+        ArtifactPath::from_segments(&segments)
+    }
+
+    /// Generated `maven-metadata.xml` for a directory below the repository:
+    /// version level for `.../artifactId/1.0-SNAPSHOT`, artifact level for
+    /// `.../artifactId`, and group level, listing plugin prefixes, for a
+    /// group without an artifact of that name.
+    pub fn metadata_xml(&self, dir: &[&str]) -> anyhow::Result<Option<String>> {
+        let index = self.index();
+        if let [group @ .., artifact_id, version] = dir {
+            if !group.is_empty() && version.ends_with(SNAPSHOT_SUFFIX) {
+                let group_id = group.join(".");
+                let files = index.version_files(&group_id, artifact_id, version);
+                if !files.is_empty() {
+                    return Ok(snapshot_metadata_xml(
+                        &group_id,
+                        artifact_id,
+                        version,
+                        &files,
+                    ));
+                }
+            }
+        }
+        if let [group @ .., artifact_id] = dir {
+            if !group.is_empty() {
+                let group_id = group.join(".");
+                let files = index.artifact_files(&group_id, artifact_id);
+                if let Some(xml) = artifact_metadata_xml(&group_id, artifact_id, &files) {
+                    return Ok(Some(xml));
+                }
+            }
+        }
+        let plugins = Self::group_plugins(&index, &dir.join("."))?;
+        Ok(group_metadata_xml(&plugins))
+    }
+
+    /// Goal prefixes of the `maven-plugin` artifacts in a group, read from
+    /// the pom of each artifact's newest version.
+    fn group_plugins(index: &MavenIndex, group_id: &str) -> anyhow::Result<Vec<PluginPrefix>> {
+        let mut newest: BTreeMap<&str, &StoredFile> = BTreeMap::new();
+        for file in index.group_files(group_id) {
+            let artifact = &file.artifact;
+            if artifact.extension != "pom" || artifact.classifier.is_some() {
+                continue;
+            }
+            if newest
+                .get(artifact.artifact_id.as_str())
+                .is_none_or(|current| {
+                    compare_versions(&artifact.file_version, &current.artifact.file_version).is_gt()
+                })
+            {
+                newest.insert(&artifact.artifact_id, file);
+            }
+        }
+        let mut plugins = Vec::new();
+        for (artifact_id, file) in newest {
+            let pom = Pom::parse(&String::from_utf8_lossy(&std::fs::read(&file.path)?));
+            if pom.is_plugin() {
+                plugins.push(PluginPrefix {
+                    prefix: pom
+                        .goal_prefix
+                        .unwrap_or_else(|| default_goal_prefix(artifact_id)),
+                    artifact_id: artifact_id.to_string(),
+                    name: pom.name,
+                });
+            }
+        }
+        Ok(plugins)
+    }
+
+    /// Stored file for a layout path. `artifactId-1.0-SNAPSHOT.jar` resolves to
+    /// the newest timestamped jar when it is not stored under that name.
+    pub fn resolve(&self, artifact: &ArtifactPath) -> Option<PathBuf> {
+        let index = self.index();
+        if let Some(file) = index.get(&artifact.relative_path()) {
+            return Some(file.path.clone());
+        }
+        if !artifact.is_snapshot() || artifact.file_version != artifact.version {
+            return None;
+        }
+        let files =
+            index.version_files(&artifact.group_id, &artifact.artifact_id, &artifact.version);
+        resolve_snapshot(&files, artifact.classifier.as_deref(), &artifact.extension)
+            .map(|f| f.path.clone())
+    }
+
+    /// Content of a checksum sidecar such as `app-1.0.jar.sha1`: the stored
+    /// sidecar of the resolved file if there is one, else a computed digest.
+    pub fn checksum(&self, sidecar: &ArtifactPath) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some((base_name, algorithm)) = split_sidecar(&sidecar.filename) else {
+            return Ok(None);
+        };
+        let rel = sidecar.relative_path();
+        if let Some(file) = self.index().get(&rel) {
+            return Ok(Some(std::fs::read(&file.path)?));
+        }
+        let (dir, _) = rel.rsplit_once('/').unwrap_or_default();
+        let Some(base) = ArtifactPath::parse(&format!("{dir}/{base_name}")) else {
+            return Ok(None);
+        };
+        let Some(path) = self.resolve(&base) else {
+            return Ok(None);
+        };
+        let base_rel = relative_path(&self.root, &path);
+        let stored = format!("{base_rel}.{}", algorithm.extension());
+        if let Some(file) = self.index().get(&stored) {
+            return Ok(Some(std::fs::read(&file.path)?));
+        }
+
+        // Computed digests are cached until the file changes
+        let cache_dir = self.root.join(CHECKSUM_CACHE_DIR);
+        let digest = cached_digest(&cache_dir, &base_rel, &path, algorithm)?;
+        Ok(Some(digest.into_bytes()))
+    }
+}
+
+/// Remove staged files that will not be committed.
+fn discard(files: Vec<StoredFile>) {
+    for file in files {
+        let _ = std::fs::remove_file(&file.path);
+    }
+}
+
+impl RepositoryBackendTrait for MavenRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        _headers: &[(String, String)],
+        body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Maven repo handle_http2_request.suburl={}", suburl);
+
+        let (path, _query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+        match parts.as_slice() {
+            // Repository metadata → /maven-prod/com/google/guava/guava/maven-metadata.xml[.sha1]
+            [repo, dir @ .., file]
+                if *repo == self.name()
+                    && (*file == METADATA_FILE
+                        || split_sidecar(file).is_some_and(|(base, _)| base == METADATA_FILE)) =>
+            {
+                if method == "PUT" {
+                    if !self.is_writable() {
+                        return Ok((403, Vec::new(), b"Repository is read-only".to_vec()));
+                    }
+                    // Metadata is generated; the upload only marks the release as complete
+                    if *file == METADATA_FILE {
+                        if let Err(e) = self.commit(&dir.join("/")) {
+                            return Ok((400, Vec::new(), format!("{e:#}").into_bytes()));
+                        }
+                    }
+                    return Ok((200, Vec::new(), b"OK".to_vec()));
+                }
+                if method != "GET" && method != "HEAD" {
+                    return Ok((405, Vec::new(), b"Method not allowed".to_vec()));
+                }
+                let Some(xml) = self.metadata_xml(dir)? else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                let body = match split_sidecar(file) {
+                    Some((_, algorithm)) => algorithm.digest_hex(xml.as_bytes()),
+                    None => xml,
+                };
+                Ok((
+                    200,
+                    vec![("Content-Type".into(), content_type(file).into())],
+                    body.into_bytes(),
+                ))
+            }
+
+            // Layout file → /maven-prod/com/google/guava/guava/33.0.0-jre/guava-33.0.0-jre.jar
+            [repo, rest @ ..] if *repo == self.name() && rest.len() >= 4 => {
+                if method == "PUT" {
+                    if !self.is_writable() {
+                        return Ok((403, Vec::new(), b"Repository is read-only".to_vec()));
+                    }
+                    let Some(artifact) = ArtifactPath::from_segments(rest) else {
+                        return Ok((400, Vec::new(), b"Not a repository layout path".to_vec()));
+                    };
+                    if self.is_released(
+                        &artifact.group_id,
+                        &artifact.artifact_id,
+                        &artifact.version,
+                    ) {
+                        let message = format!(
+                            "{}:{}:{} is already released",
+                            artifact.group_id, artifact.artifact_id, artifact.version
+                        );
+                        return Ok((409, Vec::new(), message.into_bytes()));
+                    }
+                    println!("Deploy request: file={}", artifact.relative_path());
+                    return match self.stage(&artifact, body) {
+                        Ok(()) => Ok((200, Vec::new(), b"OK".to_vec())),
+                        Err(e) => Ok((400, Vec::new(), format!("{e:#}").into_bytes())),
+                    };
+                }
+                if method != "GET" && method != "HEAD" {
+                    return Ok((405, Vec::new(), b"Method not allowed".to_vec()));
+                }
+                let Some(artifact) = ArtifactPath::from_segments(rest) else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                let body = match split_sidecar(&artifact.filename) {
+                    Some(_) => self.checksum(&artifact)?,
+                    None => self.resolve(&artifact).map(std::fs::read).transpose()?,
+                };
+                match body {
+                    Some(body) => Ok((
+                        200,
+                        vec![(
+                            "Content-Type".into(),
+                            content_type(&artifact.filename).into(),
+                        )],
+                        body,
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                Ok((404, Vec::new(), b"Not found".to_vec()))
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Maven3
+    }
+
+    fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        let Some(artifact) = Self::artifact_path(id) else {
+            return Ok(None);
+        };
+        let path = self
+            .index()
+            .get(&artifact.relative_path())
+            .map(|f| f.path.clone());
+        match path {
+            Some(path) => Ok(Some(std::fs::read(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Deploy a single file as its own release, committed right away.
+    fn put(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
+        if !self.writable {
+            bail!("repository {} is read-only", self.name);
+        }
+        let Some(artifact) = Self::artifact_path(id) else {
+            bail!("`{}` is not a file of version {}", id.name, id.version);
+        };
+        if self.is_released(&artifact.group_id, &artifact.artifact_id, &artifact.version) {
+            bail!(
+                "{}:{}:{} is already released",
+                artifact.group_id,
+                artifact.artifact_id,
+                artifact.version
+            );
+        }
+        self.stage(&artifact, data)?;
+        let dir = artifact.relative_path();
+        let (dir, _) = dir.rsplit_once('/').unwrap_or_default();
+        self.commit(dir)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checksum::{parse_sidecar, Algorithm};
+    use std::path::Path;
+
+    fn store(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    /// Store a file with a fixed mtime, so generated `lastUpdated` values are stable.
+    fn store_at(root: &Path, rel: &str, data: &[u8], secs: u64) {
+        store(root, rel, data);
+        std::fs::File::options()
+            .write(true)
+            .open(root.join(rel))
+            .unwrap()
+            .set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    fn get(repo: &MavenRepoFile, suburl: &str) -> Http2Response {
+        repo.handle_http2_request("GET", suburl, &[], b"").unwrap()
+    }
+
+    fn put(repo: &MavenRepoFile, suburl: &str, body: &[u8]) -> u16 {
+        repo.handle_http2_request("PUT", suburl, &[], body)
+            .unwrap()
+            .0
+    }
+
+    /// The PUTs `mvn deploy` sends for a jar and its pom, metadata last.
+    fn deploy(repo: &MavenRepoFile, dir: &str, base: &str, jar: &[u8]) -> Vec<u16> {
+        let pom = b"<project/>";
+        let mut statuses = Vec::new();
+        for (ext, data) in [("jar", jar), ("pom", &pom[..])] {
+            statuses.push(put(repo, &format!("/maven-prod/{dir}/{base}.{ext}"), data));
+            for algorithm in [Algorithm::Sha1, Algorithm::Md5] {
+                let url = format!("/maven-prod/{dir}/{base}.{ext}.{}", algorithm.extension());
+                statuses.push(put(repo, &url, algorithm.digest_hex(data).as_bytes()));
+            }
+        }
+        let (artifact_dir, _) = dir.rsplit_once('/').unwrap();
+        for metadata_dir in [dir, artifact_dir] {
+            let url = format!("/maven-prod/{metadata_dir}/maven-metadata.xml");
+            statuses.push(put(repo, &url, b"<metadata/>"));
+            statuses.push(put(repo, &format!("{url}.sha1"), b"ignored"));
+        }
+        statuses
+    }
+
+    #[test]
+    fn parses_layout_paths() {
+        let jar =
+            ArtifactPath::parse("com/google/guava/guava/33.0.0-jre/guava-33.0.0-jre.jar").unwrap();
+        assert_eq!(jar.group_id, "com.google.guava");
+        assert_eq!(jar.artifact_id, "guava");
+        assert_eq!(jar.version, "33.0.0-jre");
+        assert_eq!(jar.classifier, None);
+        assert_eq!(jar.extension, "jar");
+
+        let sources =
+            ArtifactPath::parse("org/slf4j/slf4j-api/2.0.13/slf4j-api-2.0.13-sources.jar.sha1")
+                .unwrap();
+        assert_eq!(sources.classifier.as_deref(), Some("sources"));
+        assert_eq!(sources.extension, "jar.sha1");
+
+        let dist = ArtifactPath::parse("org/example/app/1.0/app-1.0-bin.tar.gz").unwrap();
+        assert_eq!(dist.classifier.as_deref(), Some("bin"));
+        assert_eq!(dist.extension, "tar.gz");
+
+        let snapshot =
+            ArtifactPath::parse("org/example/app/1.1-SNAPSHOT/app-1.1-20240102.030405-7-tests.jar")
+                .unwrap();
+        assert!(snapshot.is_snapshot());
+        assert_eq!(snapshot.file_version, "1.1-20240102.030405-7");
+        assert_eq!(snapshot.classifier.as_deref(), Some("tests"));
+        assert_eq!(
+            ArtifactPath::parse("org/example/app/1.1-SNAPSHOT/app-1.1-SNAPSHOT.pom")
+                .unwrap()
+                .file_version,
+            "1.1-SNAPSHOT"
+        );
+
+        // Not layout paths
+        assert_eq!(ArtifactPath::parse("app/1.0/app-1.0.jar"), None);
+        assert_eq!(
+            ArtifactPath::parse("org/example/app/1.0/other-1.0.jar"),
+            None
+        );
+        assert_eq!(ArtifactPath::parse("org/example/app/1.0/app-2.0.jar"), None);
+        assert_eq!(ArtifactPath::parse("org/example/app/1.0/app-1.0"), None);
+        assert_eq!(ArtifactPath::parse("org/../app/1.0/app-1.0.jar"), None);
+        assert_eq!(
+            ArtifactPath::parse("org/example/app/1.0-SNAPSHOT/app-1.0-2024.jar"),
+            None
+        );
+    }
+
+    #[test]
+    fn serves_standard_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        store(root, "org/example/app/1.0/app-1.0.jar", b"jar bytes");
+        store(root, "org/example/app/1.0/app-1.0.pom", b"<project/>");
+        store(root, "org/example/app/1.0/_remote.repositories", b"noise");
+
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+        assert_eq!(repo.artifacts.len(), 2);
+
+        let (status, headers, body) = get(&repo, "/maven-prod/org/example/app/1.0/app-1.0.jar");
+        assert_eq!(status, 200);
+        assert_eq!(body, b"jar bytes");
+        assert_eq!(
+            headers,
+            vec![(
+                "Content-Type".to_string(),
+                "application/java-archive".to_string()
+            )]
+        );
+        let (status, headers, _) = get(&repo, "/maven-prod/org/example/app/1.0/app-1.0.pom");
+        assert_eq!(status, 200);
+        assert_eq!(headers[0].1, "application/xml");
+
+        for missing in [
+            "/maven-prod/org/example/app/1.0/app-1.0-sources.jar",
+            "/maven-prod/org/example/app/1.0/_remote.repositories",
+            "/maven-prod/org/example/app/1.0/../1.0/app-1.0.jar",
+            "/maven-prod/app-1.0.jar",
+        ] {
+            assert_eq!(get(&repo, missing).0, 404, "{missing}");
+        }
+        let (status, _, _) = repo
+            .handle_http2_request(
+                "DELETE",
+                "/maven-prod/org/example/app/1.0/app-1.0.jar",
+                &[],
+                b"",
+            )
+            .unwrap();
+        assert_eq!(status, 405);
+
+        let id = ArtifactId {
+            namespace: Some("org.example".into()),
+            name: "app-1.0.pom".into(),
+            version: "1.0".into(),
+        };
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), b"<project/>");
+        assert!(repo.put(&id, b"<project/>").is_err());
+    }
+
+    #[test]
+    fn orders_versions_like_maven() {
+        use std::cmp::Ordering::*;
+        use version::compare_versions;
+
+        let ordered = [
+            "1.0-alpha-1",
+            "1.0-beta2",
+            "1.0-M3",
+            "1.0-rc1",
+            "1.0-SNAPSHOT",
+            "1.0",
+            "1.0-sp1",
+            "1.0-zeta",
+            "1.0.1",
+            "1.2",
+            "1.10",
+            "2.0-android",
+            "2.0-jre",
+        ];
+        for pair in ordered.windows(2) {
+            assert_eq!(compare_versions(pair[0], pair[1]), Less, "{pair:?}");
+            assert_eq!(compare_versions(pair[1], pair[0]), Greater, "{pair:?}");
+        }
+        assert_eq!(compare_versions("1.0", "1"), Equal);
+        assert_eq!(compare_versions("1.0.0", "1-ga"), Equal);
+        assert_eq!(compare_versions("1.0-final", "1.0"), Equal);
+        assert_eq!(compare_versions("007", "7"), Equal);
+    }
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(metadata::format_utc(0), "19700101000000");
+        assert_eq!(metadata::format_utc(951_782_400), "20000229000000");
+        assert_eq!(metadata::format_utc(1_704_164_645), "20240102030405");
+    }
+
+    #[test]
+    fn generates_metadata_and_resolves_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let jan_2 = 1_704_164_645; // 2024-01-02 03:04:05 UTC
+        for version in ["1.10", "1.2", "1.0"] {
+            store_at(
+                root,
+                &format!("org/example/app/{version}/app-{version}.pom"),
+                b"<project/>",
+                jan_2 - 86_400,
+            );
+        }
+        let snapshot_dir = "org/example/app/2.0-SNAPSHOT";
+        for (file, data) in [
+            ("app-2.0-20240101.100000-1.jar", &b"build 1"[..]),
+            ("app-2.0-20240101.100000-1.pom", b"<project/>"),
+            ("app-2.0-20240102.030405-2.jar", b"build 2"),
+            (
+                "app-2.0-20240102.030405-2.jar.sha1",
+                b"da2beec7e54fa9e0c1b9be227f30dd7256d42b89",
+            ),
+            ("app-2.0-20240102.030405-2.pom", b"<project/>"),
+            ("app-2.0-20240101.100000-1-tests.jar", b"tests 1"),
+        ] {
+            store_at(root, &format!("{snapshot_dir}/{file}"), data, jan_2 - 3_600);
+        }
+        // A nested artifact whose groupId continues with `app`
+        store_at(
+            root,
+            "org/example/app/plugin/3.0/plugin-3.0.pom",
+            b"<project/>",
+            jan_2,
+        );
+
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+
+        let (status, headers, body) = get(&repo, "/maven-prod/org/example/app/maven-metadata.xml");
+        assert_eq!(status, 200);
+        assert_eq!(headers[0].1, "application/xml");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata modelVersion="1.1.0">
+  <groupId>org.example</groupId>
+  <artifactId>app</artifactId>
+  <versioning>
+    <latest>2.0-SNAPSHOT</latest>
+    <release>1.10</release>
+    <versions>
+      <version>1.0</version>
+      <version>1.2</version>
+      <version>1.10</version>
+      <version>2.0-SNAPSHOT</version>
+    </versions>
+    <lastUpdated>20240102030405</lastUpdated>
+  </versioning>
+</metadata>
+"#
+        );
+
+        let (status, _, body) = get(
+            &repo,
+            "/maven-prod/org/example/app/2.0-SNAPSHOT/maven-metadata.xml",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata modelVersion="1.1.0">
+  <groupId>org.example</groupId>
+  <artifactId>app</artifactId>
+  <version>2.0-SNAPSHOT</version>
+  <versioning>
+    <snapshot>
+      <timestamp>20240102.030405</timestamp>
+      <buildNumber>2</buildNumber>
+    </snapshot>
+    <lastUpdated>20240102030405</lastUpdated>
+    <snapshotVersions>
+      <snapshotVersion>
+        <extension>jar</extension>
+        <value>2.0-20240102.030405-2</value>
+        <updated>20240102030405</updated>
+      </snapshotVersion>
+      <snapshotVersion>
+        <extension>pom</extension>
+        <value>2.0-20240102.030405-2</value>
+        <updated>20240102030405</updated>
+      </snapshotVersion>
+      <snapshotVersion>
+        <classifier>tests</classifier>
+        <extension>jar</extension>
+        <value>2.0-20240101.100000-1</value>
+        <updated>20240101100000</updated>
+      </snapshotVersion>
+    </snapshotVersions>
+  </versioning>
+</metadata>
+"#
+        );
+
+        // -SNAPSHOT requests resolve to the newest timestamped file
+        for (file, expected) in [
+            ("app-2.0-SNAPSHOT.jar", &b"build 2"[..]),
+            (
+                "app-2.0-SNAPSHOT.jar.sha1",
+                b"da2beec7e54fa9e0c1b9be227f30dd7256d42b89",
+            ),
+            ("app-2.0-SNAPSHOT-tests.jar", b"tests 1"),
+            ("app-2.0-20240101.100000-1.jar", b"build 1"),
+        ] {
+            let (status, _, body) = get(&repo, &format!("/maven-prod/{snapshot_dir}/{file}"));
+            assert_eq!((status, body.as_slice()), (200, expected), "{file}");
+        }
+        assert_eq!(
+            get(
+                &repo,
+                &format!("/maven-prod/{snapshot_dir}/app-2.0-SNAPSHOT-sources.jar")
+            )
+            .0,
+            404
+        );
+
+        let (_, _, body) = get(
+            &repo,
+            "/maven-prod/org/example/app/plugin/maven-metadata.xml",
+        );
+        assert!(String::from_utf8(body)
+            .unwrap()
+            .contains("<versions>\n      <version>3.0</version>\n    </versions>"));
+        for missing in [
+            "/maven-prod/org/example/other/maven-metadata.xml",
+            "/maven-prod/org/example/app/1.0/maven-metadata.xml",
+            "/maven-prod/app/maven-metadata.xml",
+        ] {
+            assert_eq!(get(&repo, missing).0, 404, "{missing}");
+        }
+    }
+
+    #[test]
+    fn generates_group_plugin_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let plugins = "org/apache/maven/plugins";
+        for (version, name) in [
+            ("3.11.0", "Old Name"),
+            ("3.12.1", "Apache Maven Compiler Plugin"),
+        ] {
+            store(
+                root,
+                &format!("{plugins}/maven-compiler-plugin/{version}/maven-compiler-plugin-{version}.pom"),
+                format!(
+                    "<?xml version=\"1.0\"?>\n<project>\n  <parent><artifactId>maven-plugins</artifactId></parent>\n  <artifactId>maven-compiler-plugin</artifactId>\n  <packaging>maven-plugin</packaging>\n  <name>{name}</name>\n  <developers><developer><name>Someone</name></developer></developers>\n</project>\n"
+                )
+                .as_bytes(),
+            );
+        }
+        store(
+            root,
+            &format!("{plugins}/maven-plugins/41/maven-plugins-41.pom"),
+            b"<project><packaging>pom</packaging><name>Apache Maven Plugins</name></project>",
+        );
+        let mojo = "org/codehaus/mojo";
+        store(
+            root,
+            &format!("{mojo}/exec-maven-plugin/3.1.1/exec-maven-plugin-3.1.1.pom"),
+            b"<project><!-- <name>commented</name> --><packaging>maven-plugin</packaging></project>",
+        );
+        store(
+            root,
+            &format!("{mojo}/build-helper-maven-plugin/3.5.0/build-helper-maven-plugin-3.5.0.pom"),
+            b"<project><packaging>maven-plugin</packaging><build><plugins><plugin>\
+              <artifactId>maven-plugin-plugin</artifactId>\
+              <configuration><goalPrefix>helper</goalPrefix></configuration>\
+              </plugin></plugins></build></project>",
+        );
+
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+
+        let (status, headers, body) =
+            get(&repo, &format!("/maven-prod/{plugins}/maven-metadata.xml"));
+        assert_eq!((status, headers[0].1.as_str()), (200, "application/xml"));
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata modelVersion="1.1.0">
+  <plugins>
+    <plugin>
+      <name>Apache Maven Compiler Plugin</name>
+      <prefix>compiler</prefix>
+      <artifactId>maven-compiler-plugin</artifactId>
+    </plugin>
+  </plugins>
+</metadata>
+"#
+        );
+        let (_, _, xml) = get(&repo, &format!("/maven-prod/{mojo}/maven-metadata.xml"));
+        let xml = String::from_utf8(xml).unwrap();
+        assert!(xml.contains(
+            "<prefix>helper</prefix>\n      <artifactId>build-helper-maven-plugin</artifactId>"
+        ));
+        assert!(xml.contains("<plugin>\n      <prefix>exec</prefix>\n      <artifactId>exec-maven-plugin</artifactId>"));
+        let (status, _, sha1) = get(
+            &repo,
+            &format!("/maven-prod/{mojo}/maven-metadata.xml.sha1"),
+        );
+        assert_eq!(
+            (status, sha1),
+            (200, Algorithm::Sha1.digest_hex(xml.as_bytes()).into_bytes())
+        );
+
+        // Artifact-level metadata still wins for artifact directories
+        let (_, _, xml) = get(
+            &repo,
+            &format!("/maven-prod/{plugins}/maven-compiler-plugin/maven-metadata.xml"),
+        );
+        assert!(String::from_utf8(xml)
+            .unwrap()
+            .contains("<release>3.12.1</release>"));
+        assert_eq!(
+            get(&repo, "/maven-prod/org/apache/maven/maven-metadata.xml").0,
+            404
+        );
+
+        assert_eq!(
+            pom::default_goal_prefix("spring-boot-maven-plugin"),
+            "spring-boot"
+        );
+        assert_eq!(pom::default_goal_prefix("maven-plugin-plugin"), "plugin");
+    }
+
+    #[test]
+    fn serves_and_verifies_checksums() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let app = "org/example/app/1.0";
+        store(root, &format!("{app}/app-1.0.jar"), b"abc");
+        store(root, &format!("{app}/app-1.0.pom"), b"<project/>");
+        // Dumped sidecars in the forms Maven, sha1sum and BSD tools write
+        store(
+            root,
+            &format!("{app}/app-1.0.jar.sha1"),
+            b"a9993e364706816aba3e25717850c26c9cd0d89d  app-1.0.jar\n",
+        );
+        store(
+            root,
+            &format!("{app}/app-1.0.jar.md5"),
+            b"MD5 (app-1.0.jar) = 900150983CD24FB0D6963F7D28E17F72",
+        );
+        let snapshot = "org/example/app/2.0-SNAPSHOT";
+        store(
+            root,
+            &format!("{snapshot}/app-2.0-20240101.100000-1.jar"),
+            b"old",
+        );
+        store(
+            root,
+            &format!("{snapshot}/app-2.0-20240101.100000-1.jar.sha1"),
+            Algorithm::Sha1.digest_hex(b"old").as_bytes(),
+        );
+        store(
+            root,
+            &format!("{snapshot}/app-2.0-20240102.030405-2.jar"),
+            b"abc",
+        );
+        // A tampered dump: the jar no longer matches its sidecar
+        let bad = "org/example/bad/1.0";
+        store(root, &format!("{bad}/bad-1.0.jar"), b"tampered");
+        store(
+            root,
+            &format!("{bad}/bad-1.0.jar.sha1"),
+            b"a9993e364706816aba3e25717850c26c9cd0d89d",
+        );
+        store(root, &format!("{bad}/bad-1.0.pom"), b"<project/>");
+
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+
+        let abc = [
+            ("md5", "900150983cd24fb0d6963f7d28e17f72"),
+            ("sha1", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            ("sha256", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+            ("sha512", "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"),
+        ];
+        for (extension, digest) in abc {
+            let url = format!("/maven-prod/{app}/app-1.0.jar.{extension}");
+            let (status, headers, body) = get(&repo, &url);
+            assert_eq!(status, 200, "{url}");
+            assert_eq!(headers[0].1, "text/plain");
+            let body = String::from_utf8(body).unwrap();
+            assert_eq!(
+                parse_sidecar(
+                    body.as_bytes(),
+                    Algorithm::from_extension(extension).unwrap()
+                )
+                .unwrap(),
+                digest
+            );
+
+            // -SNAPSHOT sidecars follow the newest build, which has no stored ones
+            let url = format!("/maven-prod/{snapshot}/app-2.0-SNAPSHOT.jar.{extension}");
+            assert_eq!(get(&repo, &url).2, digest.as_bytes(), "{url}");
+        }
+        // Stored sidecars are served as dumped
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{app}/app-1.0.jar.sha1")).2,
+            b"a9993e364706816aba3e25717850c26c9cd0d89d  app-1.0.jar\n"
+        );
+
+        // Computed digests are cached and invalidated when the file changes
+        let cached = root
+            .join(CHECKSUM_CACHE_DIR)
+            .join(format!("{app}/app-1.0.jar.sha256"));
+        assert_eq!(std::fs::read(&cached).unwrap(), abc[2].1.as_bytes());
+        let fake = "c".repeat(64);
+        std::fs::write(&cached, &fake).unwrap();
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{app}/app-1.0.jar.sha256")).2,
+            fake.as_bytes()
+        );
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(root.join(app).join("app-1.0.jar"), b"abcd").unwrap();
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{app}/app-1.0.jar.sha256")).2,
+            Algorithm::Sha256.digest_hex(b"abcd").as_bytes()
+        );
+
+        // Generated metadata has sidecars too
+        let (_, _, xml) = get(&repo, "/maven-prod/org/example/app/maven-metadata.xml");
+        let (status, headers, body) =
+            get(&repo, "/maven-prod/org/example/app/maven-metadata.xml.sha1");
+        assert_eq!((status, headers[0].1.as_str()), (200, "text/plain"));
+        assert_eq!(body, Algorithm::Sha1.digest_hex(&xml).as_bytes());
+
+        // The tampered artifact is rejected with its sidecars, the rest is kept
+        for rejected in ["bad-1.0.jar", "bad-1.0.jar.sha1", "bad-1.0.jar.md5"] {
+            assert_eq!(
+                get(&repo, &format!("/maven-prod/{bad}/{rejected}")).0,
+                404,
+                "{rejected}"
+            );
+        }
+        assert_eq!(get(&repo, &format!("/maven-prod/{bad}/bad-1.0.pom")).0, 200);
+        assert_eq!(
+            get(
+                &repo,
+                "/maven-prod/org/example/app/1.0/app-1.0.pom.sha1.md5"
+            )
+            .0,
+            404
+        );
+        assert!(repo
+            .index()
+            .iter()
+            .all(|f| !f.path.starts_with(root.join(CHECKSUM_CACHE_DIR))));
+
+        // Digests verified at load are cached; the next load trusts them
+        // until the file changes instead of hashing it again
+        let old_jar = format!("{snapshot}/app-2.0-20240101.100000-1.jar");
+        let cached = root
+            .join(CHECKSUM_CACHE_DIR)
+            .join(format!("{old_jar}.sha1"));
+        assert_eq!(
+            std::fs::read(&cached).unwrap(),
+            Algorithm::Sha1.digest_hex(b"old").as_bytes()
+        );
+        std::fs::write(&cached, "0".repeat(40)).unwrap();
+        let open = || {
+            MavenRepoFile::new(
+                "maven-prod".into(),
+                root.into(),
+                "https://10.101.1.9:8443/maven-prod".into(),
+            )
+            .unwrap()
+        };
+        assert_eq!(get(&open(), &format!("/maven-prod/{old_jar}")).0, 404);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(root.join(&old_jar), b"old").unwrap();
+        assert_eq!(get(&open(), &format!("/maven-prod/{old_jar}")).0, 200);
+    }
+
+    #[test]
+    fn deploys_releases_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let mut repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+        let release = "org/example/app/1.0";
+        assert_eq!(
+            put(&repo, &format!("/maven-prod/{release}/app-1.0.jar"), b"jar"),
+            403
+        );
+        repo.writable = true;
+
+        // Nothing is visible until the metadata upload completes the release
+        let jar = format!("/maven-prod/{release}/app-1.0.jar");
+        assert_eq!(put(&repo, &jar, b"jar 1.0"), 200);
+        assert_eq!(
+            put(
+                &repo,
+                &format!("{jar}.sha1"),
+                Algorithm::Sha1.digest_hex(b"jar 1.0").as_bytes()
+            ),
+            200
+        );
+        assert_eq!(
+            put(
+                &repo,
+                &format!("/maven-prod/{release}/app-1.0.pom"),
+                b"<project/>"
+            ),
+            200
+        );
+        assert_eq!(get(&repo, &jar).0, 404);
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{release}/app-1.0.pom")).0,
+            404
+        );
+        assert_eq!(
+            get(&repo, "/maven-prod/org/example/app/maven-metadata.xml").0,
+            404
+        );
+        assert_eq!(
+            put(
+                &repo,
+                "/maven-prod/org/example/app/maven-metadata.xml",
+                b"<metadata/>"
+            ),
+            200
+        );
+        assert_eq!(get(&repo, &jar).2, b"jar 1.0");
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{release}/app-1.0.pom")).0,
+            200
+        );
+        assert_eq!(
+            get(&repo, &format!("{jar}.sha1")).2,
+            Algorithm::Sha1.digest_hex(b"jar 1.0").as_bytes()
+        );
+        assert!(
+            String::from_utf8(get(&repo, "/maven-prod/org/example/app/maven-metadata.xml").2)
+                .unwrap()
+                .contains("<release>1.0</release>")
+        );
+
+        // Releases are immutable
+        assert_eq!(put(&repo, &jar, b"other"), 409);
+        assert_eq!(
+            put(
+                &repo,
+                &format!("/maven-prod/{release}/app-1.0-sources.jar"),
+                b"src"
+            ),
+            409
+        );
+        assert_eq!(get(&repo, &jar).2, b"jar 1.0");
+
+        // A checksum mismatch drops the staged release
+        let broken = "org/example/app/1.1";
+        assert_eq!(
+            put(
+                &repo,
+                &format!("/maven-prod/{broken}/app-1.1.pom"),
+                b"<project/>"
+            ),
+            200
+        );
+        assert_eq!(
+            put(
+                &repo,
+                &format!("/maven-prod/{broken}/app-1.1.jar"),
+                b"jar 1.1"
+            ),
+            200
+        );
+        assert_eq!(
+            put(
+                &repo,
+                &format!("/maven-prod/{broken}/app-1.1.jar.sha1"),
+                Algorithm::Sha1.digest_hex(b"jar 1.0").as_bytes()
+            ),
+            400
+        );
+        assert_eq!(
+            put(
+                &repo,
+                "/maven-prod/org/example/app/maven-metadata.xml",
+                b"<metadata/>"
+            ),
+            200
+        );
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{broken}/app-1.1.pom")).0,
+            404
+        );
+        assert_eq!(
+            get(&repo, &format!("/maven-prod/{broken}/app-1.1.jar")).0,
+            404
+        );
+
+        // A full deploy, then SNAPSHOT builds, which may be deployed again
+        assert!(deploy(&repo, "org/example/app/1.2", "app-1.2", b"jar 1.2")
+            .iter()
+            .all(|s| *s == 200));
+        let snapshot = "org/example/app/2.0-SNAPSHOT";
+        for (build, jar) in [
+            ("2.0-20240101.100000-1", b"build 1"),
+            ("2.0-20240102.030405-2", b"build 2"),
+        ] {
+            let statuses = deploy(&repo, snapshot, &format!("app-{build}"), jar);
+            assert!(statuses.iter().all(|s| *s == 200), "{statuses:?}");
+        }
+        assert_eq!(
+            get(
+                &repo,
+                &format!("/maven-prod/{snapshot}/app-2.0-SNAPSHOT.jar")
+            )
+            .2,
+            b"build 2"
+        );
+        assert_eq!(
+            get(
+                &repo,
+                &format!("/maven-prod/{snapshot}/app-2.0-20240101.100000-1.jar")
+            )
+            .2,
+            b"build 1"
+        );
+        assert!(
+            String::from_utf8(get(&repo, "/maven-prod/org/example/app/maven-metadata.xml").2)
+                .unwrap()
+                .contains("<latest>2.0-SNAPSHOT</latest>\n    <release>1.2</release>")
+        );
+
+        // Single files through the backend trait are committed right away
+        let id = ArtifactId {
+            namespace: Some("org.example".into()),
+            name: "lib-3.0.jar".into(),
+            version: "3.0".into(),
+        };
+        repo.put(&id, b"lib").unwrap();
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), b"lib");
+        assert!(repo.put(&id, b"lib").is_err());
+
+        // Interrupted deploys are dropped on restart, committed ones stay
+        assert_eq!(
+            put(
+                &repo,
+                "/maven-prod/org/example/app/1.3/app-1.3.jar",
+                b"jar 1.3"
+            ),
+            200
+        );
+        let staged = root
+            .join(STAGING_DIR)
+            .join("org/example/app/1.3/app-1.3.jar");
+        assert!(staged.exists());
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+        assert!(!staged.exists());
+        assert_eq!(get(&repo, &jar).2, b"jar 1.0");
+        assert_eq!(
+            get(&repo, "/maven-prod/org/example/app/1.3/app-1.3.jar").0,
+            404
+        );
+        assert_eq!(repo.index().len(), 2 + 1 + 6 + 12 + 1);
+    }
+
+    #[test]
+    fn imports_local_repository() {
+        // An empty zip: just the end-of-central-directory record
+        let jar = [&b"PK\x05\x06"[..], &[0; 18]].concat();
+        let sha1 = |data: &[u8]| Algorithm::Sha1.digest_hex(data);
+        let m2 = tempfile::tempdir().unwrap();
+        for (rel, data) in [
+            ("com/example/lib/1.0/lib-1.0.jar", jar.clone()),
+            (
+                "com/example/lib/1.0/lib-1.0.jar.sha1",
+                sha1(&jar).into_bytes(),
+            ),
+            ("com/example/lib/1.0/lib-1.0.pom", b"<project/>".to_vec()),
+            (
+                "com/example/lib/1.0/lib-1.0.pom.sha1",
+                sha1(b"<project/>").into_bytes(),
+            ),
+            (
+                "com/example/lib/1.0/lib-1.0-javadoc.jar.sha1",
+                sha1(&jar).into_bytes(),
+            ),
+            (
+                "com/example/lib/1.0/_remote.repositories",
+                b"lib-1.0.jar>central=".to_vec(),
+            ),
+            (
+                "com/example/lib/1.0/lib-1.0-sources.jar.lastUpdated",
+                b"".to_vec(),
+            ),
+            (
+                "com/example/lib/maven-metadata-central.xml",
+                b"<metadata/>".to_vec(),
+            ),
+            ("com/example/lib/resolver-status.properties", b"".to_vec()),
+            ("com/example/broken/1.0/broken-1.0.jar", jar[..10].to_vec()),
+            (
+                "com/example/broken/1.0/broken-1.0.pom",
+                b"<project/>".to_vec(),
+            ),
+            ("com/example/bad/1.0/bad-1.0.jar", jar.clone()),
+            (
+                "com/example/bad/1.0/bad-1.0.jar.sha1",
+                sha1(b"other").into_bytes(),
+            ),
+            ("com/example/bad/1.0/bad-1.0.pom", b"<project/>".to_vec()),
+            ("com/example/nopom/1.0/nopom-1.0.jar", jar.clone()),
+            ("com/example/part/1.0/part-1.0.jar.part", jar[..4].to_vec()),
+            ("org/example/parent/1/parent-1.pom", b"<project/>".to_vec()),
+        ] {
+            store(m2.path(), rel, &data);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = MavenRepoFile::new(
+            "maven-prod".into(),
+            dir.path().into(),
+            "https://10.101.1.9:8443/maven-prod".into(),
+        )
+        .unwrap();
+        let report = repo.import_m2(m2.path()).unwrap();
+        let names = |ids: &[ArtifactId]| ids.iter().map(|id| id.name.clone()).collect::<Vec<_>>();
+        assert_eq!(
+            names(&report.imported),
+            [
+                "bad-1.0.pom",
+                "broken-1.0.pom",
+                "lib-1.0.jar",
+                "lib-1.0.jar.sha1",
+                "lib-1.0.pom",
+                "lib-1.0.pom.sha1",
+                "parent-1.pom"
+            ]
+        );
+        assert!(report.existing.is_empty());
+        let rejected: Vec<(String, &str)> = report
+            .rejected
+            .iter()
+            .map(|(path, reason)| {
+                let rel = path.strip_prefix(m2.path()).unwrap();
+                (rel.to_string_lossy().into_owned(), reason.as_str())
+            })
+            .collect();
+        let reason = |rel: &str| {
+            rejected
+                .iter()
+                .find(|(r, _)| r == rel)
+                .map(|(_, reason)| *reason)
+        };
+        assert_eq!(rejected.len(), 6, "{rejected:?}");
+        assert_eq!(
+            reason("com/example/part/1.0/part-1.0.jar.part"),
+            Some("partial download")
+        );
+        assert_eq!(
+            reason("com/example/broken/1.0/broken-1.0.jar"),
+            Some("partial download")
+        );
+        assert!(reason("com/example/bad/1.0/bad-1.0.jar")
+            .unwrap()
+            .starts_with("sha1 mismatch"));
+        assert!(reason("com/example/bad/1.0/bad-1.0.jar.sha1").is_some());
+        assert_eq!(
+            reason("com/example/nopom/1.0/nopom-1.0.jar"),
+            Some("nopom-1.0.pom is missing")
+        );
+        assert_eq!(
+            reason("com/example/lib/1.0/lib-1.0-javadoc.jar.sha1"),
+            Some("no com/example/lib/1.0/lib-1.0-javadoc.jar to go with it")
+        );
+        assert_eq!(report.dropped.len(), 4);
+
+        assert_eq!(
+            get(&repo, "/maven-prod/com/example/lib/1.0/lib-1.0.jar").2,
+            jar
+        );
+        assert_eq!(
+            get(&repo, "/maven-prod/com/example/broken/1.0/broken-1.0.jar").0,
+            404
+        );
+        assert_eq!(
+            get(
+                &repo,
+                "/maven-prod/com/example/lib/1.0/_remote.repositories"
+            )
+            .0,
+            404
+        );
+        assert_eq!(
+            get(&repo, "/maven-prod/org/example/parent/maven-metadata.xml").0,
+            200
+        );
+        assert!(!dir
+            .path()
+            .join("com/example/lib/1.0/_remote.repositories")
+            .exists());
+
+        // Importing again finds everything stored
+        let again = repo.import_m2(m2.path()).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(names(&again.existing), names(&report.imported));
+        assert_eq!(again.rejected.len(), 6);
+    }
+}
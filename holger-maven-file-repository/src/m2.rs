@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use holger_traits::util::{find_files, write_atomic};
+use holger_traits::ArtifactId;
+
+use crate::checksum::{mismatched_sidecars, split_sidecar};
+use crate::deploy::STAGING_DIR;
+use crate::index::StoredFile;
+use crate::layout::ArtifactPath;
+use crate::MavenRepoFile;
+
+/// Outcome of importing a local Maven repository such as `~/.m2/repository`.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    pub imported: Vec<ArtifactId>,
+    /// Files already stored with the same content
+    pub existing: Vec<ArtifactId>,
+    /// Files that were not stored, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+    /// Resolver bookkeeping that is never stored
+    pub dropped: Vec<PathBuf>,
+}
+
+/// Files Maven's resolver leaves in a local repository: download origins,
+/// failed-download markers, per-remote metadata and locks.
+fn is_noise(filename: &str) -> bool {
+    let filename = split_sidecar(filename).map_or(filename, |(base, _)| base);
+    filename == "_remote.repositories"
+        || filename == "resolver-status.properties"
+        || filename.ends_with(".lastUpdated")
+        || filename.ends_with(".lock")
+        || (filename.starts_with("maven-metadata-") && filename.ends_with(".xml"))
+}
+
+/// Resolver temp files of downloads that never finished.
+fn is_partial(filename: &str) -> bool {
+    filename.ends_with(".part") || filename.contains(".part-")
+}
+
+/// Archives end with a zip end-of-central-directory record; a download cut
+/// short does not.
+fn is_truncated_archive(artifact: &ArtifactPath, data: &[u8]) -> bool {
+    const EOCD: &[u8] = b"PK\x05\x06";
+    if !matches!(artifact.extension.as_str(), "jar" | "war" | "ear" | "zip") {
+        return false;
+    }
+    // The record is 22 bytes, the last two holding the length of the comment after it
+    let start = data.len().saturating_sub(22 + 0xFFFF);
+    !data[start..]
+        .windows(22)
+        .enumerate()
+        .any(|(offset, record)| {
+            let comment_len = u16::from_le_bytes([record[20], record[21]]) as usize;
+            record.starts_with(EOCD) && start + offset + 22 + comment_len == data.len()
+        })
+}
+
+impl MavenRepoFile {
+    /// Store every artifact of a local repository tree, one version directory
+    /// at a time, each published at once.
+    ///
+    /// Resolver bookkeeping ends up in `dropped`. Partial downloads, files whose
+    /// checksum sidecar disagrees with them, sidecars without their file and
+    /// artifacts without a pom for the same version end up in `rejected`.
+    pub fn import_m2(&self, m2_dir: &Path) -> anyhow::Result<IngestReport> {
+        let mut report = IngestReport::default();
+        let mut version_dirs: BTreeMap<String, Vec<(String, PathBuf)>> = BTreeMap::new();
+        for (rel, path) in find_files(m2_dir)? {
+            let (dir, filename) = rel.rsplit_once('/').unwrap_or(("", &rel));
+            if is_noise(filename) {
+                report.dropped.push(path);
+            } else if is_partial(filename) {
+                report.rejected.push((path, "partial download".into()));
+            } else {
+                version_dirs
+                    .entry(dir.to_string())
+                    .or_default()
+                    .push((rel.clone(), path));
+            }
+        }
+        for files in version_dirs.into_values() {
+            self.import_version_dir(files, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    fn import_version_dir(
+        &self,
+        files: Vec<(String, PathBuf)>,
+        report: &mut IngestReport,
+    ) -> anyhow::Result<()> {
+        let mut found: BTreeMap<String, (ArtifactPath, PathBuf)> = BTreeMap::new();
+        for (rel, path) in files {
+            match ArtifactPath::parse(&rel) {
+                Some(artifact) => {
+                    found.insert(rel, (artifact, path));
+                }
+                None => report
+                    .rejected
+                    .push((path, "not a repository layout path".into())),
+            }
+        }
+
+        let mut reasons: BTreeMap<String, String> = mismatched_sidecars(
+            &found
+                .iter()
+                .map(|(rel, (_, path))| (rel.clone(), path.clone()))
+                .collect(),
+            None,
+        )?;
+        for (rel, (artifact, path)) in &found {
+            if reasons.contains_key(rel) {
+                continue;
+            }
+            match split_sidecar(rel) {
+                Some((base, _)) if !found.contains_key(base) => {
+                    reasons.insert(rel.clone(), format!("no {} to go with it", base));
+                }
+                Some(_) => {}
+                None => {
+                    let data = std::fs::read(path)?;
+                    if data.is_empty() || is_truncated_archive(artifact, &data) {
+                        reasons.insert(rel.clone(), "partial download".into());
+                    }
+                }
+            }
+        }
+
+        // Each artifact needs the pom of its version, stored or alongside it
+        let poms: BTreeSet<&str> = found
+            .iter()
+            .filter(|(rel, (artifact, _))| {
+                artifact.extension == "pom"
+                    && artifact.classifier.is_none()
+                    && !reasons.contains_key(*rel)
+            })
+            .map(|(_, (artifact, _))| artifact.file_version.as_str())
+            .collect();
+        for (rel, (artifact, _)) in &found {
+            if reasons.contains_key(rel)
+                || split_sidecar(rel).is_some()
+                || poms.contains(artifact.file_version.as_str())
+            {
+                continue;
+            }
+            let pom = format!("{}-{}.pom", artifact.artifact_id, artifact.file_version);
+            let (dir, _) = rel.rsplit_once('/').unwrap_or_default();
+            if self.index().get(&format!("{dir}/{pom}")).is_none() {
+                reasons.insert(rel.clone(), format!("{pom} is missing"));
+            }
+        }
+        // Sidecars share the fate of their file
+        for rel in found.keys() {
+            if let Some((base, _)) = split_sidecar(rel) {
+                if let Some(reason) = reasons.get(base).filter(|_| !reasons.contains_key(rel)) {
+                    let reason = format!("{base} is rejected: {reason}");
+                    reasons.insert(rel.clone(), reason);
+                }
+            }
+        }
+
+        let mut staged = Vec::new();
+        for (rel, (artifact, path)) in found {
+            if let Some(reason) = reasons.remove(&rel) {
+                report.rejected.push((path, reason));
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            let stored = self.index().get(&rel).map(|f| f.path.clone());
+            match stored {
+                Some(stored) if std::fs::read(&stored)? == data => {
+                    report.existing.push(artifact.artifact_id())
+                }
+                Some(_) => report
+                    .rejected
+                    .push((path, format!("{rel} is already stored with other content"))),
+                None => {
+                    let staged_path = self.root.join(STAGING_DIR).join(&rel);
+                    write_atomic(&staged_path, &data)?;
+                    report.imported.push(artifact.artifact_id());
+                    staged.push(StoredFile {
+                        artifact,
+                        path: staged_path,
+                    });
+                }
+            }
+        }
+        Self::publish(&self.root, &mut self.index_mut(), staged)
+    }
+}
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+
+use holger_traits::util::UtcDateTime;
+
+use crate::index::StoredFile;
+use crate::layout::SNAPSHOT_SUFFIX;
+use crate::version::compare_versions;
+
+/// File name of generated repository metadata.
+pub const METADATA_FILE: &str = "maven-metadata.xml";
+
+/// Checksum sidecar extensions; they never appear in `snapshotVersions`.
+const CHECKSUM_EXTENSIONS: [&str; 4] = ["md5", "sha1", "sha256", "sha512"];
+
+/// Artifact-level `maven-metadata.xml`: every stored version in Maven order,
+/// the newest as `latest` and the newest non-SNAPSHOT as `release`.
+/// `None` if nothing is stored for the artifact.
+pub fn artifact_metadata_xml(
+    group_id: &str,
+    artifact_id: &str,
+    files: &[&StoredFile],
+) -> Option<String> {
+    let mut versions: Vec<&str> = files.iter().map(|f| f.artifact.version.as_str()).collect();
+    versions.sort_by(|a, b| compare_versions(a, b));
+    versions.dedup();
+    let latest = versions.last()?;
+    let release = versions
+        .iter()
+        .rev()
+        .find(|v| !v.ends_with(SNAPSHOT_SUFFIX));
+
+    let mut xml = xml_head(group_id, artifact_id);
+    xml.push_str("  <versioning>\n");
+    let _ = writeln!(xml, "    <latest>{}</latest>", escape(latest));
+    if let Some(release) = release {
+        let _ = writeln!(xml, "    <release>{}</release>", escape(release));
+    }
+    xml.push_str("    <versions>\n");
+    for version in &versions {
+        let _ = writeln!(xml, "      <version>{}</version>", escape(version));
+    }
+    xml.push_str("    </versions>\n");
+    let _ = writeln!(
+        xml,
+        "    <lastUpdated>{}</lastUpdated>",
+        last_updated(files)
+    );
+    xml.push_str("  </versioning>\n</metadata>\n");
+    Some(xml)
+}
+
+/// One `<plugin>` of group-level metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginPrefix {
+    pub prefix: String,
+    pub artifact_id: String,
+    pub name: Option<String>,
+}
+
+/// Group-level `maven-metadata.xml`, which maps goal prefixes such as
+/// `compiler` in `mvn compiler:compile` to the group's plugins. `None` if the
+/// group has no plugins.
+pub fn group_metadata_xml(plugins: &[PluginPrefix]) -> Option<String> {
+    if plugins.is_empty() {
+        return None;
+    }
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<metadata modelVersion=\"1.1.0\">\n  <plugins>\n",
+    );
+    for plugin in plugins {
+        xml.push_str("    <plugin>\n");
+        if let Some(name) = &plugin.name {
+            let _ = writeln!(xml, "      <name>{}</name>", escape(name));
+        }
+        let _ = writeln!(xml, "      <prefix>{}</prefix>", escape(&plugin.prefix));
+        let _ = writeln!(
+            xml,
+            "      <artifactId>{}</artifactId>",
+            escape(&plugin.artifact_id)
+        );
+        xml.push_str("    </plugin>\n");
+    }
+    xml.push_str("  </plugins>\n</metadata>\n");
+    Some(xml)
+}
+
+/// Version-level `maven-metadata.xml` of a SNAPSHOT version: the newest
+/// timestamped build and, per classifier and extension, its newest file.
+/// `None` if nothing is stored for the version.
+pub fn snapshot_metadata_xml(
+    group_id: &str,
+    artifact_id: &str,
+    version: &str,
+    files: &[&StoredFile],
+) -> Option<String> {
+    if files.is_empty() {
+        return None;
+    }
+    let mut newest: BTreeMap<(Option<&str>, &str), (&StoredFile, String)> = BTreeMap::new();
+    for file in files.iter().filter(|f| !is_checksum(&f.artifact.extension)) {
+        let key = (
+            file.artifact.classifier.as_deref(),
+            file.artifact.extension.as_str(),
+        );
+        let updated = file_updated(file);
+        if newest
+            .get(&key)
+            .is_none_or(|(current, _)| is_newer(file, current))
+        {
+            newest.insert(key, (file, updated));
+        }
+    }
+    let snapshot = files
+        .iter()
+        .filter_map(|f| snapshot_stamp(&f.artifact.file_version, version))
+        .max_by_key(|(timestamp, build)| (timestamp.to_string(), *build));
+
+    let mut xml = xml_head(group_id, artifact_id);
+    let _ = writeln!(xml, "  <version>{}</version>", escape(version));
+    xml.push_str("  <versioning>\n");
+    if let Some((timestamp, build)) = snapshot {
+        xml.push_str("    <snapshot>\n");
+        let _ = writeln!(xml, "      <timestamp>{timestamp}</timestamp>");
+        let _ = writeln!(xml, "      <buildNumber>{build}</buildNumber>");
+        xml.push_str("    </snapshot>\n");
+    }
+    let _ = writeln!(
+        xml,
+        "    <lastUpdated>{}</lastUpdated>",
+        last_updated(files)
+    );
+    xml.push_str("    <snapshotVersions>\n");
+    for ((classifier, extension), (file, updated)) in &newest {
+        xml.push_str("      <snapshotVersion>\n");
+        if let Some(classifier) = classifier {
+            let _ = writeln!(
+                xml,
+                "        <classifier>{}</classifier>",
+                escape(classifier)
+            );
+        }
+        let _ = writeln!(xml, "        <extension>{}</extension>", escape(extension));
+        let _ = writeln!(
+            xml,
+            "        <value>{}</value>",
+            escape(&file.artifact.file_version)
+        );
+        let _ = writeln!(xml, "        <updated>{updated}</updated>");
+        xml.push_str("      </snapshotVersion>\n");
+    }
+    xml.push_str("    </snapshotVersions>\n  </versioning>\n</metadata>\n");
+    Some(xml)
+}
+
+/// The newest timestamped file of a SNAPSHOT version with the given
+/// classifier and extension, which a request for `artifactId-1.0-SNAPSHOT.jar` resolves to.
+pub fn resolve_snapshot<'a>(
+    files: &[&'a StoredFile],
+    classifier: Option<&str>,
+    extension: &str,
+) -> Option<&'a StoredFile> {
+    files
+        .iter()
+        .copied()
+        .filter(|f| {
+            f.artifact.classifier.as_deref() == classifier && f.artifact.extension == extension
+        })
+        .filter(|f| snapshot_stamp(&f.artifact.file_version, &f.artifact.version).is_some())
+        .reduce(|best, f| if is_newer(f, best) { f } else { best })
+}
+
+fn is_checksum(extension: &str) -> bool {
+    extension
+        .rsplit('.')
+        .next()
+        .is_some_and(|ext| CHECKSUM_EXTENSIONS.contains(&ext))
+}
+
+/// `(yyyyMMdd.HHmmss, build)` of a timestamped snapshot file version such as
+/// `1.0-20240102.030405-7`; `None` for `1.0-SNAPSHOT` itself.
+fn snapshot_stamp<'a>(file_version: &'a str, version: &str) -> Option<(&'a str, u32)> {
+    let base = version.strip_suffix(SNAPSHOT_SUFFIX)?;
+    let (timestamp, build) = file_version
+        .strip_prefix(base)?
+        .strip_prefix('-')?
+        .rsplit_once('-')?;
+    Some((timestamp, build.parse().ok()?))
+}
+
+/// Timestamped files beat `-SNAPSHOT` ones; among those the later build wins.
+fn is_newer(candidate: &StoredFile, current: &StoredFile) -> bool {
+    let stamp = |f: &StoredFile| {
+        snapshot_stamp(&f.artifact.file_version, &f.artifact.version)
+            .map(|(timestamp, build)| (timestamp.to_string(), build))
+    };
+    stamp(candidate) > stamp(current)
+}
+
+/// `updated` of a `snapshotVersion`: the build timestamp, or the file's mtime.
+fn file_updated(file: &StoredFile) -> String {
+    match snapshot_stamp(&file.artifact.file_version, &file.artifact.version) {
+        Some((timestamp, _)) => timestamp.replace('.', ""),
+        None => format_utc(modified_secs(file)),
+    }
+}
+
+/// Newest of the files' build timestamps and mtimes, as `yyyyMMddHHmmss`.
+fn last_updated(files: &[&StoredFile]) -> String {
+    files
+        .iter()
+        .map(|f| file_updated(f).max(format_utc(modified_secs(f))))
+        .max()
+        .unwrap_or_else(|| format_utc(0))
+}
+
+fn modified_secs(file: &StoredFile) -> u64 {
+    std::fs::metadata(&file.path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Unix seconds as a UTC `yyyyMMddHHmmss` timestamp.
+pub fn format_utc(secs: u64) -> String {
+    let t = UtcDateTime::from_unix(secs);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+fn xml_head(group_id: &str, artifact_id: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<metadata modelVersion=\"1.1.0\">\n  <groupId>{}</groupId>\n  <artifactId>{}</artifactId>\n",
+        escape(group_id),
+        escape(artifact_id)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
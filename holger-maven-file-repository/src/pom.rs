@@ -0,0 +1,78 @@
+/// The pom fields Holger needs to describe a Maven plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pom {
+    pub packaging: Option<String>,
+    pub name: Option<String>,
+    /// `goalPrefix` configured for `maven-plugin-plugin`
+    pub goal_prefix: Option<String>,
+}
+
+impl Pom {
+    /// Read `packaging` and `name` from directly below `<project>`, so the
+    /// names of developers, licenses and the like are not mistaken for them.
+    pub fn parse(xml: &str) -> Self {
+        let mut pom = Pom::default();
+        let mut path: Vec<&str> = Vec::new();
+        let mut rest = xml;
+        while let Some(start) = rest.find('<') {
+            let text = &rest[..start];
+            rest = &rest[start..];
+            let Some(end) = rest.find('>') else {
+                break;
+            };
+            let tag = &rest[1..end];
+            if let Some(inner) = rest.strip_prefix("<!--") {
+                rest = inner.split_once("-->").map_or("", |(_, after)| after);
+                continue;
+            }
+            rest = &rest[end + 1..];
+            if tag.starts_with(['?', '!']) || tag.ends_with('/') {
+                continue;
+            }
+            if let Some(closing) = tag.strip_prefix('/') {
+                let value = Some(text.trim().to_string()).filter(|v| !v.is_empty());
+                match (path.as_slice(), closing.trim()) {
+                    (["project", "packaging"], "packaging") => pom.packaging = value,
+                    (["project", "name"], "name") => pom.name = value,
+                    (.., "goalPrefix") if pom.goal_prefix.is_none() => pom.goal_prefix = value,
+                    _ => {}
+                }
+                path.pop();
+            } else {
+                path.push(tag.split_whitespace().next().unwrap_or_default());
+            }
+        }
+        pom
+    }
+
+    pub fn is_plugin(&self) -> bool {
+        self.packaging.as_deref() == Some("maven-plugin")
+    }
+}
+
+/// The goal prefix `maven-plugin-plugin` derives from an artifactId when none
+/// is configured: `maven-compiler-plugin` → `compiler`, `exec-maven-plugin` → `exec`.
+pub fn default_goal_prefix(artifact_id: &str) -> String {
+    if artifact_id == "maven-plugin-plugin" {
+        return "plugin".into();
+    }
+    strip_word(&strip_word(artifact_id, "maven"), "plugin")
+}
+
+/// Remove every `word`, each with the `-` before and after it if present.
+fn strip_word(text: &str, word: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let tail = rest.strip_prefix('-').unwrap_or(rest);
+        match tail.strip_prefix(word) {
+            Some(after) => rest = after.strip_prefix('-').unwrap_or(after),
+            None => {
+                let c = rest.chars().next().unwrap_or_default();
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    out
+}
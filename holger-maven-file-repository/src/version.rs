@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+
+/// One token of a version string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Number(String),
+    Qualifier(String),
+}
+
+/// Well-known qualifiers in release order; the empty string is a plain release.
+const QUALIFIERS: [&str; 7] = ["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+
+/// Order two versions the way Maven's `ComparableVersion` does, simplified to
+/// a flat token list: numbers compare numerically and beat qualifiers, known
+/// qualifiers follow `alpha < beta < milestone < rc < snapshot < release < sp`,
+/// unknown ones sort after those lexically, and missing trailing tokens count
+/// as `0` or a plain release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let (a, b) = (tokenize(a), tokenize(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(i), b.get(i)) {
+            (Some(x), Some(y)) => compare_items(x, y),
+            (Some(x), None) => compare_to_missing(x),
+            (None, Some(y)) => compare_to_missing(y).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn tokenize(version: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut digits = false;
+    let flush = |current: &mut String, digits: bool, items: &mut Vec<Item>| {
+        if current.is_empty() {
+            return;
+        }
+        let token = std::mem::take(current);
+        items.push(if digits {
+            Item::Number(token.trim_start_matches('0').to_string())
+        } else {
+            Item::Qualifier(canonical_qualifier(&token.to_ascii_lowercase()).to_string())
+        });
+    };
+    for c in version.chars() {
+        if c == '.' || c == '-' || c == '_' {
+            flush(&mut current, digits, &mut items);
+        } else {
+            if !current.is_empty() && c.is_ascii_digit() != digits {
+                flush(&mut current, digits, &mut items);
+            }
+            digits = c.is_ascii_digit();
+            current.push(c);
+        }
+    }
+    flush(&mut current, digits, &mut items);
+    // Trailing zeros and release qualifiers are insignificant: 1.0-ga == 1
+    while items
+        .last()
+        .is_some_and(|item| matches!(item, Item::Number(n) | Item::Qualifier(n) if n.is_empty()))
+    {
+        items.pop();
+    }
+    items
+}
+
+fn canonical_qualifier(qualifier: &str) -> &str {
+    match qualifier {
+        "a" => "alpha",
+        "b" => "beta",
+        "m" => "milestone",
+        "cr" => "rc",
+        "ga" | "final" | "release" => "",
+        other => other,
+    }
+}
+
+fn compare_items(a: &Item, b: &Item) -> Ordering {
+    match (a, b) {
+        (Item::Number(x), Item::Number(y)) => x.len().cmp(&y.len()).then_with(|| x.cmp(y)),
+        (Item::Number(_), Item::Qualifier(_)) => Ordering::Greater,
+        (Item::Qualifier(_), Item::Number(_)) => Ordering::Less,
+        (Item::Qualifier(x), Item::Qualifier(y)) => compare_qualifiers(x, y),
+    }
+}
+
+/// How an item compares to a missing one: `0` or a plain release.
+fn compare_to_missing(item: &Item) -> Ordering {
+    match item {
+        Item::Number(n) if n.is_empty() => Ordering::Equal,
+        Item::Number(_) => Ordering::Greater,
+        Item::Qualifier(q) => compare_qualifiers(q, ""),
+    }
+}
+
+fn compare_qualifiers(a: &str, b: &str) -> Ordering {
+    let rank = |q: &str| QUALIFIERS.iter().position(|k| *k == q);
+    match (rank(a), rank(b)) {
+        (Some(x), Some(y)) => x.cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
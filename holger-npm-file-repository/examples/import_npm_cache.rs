@@ -0,0 +1,32 @@
+use holger_npm_file_repository::NpmRepoFile;
+
+/// cargo run --example import_npm_cache -- /var/lib/holger/npm-prod /airgap/npm
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(root), Some(cache_dir)) = (args.get(1), args.get(2)) else {
+        anyhow::bail!("usage: import_npm_cache <repository root> <npm cache or tarball dir>");
+    };
+    let repo = NpmRepoFile::new(
+        "npm-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/npm-prod".into(),
+    )?;
+    let report = repo.import_npm_cache(cache_dir.as_ref())?;
+
+    for id in &report.imported {
+        println!("imported {}@{}", id.name, id.version);
+    }
+    for id in &report.existing {
+        println!("existing {}@{}", id.name, id.version);
+    }
+    for (path, reason) in &report.rejected {
+        println!("rejected {}: {}", path.display(), reason);
+    }
+    println!(
+        "{} imported, {} existing, {} rejected",
+        report.imported.len(),
+        report.existing.len(),
+        report.rejected.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use holger_traits::util::{find_files, percent_decode, write_atomic};
+use holger_traits::ArtifactId;
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use crate::index::{parse_stored_path, tarball_path, StoredPath, DIST_TAGS_FILE};
+use crate::tarball::{check_integrity, content_address, read_tarball};
+use crate::{load_version, NpmRepoFile};
+
+/// Outcome of importing an npm cache or a directory of tarballs.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    pub imported: Vec<ArtifactId>,
+    /// Tarballs already stored with the same content
+    pub existing: Vec<ArtifactId>,
+    /// Files that were not stored, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+/// Key prefix of HTTP responses in the npm cache.
+const REQUEST_CACHE_KEY: &str = "make-fetch-happen:request-cache:";
+/// Key prefix of tarballs added by spec, e.g. by `npm cache add ./pkg.tgz`.
+const TARBALL_KEY: &str = "pacote:tarball:";
+
+/// One line of a cache index bucket: `<sha1 of the JSON>\t<JSON>`.
+#[derive(Deserialize)]
+struct CacheEntry {
+    key: String,
+    integrity: Option<String>,
+}
+
+/// Cache keys with the integrity of their current content. Later lines of a
+/// bucket replace earlier ones, a `null` integrity deletes the key, and
+/// lines whose hash does not match are skipped like npm does.
+fn read_cache_index(cacache: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut entries = BTreeMap::new();
+    for (_, path) in find_files(&cacache.join("index-v5"))? {
+        let content = String::from_utf8_lossy(&std::fs::read(&path)?).into_owned();
+        for line in content.lines() {
+            let Some((hash, json)) = line.split_once('\t') else {
+                continue;
+            };
+            if hex::encode(Sha1::digest(json.as_bytes())) != hash {
+                continue;
+            }
+            let Ok(entry) = serde_json::from_str::<CacheEntry>(json) else {
+                continue;
+            };
+            match entry.integrity {
+                Some(integrity) => entries.insert(entry.key, integrity),
+                None => entries.remove(&entry.key),
+            };
+        }
+    }
+    Ok(entries)
+}
+
+/// Package name and version of a tarball URL such as
+/// `https://registry.npmjs.org/@types/node/-/node-20.11.0.tgz`.
+fn tarball_url_package(url: &str) -> Option<(String, String)> {
+    let path = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let path = path.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').collect();
+    let [.., before, "-", file] = segments.as_slice() else {
+        return None;
+    };
+    let mut name = percent_decode(before)?;
+    if !name.contains('/') {
+        if let Some(scope) = segments
+            .len()
+            .checked_sub(4)
+            .and_then(|i| percent_decode(segments[i]))
+            .filter(|scope| scope.starts_with('@'))
+        {
+            name = format!("{scope}/{name}");
+        }
+    }
+    match parse_stored_path(&format!("{name}/-/{file}"))? {
+        StoredPath::Tarball { name, version } => Some((name, version)),
+        StoredPath::DistTags { .. } => None,
+    }
+}
+
+impl NpmRepoFile {
+    /// Store every package tarball of an npm cache (`~/.npm`, or its
+    /// `_cacache` directory) and of any `.tgz` files elsewhere below `dir`,
+    /// such as `npm pack` output.
+    ///
+    /// Cached content is checked against the integrity its cache index
+    /// records, the way npm checks it on read, and dist-tags are taken from
+    /// cached packuments. Files other than tarballs are ignored.
+    pub fn import_npm_cache(&self, dir: &Path) -> anyhow::Result<IngestReport> {
+        let mut report = IngestReport::default();
+        let mut tags: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        let cacache = [dir.join("_cacache"), dir.to_path_buf()]
+            .into_iter()
+            .find(|d| d.join("index-v5").is_dir());
+
+        if let Some(cacache) = &cacache {
+            for (key, integrity) in read_cache_index(cacache)? {
+                let (url, by_spec) = match (
+                    key.strip_prefix(REQUEST_CACHE_KEY),
+                    key.strip_prefix(TARBALL_KEY),
+                ) {
+                    (Some(url), _) => (url, false),
+                    (_, Some(spec)) => (spec, true),
+                    _ => continue,
+                };
+                let Some((algorithm, digest)) =
+                    content_address(&integrity).filter(|(_, d)| d.len() > 4)
+                else {
+                    report.rejected.push((
+                        cacache.join("index-v5"),
+                        format!("{url}: invalid integrity"),
+                    ));
+                    continue;
+                };
+                let content = cacache
+                    .join("content-v2")
+                    .join(algorithm)
+                    .join(&digest[..2])
+                    .join(&digest[2..4])
+                    .join(&digest[4..]);
+                if !content.is_file() {
+                    report
+                        .rejected
+                        .push((content, format!("{url}: content missing from the cache")));
+                    continue;
+                }
+                let data = std::fs::read(&content)?;
+                if check_integrity(&data, &integrity) != Some(true) {
+                    report.rejected.push((
+                        content,
+                        format!("{url}: content does not match {integrity}"),
+                    ));
+                    continue;
+                }
+                let url_path = url.split(['?', '#']).next().unwrap_or(url);
+                if by_spec {
+                    self.import_tarball(&content, &data, None, &mut report)?;
+                } else if url_path.ends_with(".tgz") {
+                    match tarball_url_package(url) {
+                        Some(expected) => {
+                            self.import_tarball(&content, &data, Some(expected), &mut report)?
+                        }
+                        None => report
+                            .rejected
+                            .push((content, format!("{url}: not a package tarball URL"))),
+                    }
+                } else if let Ok(packument) = serde_json::from_slice::<Packument>(&data) {
+                    tags.entry(packument.name)
+                        .or_default()
+                        .extend(packument.dist_tags);
+                }
+            }
+        }
+
+        for (rel, path) in find_files(dir)? {
+            let in_cache = cacache.as_ref().is_some_and(|c| path.starts_with(c));
+            if in_cache || !rel.ends_with(".tgz") {
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            self.import_tarball(&path, &data, None, &mut report)?;
+        }
+
+        for (name, tags) in tags {
+            self.import_tags(&name, tags)?;
+        }
+        Ok(report)
+    }
+
+    /// Store one tarball unless a different one is stored for its version.
+    fn import_tarball(
+        &self,
+        source: &Path,
+        data: &[u8],
+        expected: Option<(String, String)>,
+        report: &mut IngestReport,
+    ) -> anyhow::Result<()> {
+        let tarball = match read_tarball(data) {
+            Ok(tarball) => tarball,
+            Err(e) => {
+                report
+                    .rejected
+                    .push((source.to_path_buf(), format!("unreadable tarball: {e:#}")));
+                return Ok(());
+            }
+        };
+        let (Some(name), Some(version)) = (tarball.name(), tarball.version()) else {
+            report.rejected.push((
+                source.to_path_buf(),
+                "package.json has no name or version".into(),
+            ));
+            return Ok(());
+        };
+        let rel = tarball_path(name, version);
+        if parse_stored_path(&rel).is_none() {
+            report.rejected.push((
+                source.to_path_buf(),
+                format!("invalid package name or version {name}@{version}"),
+            ));
+            return Ok(());
+        }
+        if let Some((expected_name, expected_version)) = expected {
+            if (expected_name.as_str(), expected_version.as_str()) != (name, version) {
+                report.rejected.push((
+                    source.to_path_buf(),
+                    format!("cached as {expected_name}@{expected_version}, package.json names {name}@{version}"),
+                ));
+                return Ok(());
+            }
+        }
+
+        let id = ArtifactId {
+            namespace: None,
+            name: name.to_string(),
+            version: version.to_string(),
+        };
+        let stored = self
+            .index()
+            .get(name)
+            .and_then(|p| p.get(version))
+            .map(|v| v.tarball.shasum.clone());
+        match stored {
+            Some(shasum) if shasum == tarball.shasum => report.existing.push(id),
+            Some(_) => report.rejected.push((
+                source.to_path_buf(),
+                format!("{name}@{version} is already stored with other content"),
+            )),
+            None => {
+                let target = self.root.join(&rel);
+                write_atomic(&target, data)?;
+                let stored = load_version(name, version, target)?;
+                self.index_mut().insert(name, stored);
+                report.imported.push(id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record the dist-tags of a cached packument that name stored versions.
+    fn import_tags(&self, name: &str, tags: BTreeMap<String, String>) -> anyhow::Result<()> {
+        let mut index = self.index_mut();
+        let Some(package) = index.get(name) else {
+            return Ok(());
+        };
+        let mut merged = package.tags.clone();
+        merged.extend(
+            tags.into_iter()
+                .filter(|(_, version)| package.get(version).is_some()),
+        );
+        if merged == package.tags {
+            return Ok(());
+        }
+        let path = self.root.join(name).join(DIST_TAGS_FILE);
+        write_atomic(&path, &serde_json::to_vec_pretty(&merged)?)?;
+        index.set_tags(name, merged);
+        Ok(())
+    }
+}
+
+/// The parts of a cached packument the importer uses.
+#[derive(Deserialize)]
+struct Packument {
+    name: String,
+    #[serde(rename = "dist-tags")]
+    dist_tags: BTreeMap<String, String>,
+}
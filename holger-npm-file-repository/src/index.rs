@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use semver::Version;
+
+use crate::name::{is_valid_package_name, tarball_filename};
+use crate::tarball::Tarball;
+
+/// File next to a package's tarballs holding its dist-tags.
+pub const DIST_TAGS_FILE: &str = "dist-tags.json";
+
+/// One stored package version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageVersion {
+    pub version: Version,
+    pub tarball: Tarball,
+    pub path: PathBuf,
+    /// Modification time of the tarball in Unix seconds
+    pub modified: u64,
+}
+
+/// The stored versions and dist-tags of one package.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Package {
+    pub versions: BTreeMap<Version, PackageVersion>,
+    /// Tags recorded in `dist-tags.json`; they may name versions that are not stored
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Package {
+    pub fn get(&self, version: &str) -> Option<&PackageVersion> {
+        self.versions.get(&Version::parse(version).ok()?)
+    }
+
+    /// Recorded tags that name a stored version. `latest` defaults to the
+    /// newest release, else the newest prerelease.
+    pub fn dist_tags(&self) -> BTreeMap<String, String> {
+        let mut tags: BTreeMap<String, String> = self
+            .tags
+            .iter()
+            .filter(|(_, version)| self.get(version).is_some())
+            .map(|(tag, version)| (tag.clone(), version.clone()))
+            .collect();
+        if !tags.contains_key("latest") {
+            let newest = self
+                .versions
+                .keys()
+                .rev()
+                .find(|v| v.pre.is_empty())
+                .or_else(|| self.versions.keys().next_back());
+            if let Some(newest) = newest {
+                tags.insert("latest".into(), newest.to_string());
+            }
+        }
+        tags
+    }
+
+    /// A version or a dist-tag.
+    pub fn resolve(&self, version_or_tag: &str) -> Option<&PackageVersion> {
+        self.get(version_or_tag)
+            .or_else(|| self.get(self.dist_tags().get(version_or_tag)?))
+    }
+}
+
+/// In-memory view of the stored packages, keyed by package name.
+#[derive(Debug, Default)]
+pub struct PackageIndex {
+    packages: BTreeMap<String, Package>,
+}
+
+impl PackageIndex {
+    /// Add a version, replacing an earlier one.
+    pub fn insert(&mut self, name: &str, version: PackageVersion) {
+        self.packages
+            .entry(name.to_string())
+            .or_default()
+            .versions
+            .insert(version.version.clone(), version);
+    }
+
+    pub fn set_tags(&mut self, name: &str, tags: BTreeMap<String, String>) {
+        self.packages.entry(name.to_string()).or_default().tags = tags;
+    }
+
+    /// A package with at least one stored version.
+    pub fn get(&self, name: &str) -> Option<&Package> {
+        self.packages.get(name).filter(|p| !p.versions.is_empty())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Package)> {
+        self.packages
+            .iter()
+            .filter(|(_, p)| !p.versions.is_empty())
+            .map(|(name, package)| (name.as_str(), package))
+    }
+}
+
+/// What a stored file is, by its path relative to the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoredPath {
+    /// `<name>/-/<basename>-<version>.tgz`
+    Tarball { name: String, version: String },
+    /// `<name>/dist-tags.json`
+    DistTags { name: String },
+}
+
+pub fn parse_stored_path(rel: &str) -> Option<StoredPath> {
+    if let Some(name) = rel.strip_suffix(&format!("/{DIST_TAGS_FILE}")) {
+        return is_valid_package_name(name).then(|| StoredPath::DistTags {
+            name: name.to_string(),
+        });
+    }
+    let (name, filename) = rel.split_once("/-/")?;
+    if !is_valid_package_name(name) {
+        return None;
+    }
+    let basename = name.rsplit('/').next().unwrap_or(name);
+    let version = filename
+        .strip_prefix(basename)?
+        .strip_prefix('-')?
+        .strip_suffix(".tgz")?;
+    Version::parse(version).ok()?;
+    (tarball_filename(name, version) == filename).then(|| StoredPath::Tarball {
+        name: name.to_string(),
+        version: version.to_string(),
+    })
+}
+
+/// Path of a tarball relative to the root.
+pub fn tarball_path(name: &str, version: &str) -> String {
+    format!("{name}/-/{}", tarball_filename(name, version))
+}
@@ -0,0 +1,738 @@
+use anyhow::{bail, Context};
+use holger_traits::util::{find_files, UtcDateTime};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::UNIX_EPOCH;
+
+pub mod cache;
+pub mod index;
+pub mod name;
+pub mod packument;
+pub mod tarball;
+
+use index::{parse_stored_path, PackageIndex, PackageVersion, StoredPath};
+use name::split_package;
+use packument::{
+    abbreviated_packument, packument, version_document, wants_abbreviated, ABBREVIATED_CONTENT_TYPE,
+};
+use tarball::read_tarball;
+
+/// npm registry backed by a directory of package tarballs in the registry's
+/// URL layout: `<name>/-/<basename>-<version>.tgz`, e.g.
+/// `@types/node/-/node-20.11.0.tgz`, next to an optional `<name>/dist-tags.json`.
+///
+/// Artifacts are addressed by package name: `ArtifactId { namespace: None,
+/// name: "@types/node", version: "20.11.0" }` names the tarball.
+pub struct NpmRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/npm-prod
+    pub index: RwLock<PackageIndex>,
+}
+
+impl NpmRepoFile {
+    /// Open a repository rooted at `root`, indexing every tarball below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = NpmRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            index: RwLock::new(PackageIndex::default()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the files on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = PackageIndex::default();
+        for (rel, path) in find_files(&self.root)? {
+            match parse_stored_path(&rel) {
+                Some(StoredPath::Tarball { name, version }) => {
+                    match load_version(&name, &version, path) {
+                        Ok(version) => index.insert(&name, version),
+                        Err(e) => println!("Skipping {}: {:#}", rel, e),
+                    }
+                }
+                Some(StoredPath::DistTags { name }) => match read_tags(&path) {
+                    Ok(tags) => index.set_tags(&name, tags),
+                    Err(e) => println!("Skipping {}: {:#}", rel, e),
+                },
+                None => println!("Skipping non-package file {}", rel),
+            }
+        }
+        self.artifacts = index
+            .iter()
+            .flat_map(|(name, package)| {
+                package.versions.keys().map(move |version| ArtifactId {
+                    namespace: None,
+                    name: name.to_string(),
+                    version: version.to_string(),
+                })
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, PackageIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, PackageIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn json_response(
+        content_type: &str,
+        document: &serde_json::Value,
+    ) -> anyhow::Result<Http2Response> {
+        Ok((
+            200,
+            vec![("Content-Type".into(), content_type.into())],
+            serde_json::to_vec(document)?,
+        ))
+    }
+}
+
+/// Index a stored tarball, checking that its manifest matches its path.
+pub(crate) fn load_version(
+    name: &str,
+    version: &str,
+    path: PathBuf,
+) -> anyhow::Result<PackageVersion> {
+    let data = std::fs::read(&path)?;
+    let tarball = read_tarball(&data)?;
+    if tarball.name() != Some(name) || tarball.version() != Some(version) {
+        bail!(
+            "package.json names {}@{}",
+            tarball.name().unwrap_or("?"),
+            tarball.version().unwrap_or("?")
+        );
+    }
+    let modified = std::fs::metadata(&path)?
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs());
+    Ok(PackageVersion {
+        version: semver::Version::parse(version)?,
+        tarball,
+        path,
+        modified,
+    })
+}
+
+pub(crate) fn read_tags(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let content = std::fs::read(path)?;
+    serde_json::from_slice(&content).context("parsing dist-tags")
+}
+
+/// Unix seconds as an RFC 3339 UTC timestamp with milliseconds, as the
+/// registry writes them, e.g. `2024-01-02T03:04:05.000Z`.
+pub(crate) fn format_rfc3339(secs: u64) -> String {
+    let t = UtcDateTime::from_unix(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+impl RepositoryBackendTrait for NpmRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        headers: &[(String, String)],
+        _body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Npm repo handle_http2_request.suburl={}", suburl);
+
+        let (path, _query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let not_found = || Ok((404, Vec::new(), b"Not found".to_vec()));
+        let accept = headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case("accept"))
+            .map(|(_, v)| v.as_str());
+
+        match parts.as_slice() {
+            [repo, ..] if *repo == self.name() && method != "GET" && method != "HEAD" => {
+                Ok((405, Vec::new(), b"Method not allowed".to_vec()))
+            }
+
+            // npm ping → /npm-prod/-/ping
+            [repo, "-", "ping"] if *repo == self.name() => {
+                Self::json_response("application/json", &serde_json::json!({}))
+            }
+
+            // npm dist-tag ls → /npm-prod/-/package/@types%2fnode/dist-tags
+            [repo, "-", "package", package @ .., "dist-tags"] if *repo == self.name() => {
+                let index = self.index();
+                let Some(package) = split_package(package)
+                    .filter(|(_, rest)| rest.is_empty())
+                    .and_then(|(name, _)| index.get(&name))
+                else {
+                    return not_found();
+                };
+                Self::json_response("application/json", &serde_json::json!(package.dist_tags()))
+            }
+
+            [repo, rest @ ..] if *repo == self.name() => {
+                let Some((name, rest)) = split_package(rest) else {
+                    return not_found();
+                };
+                let index = self.index();
+                let Some(package) = index.get(&name) else {
+                    return not_found();
+                };
+                match rest {
+                    // Packument → /npm-prod/lodash, /npm-prod/@types%2fnode
+                    [] if wants_abbreviated(accept) => Self::json_response(
+                        ABBREVIATED_CONTENT_TYPE,
+                        &abbreviated_packument(&self.public_url, &name, package),
+                    ),
+                    [] => Self::json_response(
+                        "application/json",
+                        &packument(&self.public_url, &name, package),
+                    ),
+
+                    // Tarball → /npm-prod/@types/node/-/node-20.11.0.tgz
+                    ["-", file] => match parse_stored_path(&format!("{name}/-/{file}")) {
+                        Some(StoredPath::Tarball { version, .. }) => match package.get(&version) {
+                            Some(stored) => Ok((
+                                200,
+                                vec![("Content-Type".into(), "application/octet-stream".into())],
+                                std::fs::read(&stored.path)?,
+                            )),
+                            None => not_found(),
+                        },
+                        _ => not_found(),
+                    },
+
+                    // Version document → /npm-prod/lodash/4.17.21, /npm-prod/lodash/latest
+                    [version_or_tag] => match package.resolve(version_or_tag) {
+                        Some(version) => Self::json_response(
+                            "application/json",
+                            &version_document(&self.public_url, &name, version),
+                        ),
+                        None => not_found(),
+                    },
+
+                    _ => not_found(),
+                }
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                not_found()
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Npm
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self
+            .index()
+            .get(&id.name)
+            .and_then(|p| p.get(&id.version))
+            .map(|v| v.path.clone());
+        match path {
+            Some(path) => Ok(Some(std::fs::read(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, id: &ArtifactId, _data: &[u8]) -> anyhow::Result<()> {
+        bail!(
+            "repository {} does not accept uploads, cannot store {}@{}",
+            self.name,
+            id.name,
+            id.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use serde_json::Value;
+    use sha1::{Digest, Sha1};
+    use std::io::Write;
+
+    /// A tarball as `npm pack` writes it, with everything below `package/`.
+    fn pack(manifest: &str, extra: &[&str]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Default::default()));
+        for (path, content) in [("package/package.json", manifest)]
+            .into_iter()
+            .chain(extra.iter().map(|path| (*path, "{}")))
+        {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn manifest(name: &str, version: &str) -> String {
+        format!(
+            r#"{{"name":"{name}","version":"{version}","dependencies":{{"left-pad":"^1.3.0"}},"scripts":{{"test":"jest"}},"readme":"long"}}"#
+        )
+    }
+
+    fn store(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    fn get(repo: &NpmRepoFile, suburl: &str, accept: Option<&str>) -> Http2Response {
+        let headers: Vec<(String, String)> = accept
+            .map(|a| ("accept".to_string(), a.to_string()))
+            .into_iter()
+            .collect();
+        repo.handle_http2_request("GET", suburl, &headers, b"")
+            .unwrap()
+    }
+
+    fn json(response: &Http2Response) -> Value {
+        serde_json::from_slice(&response.2).unwrap()
+    }
+
+    fn open(root: &Path) -> NpmRepoFile {
+        NpmRepoFile::new(
+            "npm-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/npm-prod".into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_names_and_paths() {
+        use index::tarball_path;
+        use name::is_valid_package_name;
+
+        for valid in [
+            "lodash",
+            "@types/node",
+            "JSONStream",
+            "lodash.merge",
+            "a-b_c~d",
+        ] {
+            assert!(is_valid_package_name(valid), "{valid}");
+        }
+        for invalid in [
+            "", ".bin", "_private", "@types", "@/node", "a/b", "a b", "@a/b/c", "%2e",
+        ] {
+            assert!(!is_valid_package_name(invalid), "{invalid}");
+        }
+        assert_eq!(
+            split_package(&["@types%2fnode", "-", "node-20.11.0.tgz"]),
+            Some((
+                "@types/node".to_string(),
+                ["-", "node-20.11.0.tgz"].as_slice()
+            ))
+        );
+        assert_eq!(
+            split_package(&["%40types", "node"]),
+            Some(("@types/node".to_string(), [].as_slice()))
+        );
+        assert_eq!(split_package(&["..", "x"]), None);
+
+        assert_eq!(
+            tarball_path("@types/node", "20.11.0"),
+            "@types/node/-/node-20.11.0.tgz"
+        );
+        assert_eq!(
+            parse_stored_path("@types/node/-/node-20.11.0-rc.1.tgz"),
+            Some(StoredPath::Tarball {
+                name: "@types/node".into(),
+                version: "20.11.0-rc.1".into()
+            })
+        );
+        assert_eq!(
+            parse_stored_path("lodash/dist-tags.json"),
+            Some(StoredPath::DistTags {
+                name: "lodash".into()
+            })
+        );
+        for invalid in [
+            "lodash/-/lodash-latest.tgz",
+            "lodash/-/underscore-1.0.0.tgz",
+            "lodash/lodash-1.0.0.tgz",
+            "a/b/-/b-1.0.0.tgz",
+            "lodash/package.json",
+        ] {
+            assert_eq!(parse_stored_path(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn computes_integrity() {
+        use tarball::{check_integrity, content_address, integrity};
+
+        let empty = "sha512-z4PhNX7vuL3xVChQ1m2AB9Yg5AULVxXcg/SpIdNs6c5H0NE8XYXysP+DGNKHfuwvY7kxvUdBeoGlODJ6+SfaPg==";
+        assert_eq!(integrity(b""), empty);
+        assert_eq!(check_integrity(b"", empty), Some(true));
+        assert_eq!(check_integrity(b"x", empty), Some(false));
+        // The strongest algorithm decides
+        let both = format!("sha1-2jmj7l5rSw0yVb/vlWAYkK/YBwk= {empty}");
+        assert_eq!(check_integrity(b"", &both), Some(true));
+        assert_eq!(check_integrity(b"x", &both), Some(false));
+        assert_eq!(
+            check_integrity(b"", "sha1-2jmj7l5rSw0yVb/vlWAYkK/YBwk="),
+            Some(true)
+        );
+        assert_eq!(check_integrity(b"", "md5-1B2M2Y8AsgTpgAmY7PhCfg=="), None);
+        let (algorithm, digest) = content_address(empty).unwrap();
+        assert_eq!(algorithm, "sha512");
+        assert!(digest.starts_with("cf83e1357eefb8bd"));
+    }
+
+    #[test]
+    fn serves_registry_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let v1 = pack(&manifest("lodash", "4.17.20"), &[]);
+        store(root, "lodash/-/lodash-4.17.20.tgz", &v1);
+        store(
+            root,
+            "lodash/-/lodash-4.17.21.tgz",
+            &pack(&manifest("lodash", "4.17.21"), &[]),
+        );
+        store(
+            root,
+            "lodash/-/lodash-5.0.0-beta.1.tgz",
+            &pack(&manifest("lodash", "5.0.0-beta.1"), &[]),
+        );
+        store(
+            root,
+            "lodash/dist-tags.json",
+            br#"{"next":"5.0.0-beta.1","legacy":"3.10.1"}"#,
+        );
+        let node = pack(
+            r#"{"name":"@types/node","version":"20.11.0","scripts":{"postinstall":"x"}}"#,
+            &["package/npm-shrinkwrap.json", "package/lib/package.json"],
+        );
+        store(root, "@types/node/-/node-20.11.0.tgz", &node);
+        // package.json disagrees with the path
+        store(
+            root,
+            "left-pad/-/left-pad-1.3.0.tgz",
+            &pack(&manifest("right-pad", "1.3.0"), &[]),
+        );
+        store(root, "README.md", b"notes");
+
+        let repo = open(root);
+        assert_eq!(repo.artifacts.len(), 4);
+
+        let response = get(&repo, "/npm-prod/lodash", None);
+        assert_eq!(
+            (response.0, response.1[0].1.as_str()),
+            (200, "application/json")
+        );
+        let packument = json(&response);
+        assert_eq!(packument["name"], "lodash");
+        assert_eq!(
+            packument["dist-tags"],
+            serde_json::json!({"latest": "4.17.21", "next": "5.0.0-beta.1"})
+        );
+        let version = &packument["versions"]["4.17.20"];
+        assert_eq!(version["_id"], "lodash@4.17.20");
+        assert_eq!(version["readme"], "long");
+        assert_eq!(version["dist"]["shasum"], hex::encode(Sha1::digest(&v1)));
+        assert_eq!(version["dist"]["integrity"], tarball::integrity(&v1));
+        assert_eq!(
+            version["dist"]["tarball"],
+            "https://10.101.1.9:8443/npm-prod/lodash/-/lodash-4.17.20.tgz"
+        );
+        assert!(packument["time"]["4.17.20"]
+            .as_str()
+            .unwrap()
+            .ends_with(".000Z"));
+
+        let response = get(
+            &repo,
+            "/npm-prod/lodash",
+            Some("application/vnd.npm.install-v1+json; q=1.0, application/json; q=0.8, */*"),
+        );
+        assert_eq!(response.1[0].1, "application/vnd.npm.install-v1+json");
+        let abbreviated = json(&response);
+        let version = &abbreviated["versions"]["4.17.21"];
+        assert_eq!(version["dependencies"]["left-pad"], "^1.3.0");
+        assert!(version.get("readme").is_none());
+        assert!(version.get("scripts").is_none());
+        assert!(version.get("hasInstallScript").is_none());
+        assert_eq!(abbreviated["dist-tags"]["latest"], "4.17.21");
+
+        // Scoped packages, either encoding
+        for suburl in [
+            "/npm-prod/@types%2fnode",
+            "/npm-prod/@types%2Fnode",
+            "/npm-prod/@types/node",
+        ] {
+            let abbreviated = json(&get(
+                &repo,
+                suburl,
+                Some("application/vnd.npm.install-v1+json"),
+            ));
+            let version = &abbreviated["versions"]["20.11.0"];
+            assert_eq!(version["_hasShrinkwrap"], true, "{suburl}");
+            assert_eq!(version["hasInstallScript"], true);
+            assert_eq!(
+                version["dist"]["tarball"],
+                "https://10.101.1.9:8443/npm-prod/@types/node/-/node-20.11.0.tgz"
+            );
+        }
+        let (status, headers, body) = get(&repo, "/npm-prod/@types/node/-/node-20.11.0.tgz", None);
+        assert_eq!(
+            (status, headers[0].1.as_str()),
+            (200, "application/octet-stream")
+        );
+        assert_eq!(body, node);
+
+        let document = json(&get(&repo, "/npm-prod/lodash/next", None));
+        assert_eq!(document["version"], "5.0.0-beta.1");
+        let document = json(&get(&repo, "/npm-prod/lodash/4.17.20", None));
+        assert_eq!(document["_id"], "lodash@4.17.20");
+        assert_eq!(
+            json(&get(
+                &repo,
+                "/npm-prod/-/package/@types%2fnode/dist-tags",
+                None
+            )),
+            serde_json::json!({"latest": "20.11.0"})
+        );
+        assert_eq!(get(&repo, "/npm-prod/-/ping", None).0, 200);
+        let id = ArtifactId {
+            namespace: None,
+            name: "@types/node".into(),
+            version: "20.11.0".into(),
+        };
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), node);
+        assert!(repo.put(&id, &node).is_err());
+
+        for missing in [
+            "/npm-prod/left-pad",
+            "/npm-prod/lodash/legacy",
+            "/npm-prod/lodash/3.10.1",
+            "/npm-prod/lodash/-/lodash-3.10.1.tgz",
+            "/npm-prod/lodash/-/lodash-4.17.20.tar.gz",
+            "/npm-prod/lodash/-/../dist-tags.json",
+            "/npm-prod/@types",
+            "/npm-prod/",
+            "/other/lodash",
+        ] {
+            assert_eq!(get(&repo, missing, None).0, 404, "{missing}");
+        }
+        let (status, _, _) = repo
+            .handle_http2_request("PUT", "/npm-prod/lodash", &[], b"{}")
+            .unwrap();
+        assert_eq!(status, 405);
+    }
+
+    /// Add `content` to an npm cache under `key`, the way cacache stores it.
+    fn cache(cacache: &Path, key: &str, content: &[u8], integrity: Option<&str>) {
+        let integrity = integrity.map_or_else(|| tarball::integrity(content), str::to_string);
+        let (algorithm, digest) = tarball::content_address(&integrity).unwrap();
+        store(
+            cacache,
+            &format!(
+                "content-v2/{algorithm}/{}/{}/{}",
+                &digest[..2],
+                &digest[2..4],
+                &digest[4..]
+            ),
+            content,
+        );
+        let entry = serde_json::json!({"key": key, "integrity": integrity, "time": 0, "size": content.len()}).to_string();
+        let line = format!(
+            "\n{}\t{}",
+            hex::encode(Sha1::digest(entry.as_bytes())),
+            entry
+        );
+        let bucket = cacache
+            .join("index-v5/ab/cd")
+            .join(hex::encode(Sha1::digest(key.as_bytes())));
+        std::fs::create_dir_all(bucket.parent().unwrap()).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(bucket)
+            .unwrap();
+        file.write_all(line.as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn imports_npm_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let npm = dir.path().join("npm");
+        let cacache = npm.join("_cacache");
+        let registry = "make-fetch-happen:request-cache:https://registry.npmjs.org";
+
+        let lodash = pack(&manifest("lodash", "4.17.21"), &[]);
+        cache(
+            &cacache,
+            &format!("{registry}/lodash/-/lodash-4.17.21.tgz"),
+            &lodash,
+            None,
+        );
+        let node = pack(&manifest("@types/node", "20.11.0"), &[]);
+        cache(
+            &cacache,
+            &format!("{registry}/@types/node/-/node-20.11.0.tgz"),
+            &node,
+            None,
+        );
+        cache(
+            &cacache,
+            &format!("{registry}/lodash"),
+            br#"{"name":"lodash","dist-tags":{"latest":"4.17.21","next":"4.17.21","old":"3.0.0"},"versions":{}}"#,
+            None,
+        );
+        // Content that does not match its integrity
+        let chalk = pack(&manifest("chalk", "5.3.0"), &[]);
+        cache(
+            &cacache,
+            &format!("{registry}/chalk/-/chalk-5.3.0.tgz"),
+            b"tampered",
+            Some(&tarball::integrity(&chalk)),
+        );
+        // Content that was garbage collected
+        let debug = pack(&manifest("debug", "4.3.4"), &[]);
+        cache(
+            &cacache,
+            &format!("{registry}/debug/-/debug-4.3.4.tgz"),
+            &debug,
+            None,
+        );
+        let (_, digest) = tarball::content_address(&tarball::integrity(&debug)).unwrap();
+        std::fs::remove_file(cacache.join(format!(
+            "content-v2/sha512/{}/{}/{}",
+            &digest[..2],
+            &digest[2..4],
+            &digest[4..]
+        )))
+        .unwrap();
+        // A tarball added by spec
+        let semver = pack(&manifest("semver", "7.6.0"), &[]);
+        cache(
+            &cacache,
+            "pacote:tarball:file:/airgap/semver-7.6.0.tgz",
+            &semver,
+            None,
+        );
+        // A tarball cached under another package's URL
+        cache(
+            &cacache,
+            &format!("{registry}/ms/-/ms-2.1.3.tgz"),
+            &lodash,
+            None,
+        );
+        // A corrupt index line is skipped
+        std::fs::write(
+            cacache.join("index-v5/ab/cd/corrupt"),
+            "0000\t{\"key\":\"x\",\"integrity\":\"sha512-AAAA\"}\n",
+        )
+        .unwrap();
+
+        // Loose tarballs next to the cache
+        let ms = pack(&manifest("ms", "2.1.3"), &[]);
+        store(&npm, "packs/ms-2.1.3.tgz", &ms);
+        store(&npm, "packs/truncated.tgz", &ms[..ms.len() / 2]);
+        store(&npm, "packs/package-lock.json", b"{}");
+
+        let root = dir.path().join("repo");
+        let stored = pack(&manifest("lodash", "4.17.20"), &["package/extra"]);
+        store(&root, "lodash/-/lodash-4.17.20.tgz", &stored);
+        let repo = open(&root);
+        let report = repo.import_npm_cache(&npm).unwrap();
+
+        let names = |ids: &[ArtifactId]| -> Vec<String> {
+            ids.iter()
+                .map(|id| format!("{}@{}", id.name, id.version))
+                .collect()
+        };
+        assert_eq!(
+            names(&report.imported),
+            [
+                "@types/node@20.11.0",
+                "lodash@4.17.21",
+                "semver@7.6.0",
+                "ms@2.1.3"
+            ]
+        );
+        assert!(report.existing.is_empty());
+        let rejected: Vec<&str> = report.rejected.iter().map(|(_, r)| r.as_str()).collect();
+        assert_eq!(rejected.len(), 4, "{rejected:?}");
+        assert!(rejected[0].contains("chalk-5.3.0.tgz: content does not match"));
+        assert!(rejected[1].contains("debug-4.3.4.tgz: content missing from the cache"));
+        assert_eq!(
+            rejected[2],
+            "cached as ms@2.1.3, package.json names lodash@4.17.21"
+        );
+        assert!(rejected[3].starts_with("unreadable tarball"));
+        assert!(report.rejected[3].0.ends_with("packs/truncated.tgz"));
+
+        // Tags naming stored versions are kept
+        let tags = json(&get(&repo, "/npm-prod/-/package/lodash/dist-tags", None));
+        assert_eq!(
+            tags,
+            serde_json::json!({"latest": "4.17.21", "next": "4.17.21"})
+        );
+        assert_eq!(repo.fetch(&report.imported[3]).unwrap().unwrap(), ms);
+
+        // Importing again finds everything stored
+        let report = repo.import_npm_cache(&npm).unwrap();
+        assert!(report.imported.is_empty());
+        assert_eq!(report.existing.len(), 4);
+
+        let reopened = open(&root);
+        assert_eq!(reopened.artifacts.len(), 5);
+        assert_eq!(
+            json(&get(&reopened, "/npm-prod/lodash", None))["dist-tags"],
+            serde_json::json!({"latest": "4.17.21", "next": "4.17.21"})
+        );
+
+        // A different tarball for a stored version is rejected
+        let other = dir.path().join("other");
+        store(
+            &other,
+            "lodash-4.17.20.tgz",
+            &pack(&manifest("lodash", "4.17.20"), &[]),
+        );
+        let report = reopened.import_npm_cache(&other).unwrap();
+        assert_eq!(
+            report.rejected[0].1,
+            "lodash@4.17.20 is already stored with other content"
+        );
+        let id = ArtifactId {
+            namespace: None,
+            name: "lodash".into(),
+            version: "4.17.20".into(),
+        };
+        assert_eq!(reopened.fetch(&id).unwrap().unwrap(), stored);
+    }
+}
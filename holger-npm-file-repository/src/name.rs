@@ -0,0 +1,45 @@
+use holger_traits::util::percent_decode;
+
+/// Whether `name` is a package name the registry would accept: `name` or
+/// `@scope/name`, each part URL-safe and not starting with `.` or `_`.
+/// Uppercase letters are allowed for the sake of legacy packages.
+pub fn is_valid_package_name(name: &str) -> bool {
+    let parts: Vec<&str> = match name.strip_prefix('@') {
+        Some(scoped) => match scoped.split_once('/') {
+            Some((scope, name)) => vec![scope, name],
+            None => return false,
+        },
+        None => vec![name],
+    };
+    name.len() <= 214
+        && parts.iter().all(|part| {
+            !part.is_empty()
+                && !part.starts_with(['.', '_'])
+                && part.bytes().all(|b| {
+                    b.is_ascii_alphanumeric()
+                        || matches!(
+                            b,
+                            b'-' | b'.' | b'_' | b'~' | b'!' | b'\'' | b'(' | b')' | b'*'
+                        )
+                })
+        })
+}
+
+/// Package name of the leading URL segments, the rest of the segments.
+/// Clients send scoped names as `@scope%2fname` or as `@scope/name`.
+pub fn split_package<'a>(segments: &'a [&'a str]) -> Option<(String, &'a [&'a str])> {
+    let first = percent_decode(segments.first()?)?;
+    let (name, rest) = if first.starts_with('@') && !first.contains('/') {
+        let second = percent_decode(segments.get(1)?)?;
+        (format!("{first}/{second}"), &segments[2..])
+    } else {
+        (first, &segments[1..])
+    };
+    is_valid_package_name(&name).then_some((name, rest))
+}
+
+/// `lodash-4.17.21.tgz` for `lodash`, `node-20.11.0.tgz` for `@types/node`.
+pub fn tarball_filename(name: &str, version: &str) -> String {
+    let basename = name.rsplit('/').next().unwrap_or(name);
+    format!("{basename}-{version}.tgz")
+}
@@ -0,0 +1,132 @@
+use serde_json::{json, Map, Value};
+
+use crate::format_rfc3339;
+use crate::index::{tarball_path, Package, PackageVersion};
+
+/// Media type of abbreviated metadata, the documents `npm install` asks for.
+pub const ABBREVIATED_CONTENT_TYPE: &str = "application/vnd.npm.install-v1+json";
+
+/// Manifest fields abbreviated metadata keeps.
+const ABBREVIATED_FIELDS: [&str; 16] = [
+    "name",
+    "version",
+    "deprecated",
+    "dependencies",
+    "optionalDependencies",
+    "devDependencies",
+    "bundleDependencies",
+    "peerDependencies",
+    "peerDependenciesMeta",
+    "acceptDependencies",
+    "bin",
+    "directories",
+    "engines",
+    "cpu",
+    "os",
+    "libc",
+];
+
+/// Whether an `Accept` header asks for abbreviated metadata.
+pub fn wants_abbreviated(accept: Option<&str>) -> bool {
+    accept.is_some_and(|accept| {
+        accept.split(',').any(|range| {
+            range.split(';').next().unwrap_or_default().trim() == ABBREVIATED_CONTENT_TYPE
+        })
+    })
+}
+
+fn dist(base_url: &str, name: &str, version: &PackageVersion) -> Value {
+    json!({
+        "shasum": version.tarball.shasum,
+        "integrity": version.tarball.integrity,
+        "tarball": format!(
+            "{}/{}",
+            base_url.trim_end_matches('/'),
+            tarball_path(name, &version.version.to_string())
+        ),
+    })
+}
+
+/// The version document: the tarball's `package.json` with `_id` and `dist`.
+pub fn version_document(base_url: &str, name: &str, version: &PackageVersion) -> Value {
+    let mut document = version.tarball.manifest.clone();
+    document.insert("_id".into(), format!("{name}@{}", version.version).into());
+    document.insert("dist".into(), dist(base_url, name, version));
+    Value::Object(document)
+}
+
+fn abbreviated_version(base_url: &str, name: &str, version: &PackageVersion) -> Value {
+    let manifest = &version.tarball.manifest;
+    let mut document: Map<String, Value> = ABBREVIATED_FIELDS
+        .iter()
+        .filter_map(|field| Some((field.to_string(), manifest.get(*field)?.clone())))
+        .collect();
+    if version.tarball.has_shrinkwrap {
+        document.insert("_hasShrinkwrap".into(), true.into());
+    }
+    let has_install_script = manifest
+        .get("scripts")
+        .and_then(Value::as_object)
+        .is_some_and(|scripts| {
+            ["preinstall", "install", "postinstall"]
+                .iter()
+                .any(|script| scripts.contains_key(*script))
+        });
+    if has_install_script {
+        document.insert("hasInstallScript".into(), true.into());
+    }
+    document.insert("dist".into(), dist(base_url, name, version));
+    Value::Object(document)
+}
+
+fn modified(package: &Package) -> String {
+    let newest = package.versions.values().map(|v| v.modified).max();
+    format_rfc3339(newest.unwrap_or_default())
+}
+
+/// The full packument of `GET /<name>`.
+pub fn packument(base_url: &str, name: &str, package: &Package) -> Value {
+    let versions: Map<String, Value> = package
+        .versions
+        .values()
+        .map(|v| (v.version.to_string(), version_document(base_url, name, v)))
+        .collect();
+    let mut time = Map::new();
+    let created = package.versions.values().map(|v| v.modified).min();
+    time.insert(
+        "created".into(),
+        format_rfc3339(created.unwrap_or_default()).into(),
+    );
+    time.insert("modified".into(), modified(package).into());
+    for v in package.versions.values() {
+        time.insert(v.version.to_string(), format_rfc3339(v.modified).into());
+    }
+    json!({
+        "_id": name,
+        "name": name,
+        "dist-tags": package.dist_tags(),
+        "versions": versions,
+        "time": time,
+    })
+}
+
+/// The abbreviated packument of `GET /<name>` with
+/// `Accept: application/vnd.npm.install-v1+json`.
+pub fn abbreviated_packument(base_url: &str, name: &str, package: &Package) -> Value {
+    let versions: Map<String, Value> = package
+        .versions
+        .values()
+        .map(|v| {
+            (
+                v.version.to_string(),
+                abbreviated_version(base_url, name, v),
+            )
+        })
+        .collect();
+    json!({
+        "name": name,
+        "modified": modified(package),
+        "dist-tags": package.dist_tags(),
+        "versions": versions,
+    })
+}
@@ -0,0 +1,106 @@
+use std::io::Read;
+
+use anyhow::{bail, Context};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use flate2::read::GzDecoder;
+use serde_json::{Map, Value};
+use sha1::Sha1;
+use sha2::{Digest, Sha512};
+
+/// What the registry reports about a package tarball.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tarball {
+    /// The tarball's `package.json`
+    pub manifest: Map<String, Value>,
+    /// Whether the tarball ships an `npm-shrinkwrap.json`
+    pub has_shrinkwrap: bool,
+    /// `dist.shasum`: sha1 in hex
+    pub shasum: String,
+    /// `dist.integrity`: sha512 in Subresource Integrity form
+    pub integrity: String,
+}
+
+impl Tarball {
+    pub fn name(&self) -> Option<&str> {
+        self.manifest.get("name")?.as_str()
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.manifest.get("version")?.as_str()
+    }
+}
+
+/// Read the manifest of a package tarball. npm packs everything below a
+/// single top-level directory, usually `package/`.
+pub fn read_tarball(data: &[u8]) -> anyhow::Result<Tarball> {
+    let mut archive = tar::Archive::new(GzDecoder::new(data));
+    let mut manifest = None;
+    let mut has_shrinkwrap = false;
+    for entry in archive.entries().context("reading tarball")? {
+        let mut entry = entry.context("reading tarball")?;
+        let path = entry.path()?.into_owned();
+        let components: Vec<_> = path.components().collect();
+        let [_, file] = components.as_slice() else {
+            continue;
+        };
+        match file.as_os_str().to_str() {
+            Some("package.json") if manifest.is_none() => {
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+                manifest = Some(
+                    serde_json::from_slice::<Map<String, Value>>(&content)
+                        .context("parsing package.json")?,
+                );
+            }
+            Some("npm-shrinkwrap.json") => has_shrinkwrap = true,
+            _ => {}
+        }
+    }
+    let Some(manifest) = manifest else {
+        bail!("tarball has no package.json");
+    };
+    Ok(Tarball {
+        manifest,
+        has_shrinkwrap,
+        shasum: hex::encode(Sha1::digest(data)),
+        integrity: integrity(data),
+    })
+}
+
+/// sha512 of `data` in Subresource Integrity form, `sha512-<base64>`.
+pub fn integrity(data: &[u8]) -> String {
+    format!("sha512-{}", STANDARD.encode(Sha512::digest(data)))
+}
+
+/// Check `data` against a Subresource Integrity string such as
+/// `sha512-<base64> sha1-<base64>`, using its strongest supported algorithm.
+/// `None` if it has no sha512 or sha1 hash.
+pub fn check_integrity(data: &[u8], sri: &str) -> Option<bool> {
+    let hashes: Vec<(&str, &str)> = sri
+        .split_whitespace()
+        .filter_map(|hash| hash.split('?').next()?.split_once('-'))
+        .collect();
+    for (algorithm, digest) in [
+        ("sha512", STANDARD.encode(Sha512::digest(data))),
+        ("sha1", STANDARD.encode(Sha1::digest(data))),
+    ] {
+        let recorded: Vec<&str> = hashes
+            .iter()
+            .filter(|(a, _)| *a == algorithm)
+            .map(|(_, d)| *d)
+            .collect();
+        if !recorded.is_empty() {
+            return Some(recorded.contains(&digest.as_str()));
+        }
+    }
+    None
+}
+
+/// Hex digest of the first hash of a Subresource Integrity string, with its
+/// algorithm: the address of the content in an npm cache.
+pub fn content_address(sri: &str) -> Option<(&str, String)> {
+    let (algorithm, digest) = sri.split_whitespace().next()?.split_once('-')?;
+    let digest = digest.split('?').next()?;
+    Some((algorithm, hex::encode(STANDARD.decode(digest).ok()?)))
+}
@@ -0,0 +1,43 @@
+use holger_oci_file_repository::OciRepoFile;
+
+/// cargo run --example import_image -- /var/lib/holger/oci-prod /airgap/oci/alpine.tar
+/// cargo run --example import_image -- /var/lib/holger/oci-prod /airgap/oci/alpine library/alpine
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(root), Some(source)) = (args.get(1), args.get(2)) else {
+        anyhow::bail!(
+            "usage: import_image <repository root> <docker save archive | oci-layout dir> [image name]"
+        );
+    };
+    let repo = OciRepoFile::new(
+        "oci-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/oci-prod".into(),
+    )?;
+    let source = std::path::Path::new(source);
+    let report = if source.is_dir() {
+        let Some(image) = args.get(3) else {
+            anyhow::bail!("an image name is required to import an oci-layout directory");
+        };
+        repo.import_oci_layout(source, image)?
+    } else {
+        repo.import_docker_save(source)?
+    };
+
+    for id in &report.imported {
+        println!("imported {}:{}", id.name, id.version);
+    }
+    for id in &report.existing {
+        println!("existing {}:{}", id.name, id.version);
+    }
+    for (path, reason) in &report.rejected {
+        println!("rejected {}: {}", path.display(), reason);
+    }
+    println!(
+        "{} imported, {} existing, {} rejected",
+        report.imported.len(),
+        report.existing.len(),
+        report.rejected.len()
+    );
+    Ok(())
+}
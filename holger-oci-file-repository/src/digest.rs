@@ -0,0 +1,86 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest as _, Sha256, Sha512};
+
+/// A content digest such as `sha256:<64 hex>`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digest {
+    pub algorithm: Algorithm,
+    pub hex: String,
+}
+
+/// The digest algorithms the OCI image spec registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Algorithm {
+    Sha256,
+    Sha512,
+}
+
+impl Algorithm {
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+        }
+    }
+
+    fn hex_len(self) -> usize {
+        match self {
+            Algorithm::Sha256 => 64,
+            Algorithm::Sha512 => 128,
+        }
+    }
+
+    fn hex_digest(self, data: &[u8]) -> String {
+        match self {
+            Algorithm::Sha256 => hex::encode(Sha256::digest(data)),
+            Algorithm::Sha512 => hex::encode(Sha512::digest(data)),
+        }
+    }
+}
+
+impl Digest {
+    /// Parse `<algorithm>:<lowercase hex>`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (algorithm, hex) = text.split_once(':')?;
+        let algorithm = match algorithm {
+            "sha256" => Algorithm::Sha256,
+            "sha512" => Algorithm::Sha512,
+            _ => return None,
+        };
+        let valid = hex.len() == algorithm.hex_len()
+            && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        valid.then(|| Digest {
+            algorithm,
+            hex: hex.to_string(),
+        })
+    }
+
+    /// The sha256 digest of `data`.
+    pub fn of(data: &[u8]) -> Self {
+        Digest {
+            algorithm: Algorithm::Sha256,
+            hex: Algorithm::Sha256.hex_digest(data),
+        }
+    }
+
+    /// Whether `data` hashes to this digest.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.algorithm.hex_digest(data) == self.hex
+    }
+
+    /// `blobs/<algorithm>/<hex>` below an image layout directory.
+    pub fn blob_path(&self, layout: &Path) -> PathBuf {
+        layout
+            .join("blobs")
+            .join(self.algorithm.name())
+            .join(&self.hex)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), self.hex)
+    }
+}
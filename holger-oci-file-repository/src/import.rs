@@ -0,0 +1,386 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use holger_traits::util::write_atomic;
+use holger_traits::ArtifactId;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest as _, Sha256};
+
+use crate::digest::{Algorithm, Digest};
+use crate::layout::{
+    is_valid_image_name, read_index, references, tag_of, Descriptor, Image, Index, INDEX_FILE,
+    OCI_CONFIG, OCI_LAYER_TAR, OCI_LAYOUT_FILE, OCI_MANIFEST, REF_NAME,
+};
+use crate::OciRepoFile;
+
+/// Outcome of importing image layouts or `docker save` archives.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Stored manifests, by image name and tag (or digest when untagged)
+    pub imported: Vec<ArtifactId>,
+    /// Manifests already stored under the same tag
+    pub existing: Vec<ArtifactId>,
+    /// Manifests that were not stored, with the file they came from and the reason
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+/// A manifest ready to publish, with every blob it needs already verified.
+struct Candidate {
+    descriptor: Descriptor,
+    tag: Option<String>,
+    blobs: Vec<(Digest, PathBuf)>,
+}
+
+/// Verify a descriptor and everything it points at in an image layout: each
+/// blob must be present (foreign layers excepted), have the recorded size and
+/// hash to its digest.
+fn verify_tree(layout: &Path, root: &Descriptor) -> Result<Vec<(Digest, PathBuf)>, String> {
+    let mut blobs = Vec::new();
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![root.clone()];
+    while let Some(descriptor) = pending.pop() {
+        let digest = Digest::parse(&descriptor.digest)
+            .ok_or_else(|| format!("invalid digest {}", descriptor.digest))?;
+        if !seen.insert(digest.clone()) {
+            continue;
+        }
+        let path = digest.blob_path(layout);
+        if !path.is_file() {
+            if descriptor.urls.is_empty() {
+                return Err(format!("blob {digest} is missing"));
+            }
+            continue;
+        }
+        let data = std::fs::read(&path).map_err(|e| format!("reading blob {digest}: {e}"))?;
+        if data.len() as u64 != descriptor.size {
+            return Err(format!(
+                "blob {digest} has {} bytes, its descriptor says {}",
+                data.len(),
+                descriptor.size
+            ));
+        }
+        if !digest.matches(&data) {
+            return Err(format!("blob {digest} does not match its digest"));
+        }
+        pending.extend(references(&descriptor.media_type, &data).map_err(|e| format!("{e:#}"))?);
+        blobs.push((digest, path));
+    }
+    Ok(blobs)
+}
+
+/// Stream a tar archive, gzipped or not, into `dir`.
+fn unpack(archive: &Path, dir: &Path) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(File::open(archive)?);
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        tar::Archive::new(GzDecoder::new(reader)).unpack(dir)?;
+    } else {
+        tar::Archive::new(reader).unpack(dir)?;
+    }
+    Ok(())
+}
+
+/// Image name and tag of a `docker save` repo tag such as
+/// `registry.example.com:5000/team/app:1.0`; the registry host is dropped.
+fn split_repo_tag(repo_tag: &str) -> Option<(String, String)> {
+    let (name, tag) = repo_tag.rsplit_once(':')?;
+    if name.is_empty() || tag.contains('/') {
+        return None;
+    }
+    let name = match name.split_once('/') {
+        Some((host, rest)) if host.contains(['.', ':']) || host == "localhost" => rest,
+        _ => name,
+    };
+    (is_valid_image_name(name) && tag_of(tag) == Some(tag))
+        .then(|| (name.to_string(), tag.to_string()))
+}
+
+/// A path from an archive's `manifest.json`, kept inside the unpacked archive.
+fn archive_path(dir: &Path, rel: &str) -> Option<PathBuf> {
+    let rel = Path::new(rel);
+    rel.components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| dir.join(rel))
+}
+
+/// The digest a `docker save` file name spells out: `<hex>.json` for
+/// configs, `blobs/sha256/<hex>` in newer archives.
+fn digest_in_name(rel: &str) -> Option<Digest> {
+    let file = rel.rsplit('/').next()?;
+    Digest::parse(&format!("sha256:{}", file.trim_end_matches(".json")))
+}
+
+/// sha256 of a layer's uncompressed tar, the image config's `diff_id`.
+fn diff_id(data: &[u8]) -> anyhow::Result<(Digest, bool)> {
+    if !data.starts_with(&[0x1f, 0x8b]) {
+        return Ok((Digest::of(data), false));
+    }
+    let mut decoder = GzDecoder::new(data);
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = decoder.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    let digest = Digest {
+        algorithm: Algorithm::Sha256,
+        hex: hex::encode(hasher.finalize()),
+    };
+    Ok((digest, true))
+}
+
+/// One image of a `docker save` archive's `manifest.json`.
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SaveEntry {
+    config: String,
+    repo_tags: Option<Vec<String>>,
+    layers: Vec<String>,
+}
+
+impl OciRepoFile {
+    /// Store the tagged manifests of an OCI image layout directory (as
+    /// written by `skopeo copy ... oci:<dir>` or `crane pull --format=oci`)
+    /// as `image`. Untagged manifests are stored by digest.
+    pub fn import_oci_layout(&self, dir: &Path, image: &str) -> anyhow::Result<IngestReport> {
+        anyhow::ensure!(is_valid_image_name(image), "invalid image name {image}");
+        let index = read_index(dir).with_context(|| format!("reading {}", dir.display()))?;
+        let mut report = IngestReport::default();
+        let mut candidates = Vec::new();
+        for mut descriptor in index.manifests {
+            let tag = descriptor.tag().map(str::to_string);
+            let label = tag.clone().unwrap_or_else(|| descriptor.digest.clone());
+            match verify_tree(dir, &descriptor) {
+                Ok(blobs) => {
+                    descriptor.annotations.remove(REF_NAME);
+                    candidates.push(Candidate {
+                        descriptor,
+                        tag,
+                        blobs,
+                    })
+                }
+                Err(reason) => report
+                    .rejected
+                    .push((dir.to_path_buf(), format!("{image}:{label}: {reason}"))),
+            }
+        }
+        self.publish(image, candidates, dir, &mut report)?;
+        Ok(report)
+    }
+
+    /// Store the images of a `docker save` archive, optionally gzipped,
+    /// under the names and tags it records. Each layer must hash to the
+    /// `diff_id` the image config records for it.
+    pub fn import_docker_save(&self, archive: &Path) -> anyhow::Result<IngestReport> {
+        // Hidden below the root, so a crash leaves nothing that looks like content
+        std::fs::create_dir_all(&self.root)?;
+        let dir = tempfile::tempdir_in(&self.root)?;
+        unpack(archive, dir.path()).with_context(|| format!("unpacking {}", archive.display()))?;
+        let report = self.import_unpacked_save(archive, dir.path())?;
+        dir.close()?;
+        Ok(report)
+    }
+
+    fn import_unpacked_save(&self, archive: &Path, dir: &Path) -> anyhow::Result<IngestReport> {
+        let entries: Vec<SaveEntry> =
+            serde_json::from_slice(&std::fs::read(dir.join("manifest.json"))?)
+                .context("parsing manifest.json")?;
+
+        let mut report = IngestReport::default();
+        let mut images: BTreeMap<String, Vec<Candidate>> = BTreeMap::new();
+        for (n, entry) in entries.into_iter().enumerate() {
+            let repo_tags = entry.repo_tags.clone().unwrap_or_default();
+            let label = repo_tags
+                .first()
+                .cloned()
+                .unwrap_or_else(|| format!("image {}", n + 1));
+            let tags: Option<Vec<(String, String)>> =
+                repo_tags.iter().map(|t| split_repo_tag(t)).collect();
+            let reason = match tags {
+                Some(tags) if tags.is_empty() => Err("untagged image".to_string()),
+                Some(tags) => self.save_manifest(dir, &entry, n).map(|c| (tags, c)),
+                None => Err(format!("invalid repo tag in {}", repo_tags.join(", "))),
+            };
+            match reason {
+                Ok((tags, (descriptor, blobs))) => {
+                    for (image, tag) in tags {
+                        images.entry(image).or_default().push(Candidate {
+                            descriptor: descriptor.clone(),
+                            tag: Some(tag),
+                            blobs: blobs.clone(),
+                        });
+                    }
+                }
+                Err(reason) => report
+                    .rejected
+                    .push((archive.to_path_buf(), format!("{label}: {reason}"))),
+            }
+        }
+        for (image, candidates) in images {
+            self.publish(&image, candidates, archive, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    /// Verify one image of an unpacked `docker save` archive and write an
+    /// OCI manifest for it next to its files.
+    fn save_manifest(
+        &self,
+        dir: &Path,
+        entry: &SaveEntry,
+        n: usize,
+    ) -> Result<(Descriptor, Vec<(Digest, PathBuf)>), String> {
+        let read = |rel: &str| -> Result<(PathBuf, Vec<u8>), String> {
+            let path = archive_path(dir, rel).ok_or_else(|| format!("invalid path {rel}"))?;
+            let data = std::fs::read(&path).map_err(|_| format!("{rel} is missing"))?;
+            Ok((path, data))
+        };
+
+        let (config_path, config) = read(&entry.config)?;
+        let config_digest = Digest::of(&config);
+        if digest_in_name(&entry.config).is_some_and(|d| d != config_digest) {
+            return Err(format!("{} does not match its digest", entry.config));
+        }
+        let diff_ids: Vec<String> = serde_json::from_slice::<serde_json::Value>(&config)
+            .ok()
+            .and_then(|c| serde_json::from_value(c["rootfs"]["diff_ids"].clone()).ok())
+            .ok_or_else(|| format!("{} has no rootfs.diff_ids", entry.config))?;
+        if diff_ids.len() != entry.layers.len() {
+            return Err(format!(
+                "{} layers, the image config records {} diff_ids",
+                entry.layers.len(),
+                diff_ids.len()
+            ));
+        }
+
+        let mut blobs = vec![(config_digest.clone(), config_path)];
+        let mut layers = Vec::new();
+        for (rel, expected) in entry.layers.iter().zip(&diff_ids) {
+            let (path, data) = read(rel)?;
+            let digest = Digest::of(&data);
+            if digest_in_name(rel).is_some_and(|d| d != digest) {
+                return Err(format!("{rel} does not match its digest"));
+            }
+            let (diff_id, gzipped) = diff_id(&data).map_err(|e| format!("{rel}: {e:#}"))?;
+            if diff_id.to_string() != *expected {
+                return Err(format!(
+                    "{rel} hashes to {diff_id}, the image config records {expected}"
+                ));
+            }
+            let media_type = if gzipped {
+                format!("{OCI_LAYER_TAR}+gzip")
+            } else {
+                OCI_LAYER_TAR.to_string()
+            };
+            layers.push(
+                json!({"mediaType": media_type, "digest": digest.to_string(), "size": data.len()}),
+            );
+            blobs.push((digest, path));
+        }
+
+        let manifest = serde_json::to_vec(&json!({
+            "schemaVersion": 2,
+            "mediaType": OCI_MANIFEST,
+            "config": {"mediaType": OCI_CONFIG, "digest": config_digest.to_string(), "size": config.len()},
+            "layers": layers,
+        }))
+        .map_err(|e| e.to_string())?;
+        let digest = Digest::of(&manifest);
+        let path = dir.join(".manifests").join(n.to_string());
+        write_atomic(&path, &manifest).map_err(|e| format!("{e:#}"))?;
+        blobs.push((digest.clone(), path));
+        let descriptor = Descriptor {
+            media_type: OCI_MANIFEST.into(),
+            digest: digest.to_string(),
+            size: manifest.len() as u64,
+            urls: Vec::new(),
+            annotations: BTreeMap::new(),
+            extra: serde_json::Map::new(),
+        };
+        Ok((descriptor, blobs))
+    }
+
+    /// Copy the blobs of verified manifests into the image's layout and add
+    /// them to its `index.json`. Tags are never moved to another manifest.
+    fn publish(
+        &self,
+        image: &str,
+        candidates: Vec<Candidate>,
+        source: &Path,
+        report: &mut IngestReport,
+    ) -> anyhow::Result<()> {
+        if candidates.is_empty() {
+            return Ok(());
+        }
+        let layout = self.root.join(image);
+        let mut index = if layout.join(INDEX_FILE).is_file() {
+            read_index(&layout)?
+        } else {
+            Index::default()
+        };
+        let mut changed = false;
+        for mut candidate in candidates {
+            let id = ArtifactId {
+                namespace: None,
+                name: image.to_string(),
+                version: candidate
+                    .tag
+                    .clone()
+                    .unwrap_or_else(|| candidate.descriptor.digest.clone()),
+            };
+            let stored = index.manifests.iter().find(|d| match &candidate.tag {
+                Some(tag) => d.tag() == Some(tag),
+                None => d.digest == candidate.descriptor.digest,
+            });
+            match stored {
+                Some(stored) if stored.digest == candidate.descriptor.digest => {
+                    report.existing.push(id);
+                    continue;
+                }
+                Some(stored) => {
+                    report.rejected.push((
+                        source.to_path_buf(),
+                        format!("{image}:{} already names {}", id.version, stored.digest),
+                    ));
+                    continue;
+                }
+                None => {}
+            }
+            for (digest, path) in &candidate.blobs {
+                let target = digest.blob_path(&layout);
+                if !target.is_file() {
+                    write_atomic(&target, &std::fs::read(path)?)?;
+                }
+            }
+            if let Some(tag) = &candidate.tag {
+                candidate
+                    .descriptor
+                    .annotations
+                    .insert(REF_NAME.into(), tag.clone());
+            }
+            index.manifests.push(candidate.descriptor);
+            report.imported.push(id);
+            changed = true;
+        }
+        if changed {
+            write_atomic(
+                &layout.join(OCI_LAYOUT_FILE),
+                br#"{"imageLayoutVersion":"1.0.0"}"#,
+            )?;
+            write_atomic(
+                &layout.join(INDEX_FILE),
+                &serde_json::to_vec_pretty(&index)?,
+            )?;
+            let loaded = Image::load(layout)?;
+            self.index_mut().insert(image.to_string(), loaded);
+        }
+        Ok(())
+    }
+}
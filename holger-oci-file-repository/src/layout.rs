@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use holger_traits::util::relative_path;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::digest::Digest;
+
+/// Marker file of an OCI image layout directory.
+pub const OCI_LAYOUT_FILE: &str = "oci-layout";
+pub const INDEX_FILE: &str = "index.json";
+/// Annotation naming the tag of a descriptor in `index.json`.
+pub const REF_NAME: &str = "org.opencontainers.image.ref.name";
+
+pub const OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+pub const OCI_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const OCI_CONFIG: &str = "application/vnd.oci.image.config.v1+json";
+pub const OCI_LAYER_TAR: &str = "application/vnd.oci.image.layer.v1.tar";
+pub const DOCKER_MANIFEST_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+pub const DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+
+pub fn is_index(media_type: &str) -> bool {
+    media_type == OCI_INDEX || media_type == DOCKER_MANIFEST_LIST
+}
+
+pub fn is_manifest(media_type: &str) -> bool {
+    media_type == OCI_MANIFEST || media_type == DOCKER_MANIFEST
+}
+
+/// A content descriptor; fields this crate does not use, such as
+/// `platform`, are kept as they are.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub urls: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Descriptor {
+    /// The tag `index.json` gives this descriptor, if any.
+    pub fn tag(&self) -> Option<&str> {
+        tag_of(self.annotations.get(REF_NAME)?)
+    }
+}
+
+/// `index.json` of an image layout, or an image index blob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(default)]
+    pub manifests: Vec<Descriptor>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Index {
+            schema_version: 2,
+            media_type: Some(OCI_INDEX.into()),
+            manifests: Vec::new(),
+            extra: Map::new(),
+        }
+    }
+}
+
+/// The descriptors a manifest blob points at: the manifests of an image
+/// index, or the config and layers of an image manifest. `media_type` is
+/// the one of the descriptor the blob was reached by.
+pub fn references(media_type: &str, data: &[u8]) -> anyhow::Result<Vec<Descriptor>> {
+    #[derive(Deserialize)]
+    struct Manifest {
+        config: Descriptor,
+        #[serde(default)]
+        layers: Vec<Descriptor>,
+    }
+
+    if is_index(media_type) {
+        let index: Index = serde_json::from_slice(data).context("parsing image index")?;
+        Ok(index.manifests)
+    } else if is_manifest(media_type) {
+        let manifest: Manifest = serde_json::from_slice(data).context("parsing image manifest")?;
+        Ok(std::iter::once(manifest.config)
+            .chain(manifest.layers)
+            .collect())
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// The tag of a `ref.name` annotation: `3.19`, or the tag of a full
+/// reference such as `docker.io/library/alpine:3.19`.
+pub fn tag_of(ref_name: &str) -> Option<&str> {
+    let name = ref_name.rsplit('/').next().unwrap_or(ref_name);
+    let tag = name.rsplit_once(':').map_or(ref_name, |(_, tag)| tag);
+    is_valid_tag(tag).then_some(tag)
+}
+
+/// `[a-zA-Z0-9_][a-zA-Z0-9._-]{0,127}`
+pub fn is_valid_tag(tag: &str) -> bool {
+    tag.len() <= 128
+        && tag.bytes().enumerate().all(|(i, b)| {
+            b.is_ascii_alphanumeric() || b == b'_' || (i > 0 && matches!(b, b'.' | b'-'))
+        })
+        && !tag.is_empty()
+}
+
+/// Repository names of the distribution spec: `/`-separated components of
+/// lowercase alphanumerics joined by `.`, `_`, `__` or runs of `-`.
+pub fn is_valid_image_name(name: &str) -> bool {
+    name.len() <= 255 && name.split('/').all(is_valid_component)
+}
+
+fn is_valid_component(component: &str) -> bool {
+    let alnum = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    let mut rest = component;
+    loop {
+        let run = rest.find(|c| !alnum(c)).unwrap_or(rest.len());
+        if run == 0 {
+            return false;
+        }
+        rest = &rest[run..];
+        if rest.is_empty() {
+            return true;
+        }
+        let separator = &rest[..rest.find(alnum).unwrap_or(rest.len())];
+        if !matches!(separator, "." | "_" | "__") && !separator.bytes().all(|b| b == b'-') {
+            return false;
+        }
+        rest = &rest[separator.len()..];
+    }
+}
+
+/// One stored image: an OCI image layout directory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Image {
+    pub layout: PathBuf,
+    pub tags: BTreeMap<String, Digest>,
+    /// Media type of every manifest reachable from `index.json`
+    pub manifests: BTreeMap<Digest, String>,
+}
+
+impl Image {
+    /// Read the tags and manifests of an image layout.
+    pub fn load(layout: PathBuf) -> anyhow::Result<Self> {
+        let index = read_index(&layout)?;
+        let mut image = Image {
+            layout,
+            ..Image::default()
+        };
+        let mut pending = index.manifests;
+        while let Some(descriptor) = pending.pop() {
+            let Some(digest) = Digest::parse(&descriptor.digest) else {
+                bail!("invalid digest {}", descriptor.digest);
+            };
+            if let Some(tag) = descriptor.tag() {
+                image.tags.insert(tag.to_string(), digest.clone());
+            }
+            if image.manifests.contains_key(&digest) {
+                continue;
+            }
+            if is_index(&descriptor.media_type) {
+                if let Ok(data) = std::fs::read(digest.blob_path(&image.layout)) {
+                    pending.extend(references(&descriptor.media_type, &data)?);
+                }
+            }
+            image.manifests.insert(digest, descriptor.media_type);
+        }
+        Ok(image)
+    }
+
+    /// The manifest a tag or digest names, with its media type.
+    pub fn resolve(&self, reference: &str) -> Option<(&Digest, &str)> {
+        let digest = match Digest::parse(reference) {
+            Some(digest) => digest,
+            None => self.tags.get(reference)?.clone(),
+        };
+        self.manifests
+            .get_key_value(&digest)
+            .map(|(digest, media_type)| (digest, media_type.as_str()))
+    }
+}
+
+/// `index.json` of an image layout, checking its `oci-layout` marker.
+pub fn read_index(layout: &Path) -> anyhow::Result<Index> {
+    let marker: Value = serde_json::from_slice(&std::fs::read(layout.join(OCI_LAYOUT_FILE))?)
+        .context("parsing oci-layout")?;
+    if marker["imageLayoutVersion"] != "1.0.0" {
+        bail!(
+            "unsupported image layout version {}",
+            marker["imageLayoutVersion"]
+        );
+    }
+    serde_json::from_slice(&std::fs::read(layout.join(INDEX_FILE))?).context("parsing index.json")
+}
+
+/// Every image layout below `root` with its `/`-separated path as image
+/// name, skipping hidden entries and blob directories.
+pub fn find_layouts(root: &Path) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let mut found = Vec::new();
+    if !root.is_dir() {
+        return Ok(found);
+    }
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let is_layout = dir.join(OCI_LAYOUT_FILE).is_file();
+        if is_layout {
+            found.push((relative_path(root, &dir), dir.clone()));
+        }
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            if file_name.to_string_lossy().starts_with('.') || (is_layout && file_name == "blobs") {
+                continue;
+            }
+            if entry.path().is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
@@ -0,0 +1,691 @@
+use anyhow::bail;
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub mod digest;
+pub mod import;
+pub mod layout;
+
+use digest::Digest;
+use layout::{find_layouts, is_valid_image_name, Image};
+
+/// Header naming the API version on `/v2/`, which clients probe first.
+const API_VERSION_HEADER: &str = "Docker-Distribution-API-Version";
+const CONTENT_DIGEST_HEADER: &str = "Docker-Content-Digest";
+
+/// OCI distribution registry (pull only) backed by one OCI image layout
+/// directory per image: `<image>/oci-layout`, `<image>/index.json` and
+/// `<image>/blobs/sha256/<hex>`, e.g. `library/alpine/index.json`. Tags are
+/// the `org.opencontainers.image.ref.name` annotations of `index.json`.
+///
+/// Clients address images as `<host>/<repo>/<image>`, e.g.
+/// `docker pull 10.101.1.9:8443/oci-prod/library/alpine:3.19`, and artifacts
+/// as `ArtifactId { namespace: None, name: "library/alpine", version: "3.19" }`.
+pub struct OciRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/oci-prod
+    pub index: RwLock<BTreeMap<String, Image>>,
+}
+
+impl OciRepoFile {
+    /// Open a repository rooted at `root`, indexing every image layout below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = OciRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            index: RwLock::new(BTreeMap::new()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the image layouts on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = BTreeMap::new();
+        for (name, layout) in find_layouts(&self.root)? {
+            if !is_valid_image_name(&name) {
+                println!("Skipping image layout with invalid name {}", name);
+                continue;
+            }
+            match Image::load(layout) {
+                Ok(image) => {
+                    index.insert(name, image);
+                }
+                Err(e) => println!("Skipping {}: {:#}", name, e),
+            }
+        }
+        self.artifacts = index
+            .iter()
+            .flat_map(|(name, image)| {
+                image.tags.keys().map(move |tag| ArtifactId {
+                    namespace: None,
+                    name: name.clone(),
+                    version: tag.clone(),
+                })
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, BTreeMap<String, Image>> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, BTreeMap<String, Image>> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// A manifest of an image by tag or digest, checked against its digest.
+    fn read_manifest(&self, image: &str, reference: &str) -> Lookup {
+        let index = self.index();
+        let Some(image) = index.get(image) else {
+            return Lookup::NameUnknown;
+        };
+        let Some((digest, media_type)) = image.resolve(reference) else {
+            return Lookup::Unknown;
+        };
+        read_verified(&image.layout, digest, media_type)
+    }
+
+    /// A blob of an image, checked against its digest.
+    fn read_blob(&self, image: &str, digest: &Digest) -> Lookup {
+        let index = self.index();
+        let Some(image) = index.get(image) else {
+            return Lookup::NameUnknown;
+        };
+        read_verified(&image.layout, digest, "application/octet-stream")
+    }
+}
+
+/// Result of looking up stored content.
+enum Lookup {
+    Found(Digest, String, Vec<u8>),
+    NameUnknown,
+    Unknown,
+    Corrupt(Digest),
+}
+
+fn read_verified(layout: &Path, digest: &Digest, content_type: &str) -> Lookup {
+    let Ok(data) = std::fs::read(digest.blob_path(layout)) else {
+        return Lookup::Unknown;
+    };
+    if !digest.matches(&data) {
+        return Lookup::Corrupt(digest.clone());
+    }
+    Lookup::Found(digest.clone(), content_type.to_string(), data)
+}
+
+/// An error response in the distribution spec's JSON format.
+fn error(status: u16, code: &str, message: &str) -> Http2Response {
+    let body = serde_json::json!({"errors": [{"code": code, "message": message}]});
+    (
+        status,
+        vec![("Content-Type".into(), "application/json".into())],
+        body.to_string().into_bytes(),
+    )
+}
+
+impl RepositoryBackendTrait for OciRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        _headers: &[(String, String)],
+        _body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Oci repo handle_http2_request.suburl={}", suburl);
+
+        let (path, query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let lookup_response = |lookup: Lookup, unknown: (&str, &str)| match lookup {
+            Lookup::Found(digest, content_type, data) => (
+                200,
+                vec![
+                    ("Content-Type".into(), content_type),
+                    (CONTENT_DIGEST_HEADER.into(), digest.to_string()),
+                ],
+                data,
+            ),
+            Lookup::NameUnknown => {
+                error(404, "NAME_UNKNOWN", "repository name not known to registry")
+            }
+            Lookup::Unknown => error(404, unknown.0, unknown.1),
+            Lookup::Corrupt(digest) => {
+                println!("Stored content of {} does not match its digest", digest);
+                error(
+                    500,
+                    "DIGEST_INVALID",
+                    "stored content does not match its digest",
+                )
+            }
+        };
+
+        match parts.as_slice() {
+            ["v2", ..] if method != "GET" && method != "HEAD" => {
+                Ok(error(405, "UNSUPPORTED", "this registry only serves pulls"))
+            }
+
+            // API version check → /v2/
+            ["v2"] | ["v2", ""] => Ok((
+                200,
+                vec![
+                    ("Content-Type".into(), "application/json".into()),
+                    (API_VERSION_HEADER.into(), "registry/2.0".into()),
+                ],
+                b"{}".to_vec(),
+            )),
+
+            // Manifest → /v2/oci-prod/library/alpine/manifests/3.19
+            ["v2", repo, image @ .., "manifests", reference]
+                if *repo == self.name() && !image.is_empty() =>
+            {
+                Ok(lookup_response(
+                    self.read_manifest(&image.join("/"), reference),
+                    ("MANIFEST_UNKNOWN", "manifest unknown to registry"),
+                ))
+            }
+
+            // Blob → /v2/oci-prod/library/alpine/blobs/sha256:<hex>
+            ["v2", repo, image @ .., "blobs", digest]
+                if *repo == self.name() && !image.is_empty() =>
+            {
+                let Some(digest) = Digest::parse(digest) else {
+                    return Ok(error(400, "DIGEST_INVALID", "invalid digest"));
+                };
+                Ok(lookup_response(
+                    self.read_blob(&image.join("/"), &digest),
+                    ("BLOB_UNKNOWN", "blob unknown to registry"),
+                ))
+            }
+
+            // Tags → /v2/oci-prod/library/alpine/tags/list?n=10&last=3.18
+            ["v2", repo, image @ .., "tags", "list"]
+                if *repo == self.name() && !image.is_empty() =>
+            {
+                let image = image.join("/");
+                let index = self.index();
+                let Some(stored) = index.get(&image) else {
+                    return Ok(error(
+                        404,
+                        "NAME_UNKNOWN",
+                        "repository name not known to registry",
+                    ));
+                };
+                let param = |key: &str| {
+                    query
+                        .split('&')
+                        .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+                };
+                let after: Vec<&String> = match param("last") {
+                    Some(last) => stored.tags.keys().filter(|t| t.as_str() > last).collect(),
+                    None => stored.tags.keys().collect(),
+                };
+                let limit = param("n").and_then(|n| n.parse::<usize>().ok());
+                let tags = &after[..limit.unwrap_or(after.len()).min(after.len())];
+                let name = format!("{}/{}", self.name, image);
+                let mut headers = vec![("Content-Type".into(), "application/json".into())];
+                if let (Some(n), Some(last)) = (limit, tags.last()) {
+                    if tags.len() < after.len() {
+                        headers.push((
+                            "Link".into(),
+                            format!("</v2/{name}/tags/list?n={n}&last={last}>; rel=\"next\""),
+                        ));
+                    }
+                }
+                let body = serde_json::json!({"name": name, "tags": tags});
+                Ok((200, headers, serde_json::to_vec(&body)?))
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                Ok((404, Vec::new(), b"Not found".to_vec()))
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Oci
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.read_manifest(&id.name, &id.version) {
+            Lookup::Found(_, _, data) => Ok(Some(data)),
+            Lookup::NameUnknown | Lookup::Unknown => Ok(None),
+            Lookup::Corrupt(digest) => {
+                bail!("stored manifest {} does not match its digest", digest)
+            }
+        }
+    }
+
+    fn put(&self, id: &ArtifactId, _data: &[u8]) -> anyhow::Result<()> {
+        bail!(
+            "repository {} does not accept uploads, cannot store {}:{}",
+            self.name,
+            id.name,
+            id.version
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use layout::{is_valid_tag, tag_of, OCI_CONFIG, OCI_LAYER_TAR, OCI_MANIFEST};
+    use serde_json::{json, Value};
+    use std::io::Write;
+
+    /// Store a blob in an image layout and return its descriptor.
+    fn blob(layout: &Path, media_type: &str, data: &[u8]) -> Value {
+        let digest = Digest::of(data);
+        let path = digest.blob_path(layout);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+        json!({"mediaType": media_type, "digest": digest.to_string(), "size": data.len()})
+    }
+
+    /// An image layout with one manifest per tag, each with its own layer.
+    fn image_layout(layout: &Path, tags: &[&str]) -> Vec<Value> {
+        let manifests: Vec<Value> = tags
+            .iter()
+            .map(|tag| {
+                let config = blob(layout, OCI_CONFIG, format!(r#"{{"tag":"{tag}"}}"#).as_bytes());
+                let layer = blob(layout, OCI_LAYER_TAR, format!("layer of {tag}").as_bytes());
+                let manifest = json!({"schemaVersion": 2, "mediaType": OCI_MANIFEST, "config": config, "layers": [layer]});
+                let mut descriptor =
+                    blob(layout, OCI_MANIFEST, manifest.to_string().as_bytes());
+                descriptor["annotations"] = json!({"org.opencontainers.image.ref.name": tag});
+                descriptor
+            })
+            .collect();
+        std::fs::write(
+            layout.join("oci-layout"),
+            r#"{"imageLayoutVersion":"1.0.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            layout.join("index.json"),
+            json!({"schemaVersion": 2, "manifests": manifests}).to_string(),
+        )
+        .unwrap();
+        manifests
+    }
+
+    fn request(repo: &OciRepoFile, method: &str, suburl: &str) -> Http2Response {
+        repo.handle_http2_request(method, suburl, &[], b"").unwrap()
+    }
+
+    fn header<'a>(response: &'a Http2Response, name: &str) -> Option<&'a str> {
+        response
+            .1
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn error_code(response: &Http2Response) -> String {
+        let body: Value = serde_json::from_slice(&response.2).unwrap();
+        body["errors"][0]["code"].as_str().unwrap().to_string()
+    }
+
+    fn open(root: &Path) -> OciRepoFile {
+        OciRepoFile::new(
+            "oci-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/oci-prod".into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_names_and_digests() {
+        let hex = "a".repeat(64);
+        let digest = Digest::parse(&format!("sha256:{hex}")).unwrap();
+        assert_eq!(digest.to_string(), format!("sha256:{hex}"));
+        assert_eq!(
+            digest.blob_path(Path::new("img")),
+            Path::new("img/blobs/sha256").join(&hex)
+        );
+        assert!(Digest::parse(&format!("sha512:{}", "b".repeat(128))).is_some());
+        for invalid in [
+            "sha256:abc",
+            "md5:d41d8cd98f00b204e9800998ecf8427e",
+            &format!("sha256:{}", "A".repeat(64)),
+            &format!("sha256:{}", "a".repeat(65)),
+            "../../etc/passwd",
+        ] {
+            assert!(Digest::parse(invalid).is_none(), "{invalid}");
+        }
+        assert!(Digest::of(b"hello").matches(b"hello"));
+        assert!(!Digest::of(b"hello").matches(b"hullo"));
+
+        for valid in ["alpine", "library/alpine", "a.b_c__d--e/f-1", "9"] {
+            assert!(is_valid_image_name(valid), "{valid}");
+        }
+        for invalid in [
+            "", "Alpine", "a//b", "a/", ".a", "a..b", "a___b", "a-", "a/../b",
+        ] {
+            assert!(!is_valid_image_name(invalid), "{invalid}");
+        }
+        assert!(is_valid_tag("3.19") && is_valid_tag("_v1-rc.2"));
+        assert!(!is_valid_tag("") && !is_valid_tag(".x") && !is_valid_tag(&"a".repeat(129)));
+        assert_eq!(tag_of("3.19"), Some("3.19"));
+        assert_eq!(tag_of("docker.io/library/alpine:3.19"), Some("3.19"));
+        assert_eq!(tag_of("localhost:5000/alpine"), None);
+    }
+
+    #[test]
+    fn serves_distribution_api() {
+        let dir = tempfile::tempdir().unwrap();
+        let layout = dir.path().join("library/alpine");
+        std::fs::create_dir_all(&layout).unwrap();
+        let manifests = image_layout(&layout, &["3.18", "3.19", "latest"]);
+        let repo = open(dir.path());
+        assert_eq!(repo.artifacts.len(), 3);
+        assert_eq!(repo.artifacts[1].name, "library/alpine");
+
+        let base = request(&repo, "GET", "/v2/");
+        assert_eq!(base.0, 200);
+        assert_eq!(
+            header(&base, "Docker-Distribution-API-Version"),
+            Some("registry/2.0")
+        );
+
+        // Manifests by tag and by digest
+        let digest = manifests[1]["digest"].as_str().unwrap();
+        let by_tag = request(&repo, "GET", "/v2/oci-prod/library/alpine/manifests/3.19");
+        assert_eq!(by_tag.0, 200);
+        assert_eq!(header(&by_tag, "Content-Type"), Some(OCI_MANIFEST));
+        assert_eq!(header(&by_tag, "Docker-Content-Digest"), Some(digest));
+        let by_digest = request(
+            &repo,
+            "HEAD",
+            &format!("/v2/oci-prod/library/alpine/manifests/{digest}"),
+        );
+        assert_eq!((by_digest.0, by_digest.1.clone()), (200, by_tag.1.clone()));
+        assert_eq!(
+            repo.fetch(&repo.artifacts[1]).unwrap(),
+            Some(by_tag.2.clone())
+        );
+
+        // Blobs
+        let manifest: Value = serde_json::from_slice(&by_tag.2).unwrap();
+        let layer = manifest["layers"][0]["digest"].as_str().unwrap();
+        let blob = request(
+            &repo,
+            "GET",
+            &format!("/v2/oci-prod/library/alpine/blobs/{layer}"),
+        );
+        assert_eq!(blob.0, 200);
+        assert_eq!(blob.2, b"layer of 3.19");
+        assert_eq!(header(&blob, "Docker-Content-Digest"), Some(layer));
+
+        // Tags, all and paginated
+        let tags = request(&repo, "GET", "/v2/oci-prod/library/alpine/tags/list");
+        let body: Value = serde_json::from_slice(&tags.2).unwrap();
+        assert_eq!(
+            body,
+            json!({"name": "oci-prod/library/alpine", "tags": ["3.18", "3.19", "latest"]})
+        );
+        let page = request(
+            &repo,
+            "GET",
+            "/v2/oci-prod/library/alpine/tags/list?n=1&last=3.18",
+        );
+        let body: Value = serde_json::from_slice(&page.2).unwrap();
+        assert_eq!(body["tags"], json!(["3.19"]));
+        assert_eq!(
+            header(&page, "Link"),
+            Some("</v2/oci-prod/library/alpine/tags/list?n=1&last=3.19>; rel=\"next\"")
+        );
+        let last = request(
+            &repo,
+            "GET",
+            "/v2/oci-prod/library/alpine/tags/list?n=5&last=3.19",
+        );
+        assert_eq!(header(&last, "Link"), None);
+
+        // Unknown names, references and blobs
+        let missing = format!("sha256:{}", "0".repeat(64));
+        for (suburl, status, code) in [
+            (
+                "/v2/oci-prod/library/busybox/manifests/1",
+                404,
+                "NAME_UNKNOWN",
+            ),
+            (
+                "/v2/oci-prod/library/busybox/tags/list",
+                404,
+                "NAME_UNKNOWN",
+            ),
+            (
+                "/v2/oci-prod/library/alpine/manifests/3.20",
+                404,
+                "MANIFEST_UNKNOWN",
+            ),
+            (
+                &format!("/v2/oci-prod/library/alpine/blobs/{missing}"),
+                404,
+                "BLOB_UNKNOWN",
+            ),
+            (
+                "/v2/oci-prod/library/alpine/blobs/sha256:..",
+                400,
+                "DIGEST_INVALID",
+            ),
+        ] {
+            let response = request(&repo, "GET", suburl);
+            assert_eq!(
+                (response.0, error_code(&response)),
+                (status, code.into()),
+                "{suburl}"
+            );
+        }
+        let put = request(&repo, "PUT", "/v2/oci-prod/library/alpine/manifests/3.19");
+        assert_eq!((put.0, error_code(&put)), (405, "UNSUPPORTED".into()));
+        assert_eq!(request(&repo, "GET", "/v2/oci-prod/library/alpine").0, 404);
+
+        // Content that no longer matches its digest is not served
+        std::fs::write(
+            Digest::parse(layer).unwrap().blob_path(&layout),
+            b"tampered",
+        )
+        .unwrap();
+        let corrupt = request(
+            &repo,
+            "GET",
+            &format!("/v2/oci-prod/library/alpine/blobs/{layer}"),
+        );
+        assert_eq!(
+            (corrupt.0, error_code(&corrupt)),
+            (500, "DIGEST_INVALID".into())
+        );
+    }
+
+    #[test]
+    fn imports_oci_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let source = dir.path().join("alpine");
+        std::fs::create_dir_all(&source).unwrap();
+        let manifests = image_layout(&source, &["3.19", "broken"]);
+        let manifest: Value = serde_json::from_slice(
+            &std::fs::read(
+                Digest::parse(manifests[1]["digest"].as_str().unwrap())
+                    .unwrap()
+                    .blob_path(&source),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let layer = manifest["layers"][0]["digest"].as_str().unwrap();
+        std::fs::remove_file(Digest::parse(layer).unwrap().blob_path(&source)).unwrap();
+
+        let repo = open(&root);
+        assert!(repo.import_oci_layout(&source, "Alpine").is_err());
+        let report = repo.import_oci_layout(&source, "library/alpine").unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert_eq!(report.imported[0].version, "3.19");
+        assert_eq!(report.rejected.len(), 1);
+        assert!(
+            report.rejected[0].1.contains("broken"),
+            "{:?}",
+            report.rejected
+        );
+        assert!(
+            report.rejected[0].1.contains("missing"),
+            "{:?}",
+            report.rejected
+        );
+        let served = request(&repo, "GET", "/v2/oci-prod/library/alpine/manifests/3.19");
+        assert_eq!(served.0, 200);
+
+        // Importing again finds the tag stored with the same manifest
+        let again = repo.import_oci_layout(&source, "library/alpine").unwrap();
+        assert_eq!((again.imported.len(), again.existing.len()), (0, 1));
+
+        // A tag is never moved to another manifest
+        let other = dir.path().join("other");
+        std::fs::create_dir_all(&other).unwrap();
+        let mut retagged = image_layout(&other, &["3.20", "edge"]);
+        retagged[0]["annotations"] = json!({"org.opencontainers.image.ref.name": "3.19"});
+        std::fs::write(
+            other.join("index.json"),
+            json!({"schemaVersion": 2, "manifests": retagged}).to_string(),
+        )
+        .unwrap();
+        let moved = repo.import_oci_layout(&other, "library/alpine").unwrap();
+        assert_eq!(moved.imported.len(), 1);
+        assert_eq!(moved.imported[0].version, "edge");
+        assert_eq!(moved.rejected.len(), 1);
+        assert!(
+            moved.rejected[0].1.contains("already names"),
+            "{:?}",
+            moved.rejected
+        );
+        let kept = request(&repo, "GET", "/v2/oci-prod/library/alpine/manifests/3.19");
+        assert_eq!(kept.2, served.2);
+
+        // Reopening the repository finds what was imported
+        let reopened = open(&root);
+        assert_eq!(reopened.artifacts.len(), 2);
+        assert_eq!(*reopened.index(), *repo.index());
+    }
+
+    /// A `docker save` archive of `(path, content)` entries.
+    fn archive(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_slice())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn imports_docker_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("repo");
+        let plain = b"plain layer".to_vec();
+        let gzipped = gzip(b"gzipped layer");
+        let gzipped_name = format!("blobs/sha256/{}", Digest::of(&gzipped).hex);
+        let config = |diff_ids: &[&[u8]]| {
+            let diff_ids: Vec<String> =
+                diff_ids.iter().map(|d| Digest::of(d).to_string()).collect();
+            json!({"architecture": "amd64", "rootfs": {"type": "layers", "diff_ids": diff_ids}})
+                .to_string()
+                .into_bytes()
+        };
+        let good = config(&[&plain, b"gzipped layer"]);
+        let bad = config(&[b"something else"]);
+        let untagged = config(&[&plain]);
+        let name = |config: &[u8]| format!("{}.json", Digest::of(config).hex);
+        let manifest = json!([
+            {"Config": name(&good), "RepoTags": ["localhost:5000/team/app:1.0", "team/app:latest"], "Layers": ["l1/layer.tar", gzipped_name]},
+            {"Config": name(&bad), "RepoTags": ["bad:1"], "Layers": ["l1/layer.tar"]},
+            {"Config": name(&untagged), "RepoTags": null, "Layers": ["l1/layer.tar"]},
+            {"Config": "../escape.json", "RepoTags": ["escape:1"], "Layers": []},
+        ]);
+        let tar = archive(&[
+            ("manifest.json".into(), manifest.to_string().into_bytes()),
+            (name(&good), good.clone()),
+            (name(&bad), bad),
+            (name(&untagged), untagged),
+            ("l1/layer.tar".into(), plain.clone()),
+            (gzipped_name, gzipped.clone()),
+        ]);
+        let path = dir.path().join("app.tar");
+        std::fs::write(&path, &tar).unwrap();
+
+        let repo = open(&root);
+        let report = repo.import_docker_save(&path).unwrap();
+        let imported: Vec<String> = report
+            .imported
+            .iter()
+            .map(|id| format!("{}:{}", id.name, id.version))
+            .collect();
+        assert_eq!(imported, ["team/app:1.0", "team/app:latest"]);
+        assert_eq!(report.rejected.len(), 3, "{:?}", report.rejected);
+        assert!(report.rejected[0].1.starts_with("bad:1:"));
+        assert!(report.rejected[0].1.contains("the image config records"));
+        assert!(report.rejected[1].1.contains("untagged image"));
+        assert!(report.rejected[2].1.contains("invalid path"));
+        let leftovers: Vec<_> = std::fs::read_dir(&root)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .filter(|name| name.to_string_lossy().starts_with('.'))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+
+        let served = request(&repo, "GET", "/v2/oci-prod/team/app/manifests/1.0");
+        assert_eq!(served.0, 200);
+        let manifest: Value = serde_json::from_slice(&served.2).unwrap();
+        assert_eq!(manifest["config"]["digest"], Digest::of(&good).to_string());
+        assert_eq!(manifest["layers"][0]["mediaType"], OCI_LAYER_TAR);
+        assert_eq!(
+            manifest["layers"][1]["mediaType"],
+            format!("{OCI_LAYER_TAR}+gzip")
+        );
+        for (layer, content) in [(0, &plain), (1, &gzipped)] {
+            let digest = manifest["layers"][layer]["digest"].as_str().unwrap();
+            let blob = request(
+                &repo,
+                "GET",
+                &format!("/v2/oci-prod/team/app/blobs/{digest}"),
+            );
+            assert_eq!(&blob.2, content);
+        }
+
+        // The synthesized manifest is the same on every import
+        let gz_path = dir.path().join("app.tar.gz");
+        std::fs::write(&gz_path, gzip(&tar)).unwrap();
+        let again = repo.import_docker_save(&gz_path).unwrap();
+        assert_eq!((again.imported.len(), again.existing.len()), (0, 2));
+    }
+}
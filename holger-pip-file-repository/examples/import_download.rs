@@ -0,0 +1,32 @@
+use holger_pip_file_repository::PipRepoFile;
+
+/// cargo run --example import_download -- /var/lib/holger/pip-prod /airgap/python
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(root), Some(download_dir)) = (args.next(), args.next()) else {
+        anyhow::bail!("usage: import_download <repository root> <download dir>");
+    };
+    let repo = PipRepoFile::new(
+        "pip-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/pip-prod".into(),
+    )?;
+    let report = repo.import_download(download_dir.as_ref())?;
+
+    for id in &report.imported {
+        println!("imported {}", id.name);
+    }
+    for id in &report.existing {
+        println!("already stored {}", id.name);
+    }
+    for (path, reason) in &report.rejected {
+        println!("rejected {}: {}", path.display(), reason);
+    }
+    println!(
+        "{} imported, {} already stored, {} rejected",
+        report.imported.len(),
+        report.existing.len(),
+        report.rejected.len()
+    );
+    Ok(())
+}
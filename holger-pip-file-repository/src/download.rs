@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use holger_traits::ArtifactId;
+
+use crate::upload::parse_filename;
+use crate::PipRepoFile;
+
+/// Outcome of importing a `pip download` directory.
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    pub imported: Vec<ArtifactId>,
+    /// Files already stored with the same sha256
+    pub existing: Vec<ArtifactId>,
+    /// Files that were not stored, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+impl PipRepoFile {
+    /// Store every wheel and sdist in a flat `pip download` directory.
+    ///
+    /// Each file must have a parseable wheel or sdist name and core metadata
+    /// agreeing with it; everything else ends up in `rejected`.
+    pub fn import_download(&self, download_dir: &Path) -> anyhow::Result<IngestReport> {
+        let mut report = IngestReport::default();
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(download_dir)
+            .with_context(|| format!("reading download directory {}", download_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        for path in paths {
+            let Some(filename) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(str::to_string)
+            else {
+                report
+                    .rejected
+                    .push((path, "file name is not UTF-8".into()));
+                continue;
+            };
+            let id = match parse_filename(&filename) {
+                Ok((_, version)) => ArtifactId {
+                    namespace: None,
+                    name: filename.clone(),
+                    version: version.to_string(),
+                },
+                Err(e) => {
+                    report.rejected.push((path, format!("{e:#}")));
+                    continue;
+                }
+            };
+            let data = match std::fs::read(&path) {
+                Ok(data) => data,
+                Err(e) => {
+                    report.rejected.push((path, e.to_string()));
+                    continue;
+                }
+            };
+            let stored = self.index().file(&filename).map(|f| f.sha256.clone());
+            match stored {
+                Some(sha256) if sha256 == Self::sha256_hex(&data) => report.existing.push(id),
+                Some(sha256) => report.rejected.push((
+                    path,
+                    format!("{} is already stored with sha256 {}", filename, sha256),
+                )),
+                None => match self.store(&id, &data) {
+                    Ok(()) => report.imported.push(id),
+                    Err(e) => report.rejected.push((path, format!("{e:#}"))),
+                },
+            }
+        }
+        Ok(report)
+    }
+}
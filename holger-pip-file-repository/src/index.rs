@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// File extensions of the distributions a simple index serves.
+const DISTRIBUTION_SUFFIXES: [&str; 4] = [".whl", ".tar.gz", ".zip", ".tar.bz2"];
+
+/// One stored wheel or sdist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DistributionFile {
+    pub filename: String,
+    /// Project name as spelled in the file name
+    pub project: String,
+    pub version: String,
+    pub sha256: String,
+    pub requires_python: Option<String>,
+    /// Compatibility tags, for wheels only
+    pub tags: Option<WheelTags>,
+    /// sha256 of the extracted `METADATA` stored next to a wheel (PEP 658)
+    pub core_metadata_sha256: Option<String>,
+    pub path: PathBuf,
+}
+
+/// Compatibility tags of a wheel file name. Compressed tag sets such as
+/// `py2.py3` are split into their members.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WheelTags {
+    pub build: Option<String>,
+    pub python: Vec<String>,
+    pub abi: Vec<String>,
+    pub platform: Vec<String>,
+}
+
+impl WheelTags {
+    /// True if the wheel installs on any platform, like `py3-none-any`.
+    pub fn is_pure(&self) -> bool {
+        self.platform.iter().all(|p| p == "any")
+    }
+
+    /// Every `{python}-{abi}-{platform}` tag the compressed sets stand for.
+    pub fn expand(&self) -> Vec<String> {
+        let mut tags = Vec::new();
+        for python in &self.python {
+            for abi in &self.abi {
+                for platform in &self.platform {
+                    tags.push(format!("{python}-{abi}-{platform}"));
+                }
+            }
+        }
+        tags
+    }
+}
+
+/// Suffix of the core metadata file stored next to a wheel.
+pub const METADATA_SUFFIX: &str = ".metadata";
+
+impl DistributionFile {
+    /// `<file>.metadata`, next to the distribution itself.
+    pub fn metadata_path(&self) -> PathBuf {
+        self.path
+            .with_file_name(format!("{}{}", self.filename, METADATA_SUFFIX))
+    }
+}
+
+/// In-memory simple index, keyed by normalized project name.
+#[derive(Debug, Default)]
+pub struct ProjectIndex {
+    projects: BTreeMap<String, Vec<DistributionFile>>,
+}
+
+impl ProjectIndex {
+    /// Add a distribution, replacing an earlier one with the same file name.
+    pub fn insert(&mut self, file: DistributionFile) {
+        let files = self
+            .projects
+            .entry(normalize_name(&file.project))
+            .or_default();
+        files.retain(|f| f.filename != file.filename);
+        files.push(file);
+        files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    }
+
+    /// Files of a project, looked up by any spelling of its name.
+    pub fn files(&self, project: &str) -> Option<&[DistributionFile]> {
+        self.projects
+            .get(&normalize_name(project))
+            .map(|f| f.as_slice())
+    }
+
+    /// Find a stored file by its file name.
+    pub fn file(&self, filename: &str) -> Option<&DistributionFile> {
+        let (project, _) = parse_distribution_filename(filename)?;
+        self.files(project)?.iter().find(|f| f.filename == filename)
+    }
+
+    /// Normalized project names with their files, in name order.
+    pub fn projects(&self) -> impl Iterator<Item = (&str, &[DistributionFile])> {
+        self.projects
+            .iter()
+            .map(|(name, files)| (name.as_str(), files.as_slice()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DistributionFile> {
+        self.projects.values().flatten()
+    }
+}
+
+/// PEP 503 normalization: runs of `-`, `_` and `.` become one `-`, lowercased.
+pub fn normalize_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut in_separator = false;
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !in_separator {
+                out.push('-');
+            }
+            in_separator = true;
+        } else {
+            out.push(c.to_ascii_lowercase());
+            in_separator = false;
+        }
+    }
+    out
+}
+
+/// Split a distribution file name into project name and version.
+///
+/// Wheels are `{name}-{version}(-{build})?-{python}-{abi}-{platform}.whl`, with
+/// `-` escaped to `_` inside each part. Sdists are `{name}-{version}.tar.gz`
+/// where legacy names may still contain `-`, so the version follows the last one.
+pub fn parse_distribution_filename(filename: &str) -> Option<(&str, &str)> {
+    if filename.ends_with(".whl") {
+        return parse_wheel_filename(filename).map(|(name, version, _)| (name, version));
+    }
+    let stem = DISTRIBUTION_SUFFIXES
+        .iter()
+        .find_map(|suffix| filename.strip_suffix(suffix))?;
+    let (name, version) = stem.rsplit_once('-')?;
+    let version_ok = version.starts_with(|c: char| c.is_ascii_digit());
+    (!name.is_empty() && version_ok).then_some((name, version))
+}
+
+/// Split a wheel file name into project name, version and compatibility tags.
+/// The optional build tag must start with a digit.
+pub fn parse_wheel_filename(filename: &str) -> Option<(&str, &str, WheelTags)> {
+    let stem = filename.strip_suffix(".whl")?;
+    let parts: Vec<&str> = stem.split('-').collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    let (name, version, build, tags) = match parts.as_slice() {
+        [name, version, python, abi, platform] => (name, version, None, [python, abi, platform]),
+        [name, version, build, python, abi, platform]
+            if build.starts_with(|c: char| c.is_ascii_digit()) =>
+        {
+            (
+                name,
+                version,
+                Some(build.to_string()),
+                [python, abi, platform],
+            )
+        }
+        _ => return None,
+    };
+    let split = |set: &str| set.split('.').map(str::to_string).collect::<Vec<_>>();
+    let tags = WheelTags {
+        build,
+        python: split(tags[0]),
+        abi: split(tags[1]),
+        platform: split(tags[2]),
+    };
+    if [&tags.python, &tags.abi, &tags.platform]
+        .iter()
+        .any(|set| set.iter().any(String::is_empty))
+    {
+        return None;
+    }
+    Some((name, version, tags))
+}
+
+/// True for the file names of the distributions a simple index serves.
+pub fn is_distribution_file(filename: &str) -> bool {
+    DISTRIBUTION_SUFFIXES.iter().any(|s| filename.ends_with(s))
+}
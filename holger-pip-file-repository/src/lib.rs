@@ -0,0 +1,1039 @@
+use holger_traits::util::{find_files, percent_decode, write_atomic};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::path::{Path, PathBuf};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use anyhow::{anyhow, bail};
+
+pub mod download;
+pub mod index;
+pub mod metadata;
+pub mod simple;
+pub mod upload;
+
+use index::{
+    is_distribution_file, normalize_name, parse_distribution_filename, parse_wheel_filename,
+    DistributionFile, ProjectIndex, METADATA_SUFFIX,
+};
+use metadata::{read_core_metadata, read_metadata_file, read_wheel_tags, CoreMetadata};
+use simple::{
+    project_list_html, project_list_json, project_page_html, project_page_json, PageFormat,
+};
+use upload::{parse_filename, UploadRequest};
+
+/// Python repository backed by wheels and sdists in a directory tree.
+///
+/// Artifacts are addressed by distribution file name: `ArtifactId { name:
+/// "six-1.16.0-py2.py3-none-any.whl", version: "1.16.0", .. }`.
+pub struct PipRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/pip-prod
+    pub writable: bool,     // accepts `twine upload`, set for repos wired with ron_in
+    pub index: RwLock<ProjectIndex>,
+}
+
+impl PipRepoFile {
+    /// Open a repository rooted at `root`, indexing every distribution below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = PipRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            writable: false,
+            index: RwLock::new(ProjectIndex::default()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the distribution files on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = ProjectIndex::default();
+        for (rel, path) in find_files(&self.root)? {
+            if !is_distribution_file(&rel) {
+                continue;
+            }
+            match Self::distribution_file(path.clone()) {
+                Ok(Some(file)) => index.insert(file),
+                Ok(None) => println!("Skipping unrecognized file name {}", path.display()),
+                Err(e) => println!("Skipping unreadable file {}: {:#}", path.display(), e),
+            }
+        }
+        self.artifacts = index
+            .iter()
+            .map(|f| ArtifactId {
+                namespace: None,
+                name: f.filename.clone(),
+                version: f.version.clone(),
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    /// Index entry for a stored file; `None` if its name is not a distribution name.
+    ///
+    /// A wheel's `METADATA` is extracted to `<file>.metadata` once, so
+    /// resolvers can read dependencies without downloading the wheel and
+    /// later loads read the extracted copy instead of the wheel's archive.
+    fn distribution_file(path: PathBuf) -> anyhow::Result<Option<DistributionFile>> {
+        let Some(filename) = path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(None);
+        };
+        let Some((project, version)) = parse_distribution_filename(filename) else {
+            return Ok(None);
+        };
+        let data = std::fs::read(&path)?;
+        let mut file = DistributionFile {
+            filename: filename.to_string(),
+            project: project.to_string(),
+            version: version.to_string(),
+            sha256: Self::sha256_hex(&data),
+            requires_python: None,
+            tags: parse_wheel_filename(filename).map(|(_, _, tags)| tags),
+            core_metadata_sha256: None,
+            path: path.clone(),
+        };
+        if filename.ends_with(".whl") {
+            if let Some(raw) = Self::extracted_core_metadata(&file) {
+                file.requires_python = Self::requires_python(&raw);
+                file.core_metadata_sha256 = Some(Self::sha256_hex(&raw));
+                return Ok(Some(file));
+            }
+        }
+        let raw = match read_metadata_file(filename, &data) {
+            Ok(Some(raw)) => raw,
+            Ok(None) => return Ok(Some(file)),
+            Err(e) => {
+                println!("No readable metadata in {}: {:#}", filename, e);
+                return Ok(Some(file));
+            }
+        };
+        file.requires_python = Self::requires_python(&raw);
+        if filename.ends_with(".whl") {
+            match write_atomic(&file.metadata_path(), &raw) {
+                Ok(()) => file.core_metadata_sha256 = Some(Self::sha256_hex(&raw)),
+                Err(e) => println!("Cannot store metadata for {}: {:#}", filename, e),
+            }
+        }
+        Ok(Some(file))
+    }
+
+    fn requires_python(raw: &[u8]) -> Option<String> {
+        CoreMetadata::parse(&String::from_utf8_lossy(raw)).requires_python
+    }
+
+    /// The `<file>.metadata` extracted earlier, while it is newer than the file.
+    fn extracted_core_metadata(file: &DistributionFile) -> Option<Vec<u8>> {
+        let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+        let extracted = file.metadata_path();
+        if modified(&extracted)? >= modified(&file.path)? {
+            std::fs::read(extracted).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, ProjectIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, ProjectIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Validate and store a distribution, then add it to the index. Existing
+    /// files are never overwritten.
+    ///
+    /// A wheel's `WHEEL` file must list exactly the tags its file name carries.
+    fn store(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
+        let (project, version) = parse_filename(&id.name)?;
+        if !version.eq_ignore_ascii_case(&id.version) {
+            bail!("file `{}` is not version {}", id.name, id.version);
+        }
+        let metadata = read_core_metadata(&id.name, data)?
+            .ok_or_else(|| anyhow!("`{}` carries no core metadata", id.name))?;
+        let declared_name = metadata.name.unwrap_or_default();
+        let declared_version = metadata.version.unwrap_or_default();
+        if normalize_name(&declared_name) != normalize_name(project)
+            || !declared_version.eq_ignore_ascii_case(version)
+        {
+            bail!(
+                "metadata declares {} {}, file name `{}` says {} {}",
+                declared_name,
+                declared_version,
+                id.name,
+                project,
+                version
+            );
+        }
+
+        if let Some((_, _, tags)) = parse_wheel_filename(&id.name) {
+            let mut declared = read_wheel_tags(data)?
+                .ok_or_else(|| anyhow!("`{}` carries no WHEEL file", id.name))?;
+            let mut expected = tags.expand();
+            declared.sort();
+            declared.dedup();
+            expected.sort();
+            if declared != expected {
+                bail!(
+                    "WHEEL declares tags {}, file name `{}` says {}",
+                    declared.join(", "),
+                    id.name,
+                    expected.join(", ")
+                );
+            }
+        }
+
+        // Hold the write lock across the existence check, the write and the index update
+        let mut index = self.index_mut();
+        let path = self.distribution_file_path(project, &id.name);
+        if index.file(&id.name).is_some() || path.exists() {
+            bail!("file `{}` already exists", id.name);
+        }
+        write_atomic(&path, data)?;
+        let file = Self::distribution_file(path)?
+            .ok_or_else(|| anyhow!("`{}` is not a distribution file name", id.name))?;
+        index.insert(file);
+        Ok(())
+    }
+
+    /// Where an uploaded distribution is stored below the repository root.
+    pub fn distribution_file_path(&self, project: &str, filename: &str) -> PathBuf {
+        self.root.join(normalize_name(project)).join(filename)
+    }
+
+    pub fn sha256_hex(data: &[u8]) -> String {
+        use sha2::{Digest, Sha256};
+
+        hex::encode(Sha256::digest(data))
+    }
+
+    fn page(format: PageFormat, body: String) -> Http2Response {
+        (
+            200,
+            vec![
+                ("Content-Type".into(), format.content_type().into()),
+                ("Vary".into(), "Accept".into()),
+            ],
+            body.into_bytes(),
+        )
+    }
+}
+
+/// Plain-text error body; twine shows it next to the status line.
+fn upload_error(status: u16, message: &str) -> Http2Response {
+    (
+        status,
+        vec![("Content-Type".into(), "text/plain; charset=utf-8".into())],
+        message.as_bytes().to_vec(),
+    )
+}
+
+impl RepositoryBackendTrait for PipRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Pip repo handle_http2_request.suburl={}", suburl);
+
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+        let format = PageFormat::negotiate(header("accept"));
+
+        let (path, _query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+
+        match parts.as_slice() {
+            // Project list → /pip-prod/simple/
+            [repo, "simple"] | [repo, "simple", ""] if *repo == self.name() => {
+                let index = self.index();
+                let body = match format {
+                    PageFormat::Json => project_list_json(&index),
+                    _ => project_list_html(&index),
+                };
+                Ok(Self::page(format, body))
+            }
+
+            // Project page → /pip-prod/simple/<project>/
+            [repo, "simple", project] | [repo, "simple", project, ""] if *repo == self.name() => {
+                let normalized = normalize_name(project);
+                if normalized != *project {
+                    // PEP 503: redirect other spellings to the normalized URL
+                    let location = format!("{}/simple/{}/", self.public_url, normalized);
+                    return Ok((301, vec![("Location".into(), location)], Vec::new()));
+                }
+                match self.index().files(project) {
+                    Some(files) => {
+                        let body = match format {
+                            PageFormat::Json => project_page_json(project, files, &self.public_url),
+                            _ => project_page_html(project, files, &self.public_url),
+                        };
+                        Ok(Self::page(format, body))
+                    }
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            // Core metadata → /pip-prod/packages/<filename>.metadata
+            [repo, "packages", metadata]
+                if *repo == self.name() && metadata.ends_with(METADATA_SUFFIX) =>
+            {
+                let Some(filename) = percent_decode(metadata.trim_end_matches(METADATA_SUFFIX))
+                else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                let path = self
+                    .index()
+                    .file(&filename)
+                    .filter(|f| f.core_metadata_sha256.is_some())
+                    .map(|f| f.metadata_path());
+                match path {
+                    Some(path) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "text/plain; charset=utf-8".into())],
+                        std::fs::read(path)?,
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            // Twine upload → /pip-prod/legacy/
+            [repo, "legacy"] | [repo, "legacy", ""] if *repo == self.name() => {
+                if method != "POST" {
+                    return Ok(upload_error(405, "uploading requires POST"));
+                }
+                if !self.is_writable() {
+                    return Ok(upload_error(
+                        403,
+                        &format!("repository `{}` is read-only", self.name),
+                    ));
+                }
+                let request =
+                    match UploadRequest::parse(header("content-type").unwrap_or_default(), body) {
+                        Ok(request) => request,
+                        Err(e) => return Ok(upload_error(400, &format!("{e:#}"))),
+                    };
+                println!("Upload request: file={}", request.filename);
+                if let Some(expected) = &request.sha256_digest {
+                    let actual = Self::sha256_hex(request.content);
+                    if !actual.eq_ignore_ascii_case(expected) {
+                        return Ok(upload_error(
+                            400,
+                            &format!(
+                                "sha256 digest {expected} does not match uploaded file ({actual})"
+                            ),
+                        ));
+                    }
+                }
+                let id = request.artifact_id();
+                if self.index().file(&id.name).is_some() {
+                    // twine --skip-existing recognizes 409
+                    return Ok(upload_error(
+                        409,
+                        &format!("File already exists: {}", id.name),
+                    ));
+                }
+                match self.put(&id, request.content) {
+                    Ok(()) => Ok((200, Vec::new(), b"OK".to_vec())),
+                    Err(e) => Ok(upload_error(400, &format!("{e:#}"))),
+                }
+            }
+
+            // Distribution download → /pip-prod/packages/<filename>
+            [repo, "packages", filename] if *repo == self.name() => {
+                let Some(filename) = percent_decode(filename) else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                let Some((_, version)) = parse_distribution_filename(&filename) else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                let id = ArtifactId {
+                    namespace: None,
+                    name: filename.clone(),
+                    version: version.to_string(),
+                };
+                match self.fetch(&id)? {
+                    Some(data) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "application/octet-stream".into())],
+                        data,
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                Ok((404, Vec::new(), b"Not found".to_vec()))
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Pip
+    }
+
+    fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.index().file(&id.name).map(|f| f.path.clone());
+        match path {
+            Some(path) => Ok(Some(std::fs::read(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store a new distribution. Stored files are immutable, so existing ones are refused.
+    fn put(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
+        if !self.writable {
+            bail!("repository {} is read-only", self.name);
+        }
+        self.store(id, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::path::Path;
+
+    const PUBLIC_URL: &str = "https://10.101.1.9:8443/pip-prod";
+
+    /// Wheel holding only its dist-info `METADATA` and a `WHEEL` file
+    /// matching its file name.
+    fn wheel(dir: &Path, filename: &str, metadata: &str) -> PathBuf {
+        let (_, _, tags) = parse_wheel_filename(filename).unwrap();
+        wheel_with_tags(dir, filename, metadata, &tags.expand())
+    }
+
+    fn wheel_with_tags(dir: &Path, filename: &str, metadata: &str, tags: &[String]) -> PathBuf {
+        let path = dir.join(filename);
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let (name, version) = parse_distribution_filename(filename).unwrap();
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file(format!("{name}-{version}.dist-info/METADATA"), options)
+            .unwrap();
+        zip.write_all(metadata.as_bytes()).unwrap();
+        zip.start_file(format!("{name}-{version}.dist-info/WHEEL"), options)
+            .unwrap();
+        write!(zip, "Wheel-Version: 1.0\nRoot-Is-Purelib: true\n").unwrap();
+        for tag in tags {
+            writeln!(zip, "Tag: {tag}").unwrap();
+        }
+        zip.finish().unwrap();
+        path
+    }
+
+    /// Gzipped sdist holding only its top-level `PKG-INFO`.
+    fn sdist(dir: &Path, filename: &str, pkg_info: &str) -> PathBuf {
+        let path = dir.join(filename);
+        let base = filename.strip_suffix(".tar.gz").unwrap();
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(pkg_info.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, format!("{base}/PKG-INFO"), pkg_info.as_bytes())
+            .unwrap();
+        std::fs::write(&path, builder.into_inner().unwrap().finish().unwrap()).unwrap();
+        path
+    }
+
+    /// `twine upload` style multipart body and its Content-Type header.
+    fn upload_body(fields: &[(&str, &str)], filename: &str, content: &[u8]) -> (String, Vec<u8>) {
+        let boundary = "d6b3a9f1c2e84e0b";
+        let mut body = Vec::new();
+        for (name, value) in fields {
+            write!(
+                body,
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .unwrap();
+        }
+        write!(
+            body,
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"content\"; filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+        )
+        .unwrap();
+        body.extend_from_slice(content);
+        write!(body, "\r\n--{boundary}--\r\n").unwrap();
+        (format!("multipart/form-data; boundary={boundary}"), body)
+    }
+
+    fn upload(repo: &PipRepoFile, content_type: &str, body: &[u8]) -> (u16, String) {
+        let headers = [("content-type".to_string(), content_type.to_string())];
+        let (status, _, body) = repo
+            .handle_http2_request("POST", "/pip-prod/legacy/", &headers, body)
+            .unwrap();
+        (status, String::from_utf8(body).unwrap())
+    }
+
+    fn get(repo: &PipRepoFile, suburl: &str) -> Http2Response {
+        repo.handle_http2_request("GET", suburl, &[], b"").unwrap()
+    }
+
+    #[test]
+    fn normalizes_names_and_parses_file_names() {
+        assert_eq!(normalize_name("Friendly_Bard"), "friendly-bard");
+        assert_eq!(normalize_name("zope.interface"), "zope-interface");
+        assert_eq!(normalize_name("a-_.b"), "a-b");
+        assert_eq!(
+            parse_distribution_filename("Friendly_Bard-1.0-py3-none-any.whl"),
+            Some(("Friendly_Bard", "1.0"))
+        );
+        assert_eq!(
+            parse_distribution_filename("numpy-2.0.0-1-cp312-cp312-manylinux_2_17_x86_64.whl"),
+            Some(("numpy", "2.0.0"))
+        );
+        assert_eq!(
+            parse_distribution_filename("python-dateutil-2.9.0.tar.gz"),
+            Some(("python-dateutil", "2.9.0"))
+        );
+        assert_eq!(parse_distribution_filename("broken-py3-none-any.whl"), None);
+        assert_eq!(
+            parse_distribution_filename("numpy-2.0.0-build-cp312-cp312-linux_x86_64.whl"),
+            None
+        );
+
+        let (_, _, tags) = parse_wheel_filename(
+            "numpy-2.0.0-1-cp312-cp312-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+        )
+        .unwrap();
+        assert_eq!(tags.build.as_deref(), Some("1"));
+        assert_eq!(tags.python, ["cp312"]);
+        assert_eq!(tags.abi, ["cp312"]);
+        assert_eq!(
+            tags.platform,
+            ["manylinux_2_17_x86_64", "manylinux2014_x86_64"]
+        );
+        assert!(!tags.is_pure());
+        let (_, _, tags) = parse_wheel_filename("six-1.16.0-py2.py3-none-any.whl").unwrap();
+        assert_eq!(tags.python, ["py2", "py3"]);
+        assert!(tags.is_pure());
+        assert_eq!(parse_distribution_filename("README.tar.gz"), None);
+    }
+
+    #[test]
+    fn serves_simple_index_pages_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let wheel_path = wheel(
+            dir.path(),
+            "Friendly_Bard-1.0-py3-none-any.whl",
+            "Metadata-Version: 2.1\nName: Friendly_Bard\nVersion: 1.0\nRequires-Python: >=3.8\n\nBody: ignored\n",
+        );
+        std::fs::create_dir(dir.path().join("six")).unwrap();
+        sdist(
+            &dir.path().join("six"),
+            "six-1.16.0.tar.gz",
+            "Metadata-Version: 1.2\nName: six\nVersion: 1.16.0\n",
+        );
+        std::fs::write(dir.path().join("notes.txt"), b"not a distribution").unwrap();
+        // Hidden entries, such as leftovers of an interrupted write, are skipped
+        std::fs::create_dir(dir.path().join(".tmpA1b2")).unwrap();
+        std::fs::copy(
+            &wheel_path,
+            dir.path()
+                .join(".tmpA1b2/Friendly_Bard-2.0-py3-none-any.whl"),
+        )
+        .unwrap();
+
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        assert_eq!(repo.artifacts.len(), 2);
+
+        let (status, headers, body) = get(&repo, "/pip-prod/simple/");
+        assert_eq!(status, 200);
+        assert!(headers
+            .iter()
+            .any(|(k, v)| k == "Content-Type" && v.starts_with("text/html")));
+        let list = String::from_utf8(body).unwrap();
+        assert!(list.contains("<a href=\"friendly-bard/\">friendly-bard</a>"));
+        assert!(list.contains("<a href=\"six/\">six</a>"));
+
+        let wheel_bytes = std::fs::read(&wheel_path).unwrap();
+        let (status, _, body) = get(&repo, "/pip-prod/simple/friendly-bard/");
+        assert_eq!(status, 200);
+        let page = String::from_utf8(body).unwrap();
+        assert!(page.contains(&format!(
+            "<a href=\"{PUBLIC_URL}/packages/Friendly_Bard-1.0-py3-none-any.whl#sha256={}\" data-requires-python=\"&gt;=3.8\" ",
+            PipRepoFile::sha256_hex(&wheel_bytes)
+        )));
+
+        let (status, _, body) = get(&repo, "/pip-prod/simple/six/");
+        assert_eq!(status, 200);
+        assert!(!String::from_utf8(body)
+            .unwrap()
+            .contains("data-requires-python"));
+
+        // Other spellings redirect to the normalized page
+        let (status, headers, _) = get(&repo, "/pip-prod/simple/Friendly.Bard/");
+        assert_eq!(status, 301);
+        assert_eq!(
+            headers,
+            vec![(
+                "Location".to_string(),
+                format!("{PUBLIC_URL}/simple/friendly-bard/")
+            )]
+        );
+
+        let (status, _, _) = get(&repo, "/pip-prod/simple/missing/");
+        assert_eq!(status, 404);
+
+        let (status, _, body) = get(
+            &repo,
+            "/pip-prod/packages/Friendly_Bard-1.0-py3-none-any.whl",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body, wheel_bytes);
+        let (status, _, _) = get(&repo, "/pip-prod/packages/six-9.9.9.tar.gz");
+        assert_eq!(status, 404);
+
+        let id = ArtifactId {
+            namespace: None,
+            name: "six-1.16.0.tar.gz".into(),
+            version: "1.16.0".into(),
+        };
+        assert!(repo.put(&id, b"data").is_err());
+    }
+
+    #[test]
+    fn negotiates_page_format_from_accept() {
+        use PageFormat::*;
+
+        assert_eq!(PageFormat::negotiate(None), LegacyHtml);
+        assert_eq!(PageFormat::negotiate(Some("*/*")), LegacyHtml);
+        assert_eq!(PageFormat::negotiate(Some("image/png")), LegacyHtml);
+        // What current pip sends
+        assert_eq!(
+            PageFormat::negotiate(Some(
+                "application/vnd.pypi.simple.v1+json, application/vnd.pypi.simple.v1+html; q=0.1, text/html; q=0.01"
+            )),
+            Json
+        );
+        assert_eq!(
+            PageFormat::negotiate(Some("application/vnd.pypi.simple.latest+html")),
+            Html
+        );
+        assert_eq!(
+            PageFormat::negotiate(Some("application/*;q=0.5, text/html;q=0.9")),
+            LegacyHtml
+        );
+        assert_eq!(
+            PageFormat::negotiate(Some(
+                "text/html;q=0, application/vnd.pypi.simple.v1+json;q=0.2"
+            )),
+            Json
+        );
+    }
+
+    #[test]
+    fn serves_json_simple_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let wheel_path = wheel(
+            dir.path(),
+            "Friendly_Bard-1.0-py3-none-any.whl",
+            "Metadata-Version: 2.1\nName: Friendly_Bard\nVersion: 1.0\nRequires-Python: >=3.8\n",
+        );
+        sdist(
+            dir.path(),
+            "friendly-bard-0.9.tar.gz",
+            "Metadata-Version: 1.2\nName: friendly-bard\nVersion: 0.9\n",
+        );
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        let json_accept = [(
+            "accept".to_string(),
+            "application/vnd.pypi.simple.v1+json".to_string(),
+        )];
+
+        let (status, headers, body) = repo
+            .handle_http2_request("GET", "/pip-prod/simple/", &json_accept, b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        assert!(headers.contains(&(
+            "Content-Type".to_string(),
+            "application/vnd.pypi.simple.v1+json".to_string()
+        )));
+        assert!(headers.contains(&("Vary".to_string(), "Accept".to_string())));
+        let list: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            list,
+            serde_json::json!({
+                "meta": {"api-version": "1.0"},
+                "projects": [{"name": "friendly-bard"}],
+            })
+        );
+
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/pip-prod/simple/friendly-bard/", &json_accept, b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        let page: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let wheel_sha256 = PipRepoFile::sha256_hex(&std::fs::read(&wheel_path).unwrap());
+        let metadata_sha256 = PipRepoFile::sha256_hex(
+            b"Metadata-Version: 2.1\nName: Friendly_Bard\nVersion: 1.0\nRequires-Python: >=3.8\n",
+        );
+        assert_eq!(page["name"], "friendly-bard");
+        assert_eq!(
+            page["files"][0],
+            serde_json::json!({
+                "filename": "Friendly_Bard-1.0-py3-none-any.whl",
+                "url": format!("{PUBLIC_URL}/packages/Friendly_Bard-1.0-py3-none-any.whl"),
+                "hashes": {"sha256": wheel_sha256},
+                "requires-python": ">=3.8",
+                "core-metadata": {"sha256": metadata_sha256},
+                "dist-info-metadata": {"sha256": metadata_sha256},
+                "yanked": false,
+            })
+        );
+        assert_eq!(page["files"][1]["filename"], "friendly-bard-0.9.tar.gz");
+        assert!(page["files"][1].get("requires-python").is_none());
+        assert_eq!(page["files"][1]["core-metadata"], false);
+
+        // PEP 691 HTML carries the same page under its own content type
+        let html_accept = [(
+            "accept".to_string(),
+            "application/vnd.pypi.simple.v1+html".to_string(),
+        )];
+        let (_, headers, body) = repo
+            .handle_http2_request("GET", "/pip-prod/simple/friendly-bard/", &html_accept, b"")
+            .unwrap();
+        assert!(headers.contains(&(
+            "Content-Type".to_string(),
+            "application/vnd.pypi.simple.v1+html".to_string()
+        )));
+        assert!(String::from_utf8(body)
+            .unwrap()
+            .contains("data-requires-python"));
+    }
+
+    #[test]
+    fn extracts_and_serves_wheel_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let metadata = "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\nRequires-Dist: pytest; extra == \"test\"\n";
+        let wheel_path = wheel(dir.path(), "six-1.16.0-py2.py3-none-any.whl", metadata);
+        sdist(dir.path(), "six-1.16.0.tar.gz", metadata);
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+
+        // Stored next to the wheel at ingest; sdists get none
+        let sidecar = dir.path().join("six-1.16.0-py2.py3-none-any.whl.metadata");
+        assert_eq!(std::fs::read(&sidecar).unwrap(), metadata.as_bytes());
+        assert!(!dir.path().join("six-1.16.0.tar.gz.metadata").exists());
+        assert_eq!(repo.artifacts.len(), 2);
+
+        let (status, _, body) = get(
+            &repo,
+            "/pip-prod/packages/six-1.16.0-py2.py3-none-any.whl.metadata",
+        );
+        assert_eq!(status, 200);
+        assert_eq!(body, metadata.as_bytes());
+        let (status, _, _) = get(&repo, "/pip-prod/packages/six-1.16.0.tar.gz.metadata");
+        assert_eq!(status, 404);
+
+        let (_, _, body) = get(&repo, "/pip-prod/simple/six/");
+        let page = String::from_utf8(body).unwrap();
+        let sha256 = PipRepoFile::sha256_hex(metadata.as_bytes());
+        assert!(page.contains(&format!(
+            "{PUBLIC_URL}/packages/six-1.16.0-py2.py3-none-any.whl#sha256={}\" data-core-metadata=\"sha256={sha256}\" data-dist-info-metadata=\"sha256={sha256}\">",
+            PipRepoFile::sha256_hex(&std::fs::read(&wheel_path).unwrap())
+        )));
+        assert_eq!(page.matches("data-core-metadata").count(), 1);
+
+        // Later loads read the extracted copy, not the wheel
+        let cached = "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\nRequires-Python: >=3.7\n";
+        std::fs::write(&sidecar, cached).unwrap();
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        let file = repo
+            .index()
+            .file("six-1.16.0-py2.py3-none-any.whl")
+            .cloned()
+            .unwrap();
+        assert_eq!(file.requires_python.as_deref(), Some(">=3.7"));
+        let (_, _, body) = get(
+            &repo,
+            "/pip-prod/packages/six-1.16.0-py2.py3-none-any.whl.metadata",
+        );
+        assert_eq!(body, cached.as_bytes());
+
+        // ... until the wheel is newer than it
+        let later = std::fs::metadata(&sidecar).unwrap().modified().unwrap()
+            + std::time::Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&wheel_path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        assert_eq!(std::fs::read(&sidecar).unwrap(), metadata.as_bytes());
+    }
+
+    #[test]
+    fn serves_local_version_wheels() {
+        let dir = tempfile::tempdir().unwrap();
+        let filename = "torch-2.1.0+cpu-cp311-cp311-linux_x86_64.whl";
+        let metadata = "Metadata-Version: 2.1\nName: torch\nVersion: 2.1.0+cpu\n";
+        let wheel_path = wheel(dir.path(), filename, metadata);
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+
+        let (_, _, body) = get(&repo, "/pip-prod/simple/torch/");
+        let page = String::from_utf8(body).unwrap();
+        let encoded = "torch-2.1.0%2Bcpu-cp311-cp311-linux_x86_64.whl";
+        assert!(page.contains(&format!("{PUBLIC_URL}/packages/{encoded}#sha256=")));
+
+        // pip re-quotes link paths, so `+` arrives as `%2B`
+        let (status, _, body) = get(&repo, &format!("/pip-prod/packages/{encoded}"));
+        assert_eq!(status, 200);
+        assert_eq!(body, std::fs::read(&wheel_path).unwrap());
+        let (status, _, body) = get(&repo, &format!("/pip-prod/packages/{encoded}.metadata"));
+        assert_eq!(status, 200);
+        assert_eq!(body, metadata.as_bytes());
+        let (status, _, _) = get(&repo, &format!("/pip-prod/packages/{filename}"));
+        assert_eq!(status, 200);
+        let (status, _, _) = get(&repo, "/pip-prod/packages/torch-2.1.0%2-cp311.whl");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn accepts_twine_uploads() {
+        let scratch = tempfile::tempdir().unwrap();
+        let filename = "Internal_Lib-0.3.0-py3-none-any.whl";
+        let wheel_bytes = std::fs::read(wheel(
+            scratch.path(),
+            filename,
+            "Metadata-Version: 2.1\nName: internal-lib\nVersion: 0.3.0\n",
+        ))
+        .unwrap();
+        let sha256 = PipRepoFile::sha256_hex(&wheel_bytes);
+        let fields = |sha256| {
+            [
+                (":action", "file_upload"),
+                ("protocol_version", "1"),
+                ("name", "internal-lib"),
+                ("version", "0.3.0"),
+                ("filetype", "bdist_wheel"),
+                ("sha256_digest", sha256),
+            ]
+        };
+        let (content_type, body) = upload_body(&fields(&sha256), filename, &wheel_bytes);
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        assert_eq!(upload(&repo, &content_type, &body).0, 403);
+
+        repo.writable = true;
+        let (status, _, _) = get(&repo, "/pip-prod/legacy/");
+        assert_eq!(status, 405);
+
+        let zeros = "0".repeat(64);
+        let (bad_type, bad_body) = upload_body(&fields(&zeros), filename, &wheel_bytes);
+        let (status, message) = upload(&repo, &bad_type, &bad_body);
+        assert_eq!(status, 400);
+        assert!(
+            message.contains("does not match uploaded file"),
+            "{message}"
+        );
+
+        let (status, message) = upload(&repo, &content_type, &body);
+        assert_eq!((status, message.as_str()), (200, "OK"));
+        let stored = dir.path().join("internal-lib").join(filename);
+        assert_eq!(std::fs::read(&stored).unwrap(), wheel_bytes);
+        assert!(dir
+            .path()
+            .join("internal-lib")
+            .join(format!("{filename}.metadata"))
+            .is_file());
+        let (status, _, _) = get(&repo, &format!("/pip-prod/packages/{filename}.metadata"));
+        assert_eq!(status, 200);
+
+        let (_, _, page) = get(&repo, "/pip-prod/simple/internal-lib/");
+        assert!(String::from_utf8(page)
+            .unwrap()
+            .contains(&format!("{filename}#sha256={sha256}")));
+
+        // Stored files are immutable
+        let (status, message) = upload(&repo, &content_type, &body);
+        assert_eq!(status, 409);
+        assert!(message.starts_with("File already exists"), "{message}");
+        assert_eq!(std::fs::read(&stored).unwrap(), wheel_bytes);
+    }
+
+    #[test]
+    fn rejects_uploads_that_disagree_with_their_metadata() {
+        let scratch = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        repo.writable = true;
+
+        let upload_fields = [
+            (":action", "file_upload"),
+            ("name", "internal-lib"),
+            ("version", "0.3.0"),
+        ];
+
+        // Form fields and file name disagree
+        let (content_type, body) = upload_body(
+            &upload_fields,
+            "internal_lib-0.4.0-py3-none-any.whl",
+            b"wheel",
+        );
+        let (status, message) = upload(&repo, &content_type, &body);
+        assert_eq!(status, 400);
+        assert!(
+            message.contains("does not match internal-lib 0.3.0"),
+            "{message}"
+        );
+
+        // METADATA inside the wheel names another version
+        let filename = "internal_lib-0.3.0-py3-none-any.whl";
+        let wheel_bytes = std::fs::read(wheel(
+            scratch.path(),
+            filename,
+            "Metadata-Version: 2.1\nName: internal-lib\nVersion: 0.2.0\n",
+        ))
+        .unwrap();
+        let (content_type, body) = upload_body(&upload_fields, filename, &wheel_bytes);
+        let (status, message) = upload(&repo, &content_type, &body);
+        assert_eq!(status, 400);
+        assert!(
+            message.contains("metadata declares internal-lib 0.2.0"),
+            "{message}"
+        );
+
+        let (content_type, body) =
+            upload_body(&upload_fields, "../internal_lib-0.3.0.tar.gz", b"sdist");
+        assert_eq!(upload(&repo, &content_type, &body).0, 400);
+
+        let (status, message) = upload(&repo, "application/json", b"{}");
+        assert_eq!(status, 400);
+        assert!(message.contains("multipart/form-data"), "{message}");
+        assert!(repo.index().files("internal-lib").is_none());
+    }
+
+    #[test]
+    fn imports_pip_download_directories() {
+        let download = tempfile::tempdir().unwrap();
+        wheel(
+            download.path(),
+            "six-1.16.0-py2.py3-none-any.whl",
+            "Metadata-Version: 2.1\nName: six\nVersion: 1.16.0\n",
+        );
+        wheel(
+            download.path(),
+            "PyYAML-6.0.2-cp312-cp312-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+            "Metadata-Version: 2.1\nName: PyYAML\nVersion: 6.0.2\nRequires-Python: >=3.8\n",
+        );
+        sdist(
+            download.path(),
+            "pyyaml-6.0.2.tar.gz",
+            "Metadata-Version: 2.1\nName: PyYAML\nVersion: 6.0.2\nRequires-Python: >=3.8\n",
+        );
+        wheel(
+            download.path(),
+            "idna-3.7-py3-none-any.whl",
+            "Metadata-Version: 2.1\nName: idna\nVersion: 3.6\n",
+        );
+        // Built for one platform but named as pure
+        wheel_with_tags(
+            download.path(),
+            "charset_normalizer-3.3.2-py3-none-any.whl",
+            "Metadata-Version: 2.1\nName: charset-normalizer\nVersion: 3.3.2\n",
+            &["cp312-cp312-manylinux_2_17_x86_64".into()],
+        );
+        std::fs::write(download.path().join("requirements.txt"), b"six\n").unwrap();
+        std::fs::write(download.path().join("broken-py3-none-any.whl"), b"").unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo =
+            PipRepoFile::new("pip-prod".into(), dir.path().into(), PUBLIC_URL.into()).unwrap();
+        let report = repo.import_download(download.path()).unwrap();
+
+        let imported: Vec<&str> = report.imported.iter().map(|id| id.name.as_str()).collect();
+        assert_eq!(
+            imported,
+            [
+                "PyYAML-6.0.2-cp312-cp312-manylinux_2_17_x86_64.manylinux2014_x86_64.whl",
+                "pyyaml-6.0.2.tar.gz",
+                "six-1.16.0-py2.py3-none-any.whl",
+            ]
+        );
+        let rejected: Vec<(String, &str)> = report
+            .rejected
+            .iter()
+            .map(|(path, reason)| {
+                (
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                    reason.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(rejected.len(), 4, "{rejected:?}");
+        assert_eq!(rejected[0].0, "broken-py3-none-any.whl");
+        assert!(rejected[0].1.contains("not a wheel or sdist file name"));
+        assert_eq!(rejected[1].0, "charset_normalizer-3.3.2-py3-none-any.whl");
+        assert!(rejected[1]
+            .1
+            .contains("WHEEL declares tags cp312-cp312-manylinux_2_17_x86_64"));
+        assert_eq!(rejected[2].0, "idna-3.7-py3-none-any.whl");
+        assert!(rejected[2].1.contains("metadata declares idna 3.6"));
+        assert_eq!(rejected[3].0, "requirements.txt");
+
+        // Both spellings land under the normalized project, with tags and Requires-Python
+        let index = repo.index();
+        let files = index.files("PyYAML").unwrap();
+        assert_eq!(files.len(), 2);
+        let platform_wheel = &files[0];
+        assert_eq!(platform_wheel.requires_python.as_deref(), Some(">=3.8"));
+        let tags = platform_wheel.tags.as_ref().unwrap();
+        assert_eq!(tags.python, ["cp312"]);
+        assert_eq!(tags.abi, ["cp312"]);
+        assert!(files[1].tags.is_none());
+        assert!(dir
+            .path()
+            .join("pyyaml")
+            .join("pyyaml-6.0.2.tar.gz")
+            .is_file());
+        drop(index);
+
+        let (status, _, body) = get(&repo, "/pip-prod/simple/pyyaml/");
+        assert_eq!(status, 200);
+        assert_eq!(
+            String::from_utf8(body)
+                .unwrap()
+                .matches("data-requires-python")
+                .count(),
+            2
+        );
+
+        let again = repo.import_download(download.path()).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.existing.len(), 3);
+        assert_eq!(again.rejected.len(), 4);
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{Cursor, Read};
+
+use anyhow::Context;
+
+/// The core metadata fields Holger indexes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoreMetadata {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub requires_python: Option<String>,
+}
+
+impl CoreMetadata {
+    /// Parse the email-header part of a `METADATA` / `PKG-INFO` file. Header
+    /// continuation lines are folded; the body after the first blank line is ignored.
+    pub fn parse(text: &str) -> Self {
+        let headers = parse_headers(text);
+        let field = |key: &str| {
+            headers
+                .iter()
+                .find(|(k, v)| k == key && !v.is_empty())
+                .map(|(_, v)| v.clone())
+        };
+        CoreMetadata {
+            name: field("name"),
+            version: field("version"),
+            requires_python: field("requires-python"),
+        }
+    }
+}
+
+/// Email-style `Key: value` headers up to the first blank line, keys
+/// lowercased and continuation lines folded.
+fn parse_headers(text: &str) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            break;
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            headers.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    headers
+}
+
+/// Raw core metadata of a distribution: `*.dist-info/METADATA` for wheels,
+/// the top-level `PKG-INFO` for sdists. `None` if the archive carries none.
+pub fn read_metadata_file(filename: &str, data: &[u8]) -> anyhow::Result<Option<Vec<u8>>> {
+    if filename.ends_with(".whl") {
+        read_zip_member(data, |path| {
+            path.split_once('/')
+                .is_some_and(|(dir, file)| dir.ends_with(".dist-info") && file == "METADATA")
+        })
+    } else if filename.ends_with(".zip") {
+        read_zip_member(data, is_sdist_pkg_info)
+    } else if filename.ends_with(".tar.gz") {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(data));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().into_owned();
+            if is_sdist_pkg_info(&path) {
+                let mut out = Vec::new();
+                entry.read_to_end(&mut out)?;
+                return Ok(Some(out));
+            }
+        }
+        Ok(None)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Parsed core metadata of a distribution, see [`read_metadata_file`].
+pub fn read_core_metadata(filename: &str, data: &[u8]) -> anyhow::Result<Option<CoreMetadata>> {
+    Ok(read_metadata_file(filename, data)?
+        .map(|raw| CoreMetadata::parse(&String::from_utf8_lossy(&raw))))
+}
+
+/// `Tag` lines of a wheel's `*.dist-info/WHEEL` file, e.g. `py3-none-any`.
+/// `None` if the wheel carries no `WHEEL` file.
+pub fn read_wheel_tags(data: &[u8]) -> anyhow::Result<Option<Vec<String>>> {
+    let wheel = read_zip_member(data, |path| {
+        path.split_once('/')
+            .is_some_and(|(dir, file)| dir.ends_with(".dist-info") && file == "WHEEL")
+    })?;
+    Ok(wheel.map(|raw| {
+        parse_headers(&String::from_utf8_lossy(&raw))
+            .into_iter()
+            .filter(|(key, _)| key == "tag")
+            .map(|(_, value)| value)
+            .collect()
+    }))
+}
+
+/// `{name}-{version}/PKG-INFO`, not the copies below `*.egg-info/`.
+fn is_sdist_pkg_info(path: &str) -> bool {
+    path.split_once('/')
+        .is_some_and(|(_, rest)| rest == "PKG-INFO")
+}
+
+fn read_zip_member(data: &[u8], matches: impl Fn(&str) -> bool) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data)).context("invalid zip archive")?;
+    let Some(name) = archive
+        .file_names()
+        .find(|n| matches(n))
+        .map(str::to_string)
+    else {
+        return Ok(None);
+    };
+    let mut out = Vec::new();
+    archive.by_name(&name)?.read_to_end(&mut out)?;
+    Ok(Some(out))
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use holger_traits::util::percent_encode;
+use serde::Serialize;
+
+use crate::index::{DistributionFile, ProjectIndex};
+
+/// Simple API page formats, in the order ties between equal `q` values are resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageFormat {
+    /// PEP 503 HTML as `text/html`, for clients that send no or a generic `Accept`
+    LegacyHtml,
+    /// PEP 691 HTML
+    Html,
+    /// PEP 691 JSON
+    Json,
+}
+
+impl PageFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            PageFormat::LegacyHtml => "text/html; charset=utf-8",
+            PageFormat::Html => "application/vnd.pypi.simple.v1+html",
+            PageFormat::Json => "application/vnd.pypi.simple.v1+json",
+        }
+    }
+
+    fn media_types(self) -> &'static [&'static str] {
+        match self {
+            PageFormat::LegacyHtml => &["text/html"],
+            PageFormat::Html => &[
+                "application/vnd.pypi.simple.v1+html",
+                "application/vnd.pypi.simple.latest+html",
+            ],
+            PageFormat::Json => &[
+                "application/vnd.pypi.simple.v1+json",
+                "application/vnd.pypi.simple.latest+json",
+            ],
+        }
+    }
+
+    /// Pick the format an `Accept` header prefers. Falls back to
+    /// [`PageFormat::LegacyHtml`] when the header is missing or matches nothing.
+    pub fn negotiate(accept: Option<&str>) -> PageFormat {
+        let Some(accept) = accept else {
+            return PageFormat::LegacyHtml;
+        };
+        let ranges: Vec<(String, f32)> = accept
+            .split(',')
+            .filter_map(|range| {
+                let mut params = range.split(';');
+                let media = params.next()?.trim().to_ascii_lowercase();
+                let q = params
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .map_or(1.0, |q| q.trim().parse().unwrap_or(0.0));
+                (!media.is_empty()).then_some((media, q))
+            })
+            .collect();
+
+        let mut best = (PageFormat::LegacyHtml, 0.0);
+        for format in [PageFormat::LegacyHtml, PageFormat::Html, PageFormat::Json] {
+            let q = format.quality(&ranges);
+            if q > best.1 {
+                best = (format, q);
+            }
+        }
+        best.0
+    }
+
+    /// `q` of the most specific range matching one of this format's media types.
+    fn quality(self, ranges: &[(String, f32)]) -> f32 {
+        let mut best: Option<(u8, f32)> = None;
+        for media in self.media_types() {
+            let kind = media.split('/').next().unwrap_or_default();
+            for (range, q) in ranges {
+                let specificity = if range == media {
+                    2
+                } else if range.strip_suffix("/*") == Some(kind) {
+                    1
+                } else if range == "*/*" {
+                    0
+                } else {
+                    continue;
+                };
+                if best.is_none_or(|(s, _)| specificity > s) {
+                    best = Some((specificity, *q));
+                }
+            }
+        }
+        best.map_or(0.0, |(_, q)| q)
+    }
+}
+
+#[derive(Serialize)]
+struct Meta {
+    #[serde(rename = "api-version")]
+    api_version: &'static str,
+}
+
+const META: Meta = Meta { api_version: "1.0" };
+
+#[derive(Serialize)]
+struct ProjectListJson<'a> {
+    meta: Meta,
+    projects: Vec<ProjectName<'a>>,
+}
+
+#[derive(Serialize)]
+struct ProjectName<'a> {
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct ProjectPageJson<'a> {
+    meta: Meta,
+    name: &'a str,
+    files: Vec<FileJson<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct FileJson<'a> {
+    filename: &'a str,
+    url: String,
+    hashes: BTreeMap<&'static str, &'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    requires_python: Option<&'a str>,
+    core_metadata: serde_json::Value,
+    dist_info_metadata: serde_json::Value,
+    yanked: bool,
+}
+
+/// PEP 691 form of [`project_list_html`].
+pub fn project_list_json(index: &ProjectIndex) -> String {
+    let list = ProjectListJson {
+        meta: META,
+        projects: index
+            .projects()
+            .map(|(name, _)| ProjectName { name })
+            .collect(),
+    };
+    serde_json::to_string(&list).expect("project list serializes")
+}
+
+/// PEP 691 form of [`project_page_html`].
+pub fn project_page_json(project: &str, files: &[DistributionFile], public_url: &str) -> String {
+    let page = ProjectPageJson {
+        meta: META,
+        name: project,
+        files: files
+            .iter()
+            .map(|file| {
+                // `false`, or the hashes of the served `<file>.metadata`
+                let core_metadata = match &file.core_metadata_sha256 {
+                    Some(sha256) => serde_json::json!({ "sha256": sha256 }),
+                    None => serde_json::Value::Bool(false),
+                };
+                FileJson {
+                    filename: &file.filename,
+                    url: file_url(public_url, file),
+                    hashes: BTreeMap::from([("sha256", file.sha256.as_str())]),
+                    requires_python: file.requires_python.as_deref(),
+                    dist_info_metadata: core_metadata.clone(),
+                    core_metadata,
+                    yanked: false,
+                }
+            })
+            .collect(),
+    };
+    serde_json::to_string(&page).expect("project page serializes")
+}
+
+/// `/simple/`: one anchor per project, linking to its normalized page.
+pub fn project_list_html(index: &ProjectIndex) -> String {
+    let mut html = page_head("Simple index");
+    for (name, _) in index.projects() {
+        let name = escape(name);
+        let _ = writeln!(html, "    <a href=\"{name}/\">{name}</a><br/>");
+    }
+    html.push_str(PAGE_TAIL);
+    html
+}
+
+/// `/simple/<project>/`: one anchor per file with its sha256 fragment and
+/// `data-requires-python` when the metadata declares it.
+pub fn project_page_html(project: &str, files: &[DistributionFile], public_url: &str) -> String {
+    let mut html = page_head(&format!("Links for {}", project));
+    for file in files {
+        let href = format!("{}#sha256={}", file_url(public_url, file), file.sha256);
+        let _ = write!(html, "    <a href=\"{}\"", escape(&href));
+        if let Some(requires_python) = &file.requires_python {
+            let _ = write!(
+                html,
+                " data-requires-python=\"{}\"",
+                escape(requires_python)
+            );
+        }
+        if let Some(sha256) = &file.core_metadata_sha256 {
+            // PEP 714 name, plus the PEP 658 one older pip still reads
+            let _ = write!(
+                html,
+                " data-core-metadata=\"sha256={sha256}\" data-dist-info-metadata=\"sha256={sha256}\""
+            );
+        }
+        let _ = writeln!(html, ">{}</a><br/>", escape(&file.filename));
+    }
+    html.push_str(PAGE_TAIL);
+    html
+}
+
+/// Download URL of a stored file: `{public_url}/packages/{filename}`, with
+/// the file name percent-encoded (`+` in local versions becomes `%2B`).
+pub fn file_url(public_url: &str, file: &DistributionFile) -> String {
+    format!("{}/packages/{}", public_url, percent_encode(&file.filename))
+}
+
+fn page_head(title: &str) -> String {
+    let title = escape(title);
+    format!(
+        "<!DOCTYPE html>\n<html>\n  <head>\n    <meta name=\"pypi:repository-version\" content=\"1.0\">\n    <title>{title}</title>\n  </head>\n  <body>\n    <h1>{title}</h1>\n"
+    )
+}
+
+const PAGE_TAIL: &str = "  </body>\n</html>\n";
+
+/// Escape text for HTML content and double-quoted attributes.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#x27;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
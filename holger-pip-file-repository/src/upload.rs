@@ -0,0 +1,149 @@
+use anyhow::{anyhow, bail, Context};
+use holger_traits::ArtifactId;
+
+use crate::index::{normalize_name, parse_distribution_filename};
+
+/// A decoded legacy upload (`twine upload`) request. Only the fields Holger
+/// checks are kept; the index entry is rebuilt from the stored file.
+#[derive(Debug)]
+pub struct UploadRequest<'a> {
+    pub name: String,
+    pub version: String,
+    pub filename: String,
+    /// Hex sha256 the client computed, if it sent one
+    pub sha256_digest: Option<String>,
+    pub content: &'a [u8],
+}
+
+impl<'a> UploadRequest<'a> {
+    /// Decode the `multipart/form-data` body of a `:action=file_upload` request
+    /// and check that the file name agrees with the `name` and `version` fields.
+    pub fn parse(content_type: &str, body: &'a [u8]) -> anyhow::Result<Self> {
+        let boundary = multipart_boundary(content_type)?;
+        let parts = parse_multipart(body, boundary)?;
+        let field = |name: &str| {
+            parts
+                .iter()
+                .find(|p| p.name == name && p.filename.is_none())
+                .map(|p| String::from_utf8_lossy(p.data).trim().to_string())
+        };
+
+        match field(":action").as_deref() {
+            Some("file_upload") => {}
+            other => bail!("unsupported :action `{}`", other.unwrap_or_default()),
+        }
+        let content = parts
+            .iter()
+            .find(|p| p.name == "content")
+            .ok_or_else(|| anyhow!("missing content field"))?;
+        let filename = content
+            .filename
+            .clone()
+            .ok_or_else(|| anyhow!("content field has no file name"))?;
+        let name = field("name").ok_or_else(|| anyhow!("missing name field"))?;
+        let version = field("version").ok_or_else(|| anyhow!("missing version field"))?;
+
+        let (file_project, file_version) = parse_filename(&filename)?;
+        if normalize_name(file_project) != normalize_name(&name)
+            || !file_version.eq_ignore_ascii_case(&version)
+        {
+            bail!(
+                "file name `{}` does not match {} {}",
+                filename,
+                name,
+                version
+            );
+        }
+        Ok(UploadRequest {
+            name,
+            version,
+            filename,
+            sha256_digest: field("sha256_digest").filter(|d| !d.is_empty()),
+            content: content.data,
+        })
+    }
+
+    pub fn artifact_id(&self) -> ArtifactId {
+        ArtifactId {
+            namespace: None,
+            name: self.filename.clone(),
+            version: self.version.clone(),
+        }
+    }
+}
+
+/// Split an uploaded file name into project and version, refusing anything
+/// that is not a plain distribution file name.
+pub fn parse_filename(filename: &str) -> anyhow::Result<(&str, &str)> {
+    if filename.contains(['/', '\\']) || filename.starts_with('.') {
+        bail!("invalid file name `{}`", filename);
+    }
+    parse_distribution_filename(filename)
+        .ok_or_else(|| anyhow!("`{}` is not a wheel or sdist file name", filename))
+}
+
+/// One `multipart/form-data` part.
+#[derive(Debug)]
+struct FormPart<'a> {
+    name: String,
+    filename: Option<String>,
+    data: &'a [u8],
+}
+
+fn multipart_boundary(content_type: &str) -> anyhow::Result<&str> {
+    let (mime, params) = content_type.split_once(';').unwrap_or((content_type, ""));
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        bail!("expected multipart/form-data, got `{}`", content_type);
+    }
+    params
+        .split(';')
+        .find_map(|p| p.trim().strip_prefix("boundary="))
+        .map(|b| b.trim_matches('"'))
+        .filter(|b| !b.is_empty())
+        .ok_or_else(|| anyhow!("multipart boundary missing"))
+}
+
+fn parse_multipart<'a>(body: &'a [u8], boundary: &str) -> anyhow::Result<Vec<FormPart<'a>>> {
+    let first = format!("--{boundary}");
+    let next = format!("\r\n--{boundary}");
+    let start = find(body, first.as_bytes()).context("multipart body has no parts")?;
+    let mut rest = &body[start + first.len()..];
+
+    let mut parts = Vec::new();
+    // `--` after a delimiter closes the body
+    while !rest.starts_with(b"--") {
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .ok_or_else(|| anyhow!("malformed multipart delimiter"))?;
+        let end = find(rest, next.as_bytes()).context("unterminated multipart part")?;
+        let (part, tail) = rest.split_at(end);
+        rest = &tail[next.len()..];
+
+        let header_end = find(part, b"\r\n\r\n").context("multipart part without headers")?;
+        let headers = std::str::from_utf8(&part[..header_end]).context("invalid part headers")?;
+        let disposition = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case("content-disposition"))
+            .map(|(_, v)| v)
+            .ok_or_else(|| anyhow!("multipart part without Content-Disposition"))?;
+
+        let param = |key: &str| {
+            disposition
+                .split(';')
+                .filter_map(|p| p.trim().split_once('='))
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.trim_matches('"').to_string())
+        };
+        parts.push(FormPart {
+            name: param("name").ok_or_else(|| anyhow!("multipart part without a name"))?,
+            filename: param("filename"),
+            data: &part[header_end + 4..],
+        });
+    }
+    Ok(parts)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
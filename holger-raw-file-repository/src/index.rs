@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+/// One stored file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawFile {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Modification time in Unix seconds
+    pub modified: u64,
+}
+
+impl RawFile {
+    /// Describe a file on disk.
+    pub fn from_path(path: PathBuf) -> anyhow::Result<Self> {
+        let metadata = std::fs::metadata(&path)?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        Ok(RawFile {
+            path,
+            size: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// A child of a listed directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry<'a> {
+    Directory(&'a str),
+    File(&'a str, &'a RawFile),
+}
+
+/// In-memory view of the stored files, keyed by `/`-separated path relative to the root.
+#[derive(Debug, Default)]
+pub struct RawIndex {
+    files: BTreeMap<String, RawFile>,
+}
+
+impl RawIndex {
+    /// Add a file, replacing an earlier one at the same path.
+    pub fn insert(&mut self, relative_path: &str, file: RawFile) {
+        self.files.insert(relative_path.to_string(), file);
+    }
+
+    pub fn get(&self, relative_path: &str) -> Option<&RawFile> {
+        self.files.get(relative_path)
+    }
+
+    /// Whether any file is stored below `dir`; the root always exists.
+    pub fn is_dir(&self, dir: &str) -> bool {
+        dir.is_empty() || self.below(format!("{dir}/")).next().is_some()
+    }
+
+    /// Immediate children of `dir` (empty for the root), directories first,
+    /// each group by name. `None` if the directory does not exist.
+    pub fn entries(&self, dir: &str) -> Option<Vec<Entry<'_>>> {
+        if !self.is_dir(dir) {
+            return None;
+        }
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        let mut directories: Vec<&str> = Vec::new();
+        let mut files = Vec::new();
+        for (rel, file) in self.below(prefix.clone()) {
+            let rest = &rel[prefix.len()..];
+            match rest.split_once('/') {
+                Some((child, _)) => {
+                    if directories.last() != Some(&child) {
+                        directories.push(child);
+                    }
+                }
+                None => files.push(Entry::File(rest, file)),
+            }
+        }
+        Some(
+            directories
+                .into_iter()
+                .map(Entry::Directory)
+                .chain(files)
+                .collect(),
+        )
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &RawFile)> {
+        self.files.iter().map(|(rel, file)| (rel.as_str(), file))
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn below(&self, prefix: String) -> impl Iterator<Item = (&String, &RawFile)> {
+        self.files
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(move |(rel, _)| rel.starts_with(&prefix))
+    }
+}
@@ -0,0 +1,482 @@
+use anyhow::bail;
+use holger_traits::util::{find_files, percent_encode, write_atomic};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::path::PathBuf;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub mod index;
+pub mod listing;
+
+use index::{RawFile, RawIndex};
+use listing::{content_type, decode_path, listing_html, listing_json, wants_json};
+
+/// Generic file repository: arbitrary files under hierarchical paths, such as
+/// toolchain tarballs, installers and firmware images.
+///
+/// Artifacts are addressed by path: `ArtifactId { namespace: None, name:
+/// "tools/cmake/cmake-3.29.3-linux-x86_64.tar.gz", version: "" }`.
+pub struct RawRepoFile {
+    pub name: String,
+    pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/raw-prod
+    /// Accept PUT uploads
+    pub writable: bool,
+    pub index: RwLock<RawIndex>,
+}
+
+impl RawRepoFile {
+    /// Open a repository rooted at `root`, indexing every file below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = RawRepoFile {
+            name,
+            artifacts: vec![],
+            root,
+            public_url,
+            writable: false,
+            index: RwLock::new(RawIndex::default()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the files on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = RawIndex::default();
+        for (rel, path) in find_files(&self.root)? {
+            index.insert(&rel, RawFile::from_path(path)?);
+        }
+        self.artifacts = index
+            .iter()
+            .map(|(rel, _)| ArtifactId {
+                namespace: None,
+                name: rel.to_string(),
+                version: String::new(),
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, RawIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, RawIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Store a new file at `rel`. Stored files are never replaced, and a path
+    /// cannot be both a file and a directory; errors are I/O failures.
+    fn store(&self, rel: &str, data: &[u8]) -> anyhow::Result<Store> {
+        // Hold the write lock across the conflict checks, the write and the index update
+        let mut index = self.index_mut();
+        if index.get(rel).is_some() {
+            return Ok(Store::Conflict(format!("{} already exists", rel)));
+        }
+        if index.is_dir(rel) {
+            return Ok(Store::Conflict(format!("{} is a directory", rel)));
+        }
+        let mut parent = rel;
+        while let Some((dir, _)) = parent.rsplit_once('/') {
+            if index.get(dir).is_some() {
+                return Ok(Store::Conflict(format!("{} is a file", dir)));
+            }
+            parent = dir;
+        }
+        let path = self.root.join(rel);
+        write_atomic(&path, data)?;
+        index.insert(rel, RawFile::from_path(path)?);
+        Ok(Store::Stored)
+    }
+}
+
+/// Outcome of storing an upload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Store {
+    Stored,
+    /// The path is taken by a stored file or a directory
+    Conflict(String),
+}
+
+impl RepositoryBackendTrait for RawRepoFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn handle_http2_request(
+        &self,
+        method: &str,
+        suburl: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> anyhow::Result<Http2Response> {
+        println!("Raw repo handle_http2_request.suburl={}", suburl);
+
+        let (path, query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+
+        match parts.as_slice() {
+            // Upload → PUT /raw-prod/tools/cmake/cmake-3.29.3-linux-x86_64.tar.gz
+            [repo, rest @ ..] if *repo == self.name() && method == "PUT" => {
+                if !self.is_writable() {
+                    return Ok((403, Vec::new(), b"Repository is read-only".to_vec()));
+                }
+                let Some(rel) = decode_path(rest).filter(|rel| !rel.is_empty()) else {
+                    return Ok((400, Vec::new(), b"Invalid path".to_vec()));
+                };
+                println!("Upload request: file={}", rel);
+                match self.store(&rel, body)? {
+                    Store::Stored => Ok((200, Vec::new(), b"OK".to_vec())),
+                    Store::Conflict(reason) => Ok((409, Vec::new(), reason.into_bytes())),
+                }
+            }
+
+            [repo, ..] if *repo == self.name() && method != "GET" && method != "HEAD" => {
+                Ok((405, Vec::new(), b"Method not allowed".to_vec()))
+            }
+
+            // File → /raw-prod/tools/cmake/cmake-3.29.3-linux-x86_64.tar.gz
+            // Listing → /raw-prod/tools/cmake/
+            [repo, rest @ ..] if *repo == self.name() => {
+                let (segments, is_dir_url) = match rest {
+                    [] => (rest, false),
+                    [segments @ .., ""] => (segments, true),
+                    _ => (rest, false),
+                };
+                let Some(rel) = decode_path(segments) else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                if !is_dir_url {
+                    let file = self.index().get(&rel).cloned();
+                    if let Some(file) = file {
+                        return Ok((
+                            200,
+                            vec![("Content-Type".into(), content_type(&rel).into())],
+                            std::fs::read(&file.path)?,
+                        ));
+                    }
+                }
+                let index = self.index();
+                let Some(entries) = index.entries(&rel) else {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                };
+                if !is_dir_url {
+                    // Relative links in listings need the trailing slash
+                    let mut location = self.public_url.trim_end_matches('/').to_string();
+                    for segment in rel.split('/').filter(|s| !s.is_empty()) {
+                        location.push('/');
+                        location.push_str(&percent_encode(segment));
+                    }
+                    location.push('/');
+                    return Ok((301, vec![("Location".into(), location)], Vec::new()));
+                }
+                if wants_json(header("accept"), query) {
+                    Ok((
+                        200,
+                        vec![("Content-Type".into(), "application/json".into())],
+                        listing_json(&self.public_url, &rel, &entries)?,
+                    ))
+                } else {
+                    Ok((
+                        200,
+                        vec![("Content-Type".into(), "text/html; charset=utf-8".into())],
+                        listing_html(&rel, &entries).into_bytes(),
+                    ))
+                }
+            }
+
+            _ => {
+                println!("Unhandled path: {}", suburl);
+                Ok((404, Vec::new(), b"Not found".to_vec()))
+            }
+        }
+    }
+
+    fn format(&self) -> ArtifactFormat {
+        ArtifactFormat::Raw
+    }
+
+    fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.index().get(&id.name).map(|f| f.path.clone());
+        match path {
+            Some(path) => Ok(Some(std::fs::read(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn put(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
+        if !self.writable {
+            bail!("repository {} is read-only", self.name);
+        }
+        let segments: Vec<&str> = id.name.split('/').collect();
+        match decode_path(&segments) {
+            Some(rel) if rel == id.name && !rel.is_empty() => match self.store(&rel, data)? {
+                Store::Stored => Ok(()),
+                Store::Conflict(reason) => bail!(reason),
+            },
+            _ => bail!("`{}` is not a valid file path", id.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn store(root: &Path, rel: &str, data: &[u8]) {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+
+    fn request(repo: &RawRepoFile, method: &str, suburl: &str, body: &[u8]) -> Http2Response {
+        repo.handle_http2_request(method, suburl, &[], body)
+            .unwrap()
+    }
+
+    fn open(root: &Path) -> RawRepoFile {
+        RawRepoFile::new(
+            "raw-prod".into(),
+            root.into(),
+            "https://10.101.1.9:8443/raw-prod".into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn guesses_content_types() {
+        use listing::content_type;
+
+        assert_eq!(
+            content_type("cmake-3.29.3-linux-x86_64.tar.gz"),
+            "application/gzip"
+        );
+        assert_eq!(content_type("node-v20.tgz"), "application/gzip");
+        assert_eq!(
+            content_type("SHA256SUMS.sha256"),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(
+            content_type("setup.EXE"),
+            "application/vnd.microsoft.portable-executable"
+        );
+        assert_eq!(content_type("firmware.bin"), "application/octet-stream");
+        assert_eq!(content_type("README"), "application/octet-stream");
+        assert!(wants_json(Some("text/html, application/json;q=0.9"), ""));
+        assert!(wants_json(None, "a=b&format=json"));
+        assert!(!wants_json(Some("text/html"), ""));
+    }
+
+    #[test]
+    fn serves_files_and_listings() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        store(
+            root,
+            "tools/cmake/cmake-3.29.3-linux-x86_64.tar.gz",
+            b"cmake",
+        );
+        store(root, "tools/cmake/SHA256SUMS", b"sums");
+        store(root, "tools/cmake/old/cmake-3.20.0.tar.gz", b"old");
+        store(root, "tools/ninja 1.12/ninja.zip", b"ninja");
+        store(root, "README.txt", b"readme");
+        store(root, ".hidden/secret", b"secret");
+
+        let repo = open(root);
+        assert_eq!(repo.artifacts.len(), 5);
+
+        let (status, headers, body) = request(
+            &repo,
+            "GET",
+            "/raw-prod/tools/cmake/cmake-3.29.3-linux-x86_64.tar.gz",
+            b"",
+        );
+        assert_eq!((status, headers[0].1.as_str()), (200, "application/gzip"));
+        assert_eq!(body, b"cmake");
+        let (status, _, body) =
+            request(&repo, "GET", "/raw-prod/tools/ninja%201.12/ninja.zip", b"");
+        assert_eq!((status, body.as_slice()), (200, b"ninja".as_slice()));
+        let id = ArtifactId {
+            namespace: None,
+            name: "README.txt".into(),
+            version: String::new(),
+        };
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), b"readme");
+
+        let (status, headers, body) = request(&repo, "GET", "/raw-prod/tools/", b"");
+        assert_eq!(
+            (status, headers[0].1.as_str()),
+            (200, "text/html; charset=utf-8")
+        );
+        let html = String::from_utf8(body).unwrap();
+        assert!(html.contains("<title>Index of /tools/</title>"));
+        assert!(html.contains(r#"<a href="../">../</a>"#));
+        assert!(html.contains(r#"<a href="cmake/">cmake/</a>"#));
+        assert!(html.contains(r#"<a href="ninja%201.12/">ninja 1.12/</a>"#));
+        let (_, _, body) = request(&repo, "GET", "/raw-prod/", b"");
+        let html = String::from_utf8(body).unwrap();
+        assert!(!html.contains("../"));
+        assert!(!html.contains("hidden"));
+        assert!(html.find("tools/").unwrap() < html.find("README.txt").unwrap());
+
+        let (status, headers, body) = repo
+            .handle_http2_request(
+                "GET",
+                "/raw-prod/tools/cmake/",
+                &[("Accept".into(), "application/json".into())],
+                b"",
+            )
+            .unwrap();
+        assert_eq!((status, headers[0].1.as_str()), (200, "application/json"));
+        let listing: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(listing["path"], "/tools/cmake/");
+        let entries = listing["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["type"], "directory");
+        assert_eq!(
+            entries[0]["url"],
+            "https://10.101.1.9:8443/raw-prod/tools/cmake/old/"
+        );
+        assert_eq!(entries[1]["type"], "file");
+        assert_eq!(entries[1]["name"], "SHA256SUMS");
+        assert_eq!(entries[1]["size"], 4);
+        assert!(entries[1]["modified"].as_str().unwrap().ends_with('Z'));
+        let (_, _, body) = request(&repo, "GET", "/raw-prod/tools/cmake/?format=json", b"");
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            listing
+        );
+
+        // Directories are listed under their trailing-slash URL
+        let (status, headers, _) = request(&repo, "GET", "/raw-prod/tools/ninja%201.12", b"");
+        assert_eq!(status, 301);
+        assert_eq!(
+            headers[0],
+            (
+                "Location".into(),
+                "https://10.101.1.9:8443/raw-prod/tools/ninja%201.12/".into()
+            )
+        );
+        let (status, headers, _) = request(&repo, "GET", "/raw-prod", b"");
+        assert_eq!(status, 301);
+        assert_eq!(headers[0].1, "https://10.101.1.9:8443/raw-prod/");
+
+        for missing in [
+            "/raw-prod/tools/cmake/missing.tar.gz",
+            "/raw-prod/README.txt/",
+            "/raw-prod/nope/",
+            "/raw-prod/.hidden/secret",
+            "/raw-prod/tools/../README.txt",
+            "/raw-prod/tools/%2e%2e/README.txt",
+            "/raw-prod/tools%2f..%2fREADME.txt",
+            "/raw-prod/tools//cmake/",
+            "/other/README.txt",
+        ] {
+            assert_eq!(request(&repo, "GET", missing, b"").0, 404, "{missing}");
+        }
+        assert_eq!(request(&repo, "DELETE", "/raw-prod/README.txt", b"").0, 405);
+    }
+
+    #[test]
+    fn accepts_uploads_when_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        store(root, "fw/board-a/v1.bin", b"v1");
+
+        let mut repo = open(root);
+        let upload = "/raw-prod/fw/board-a/v2.bin";
+        assert_eq!(request(&repo, "PUT", upload, b"v2").0, 403);
+
+        repo.writable = true;
+        assert_eq!(
+            request(&repo, "PUT", upload, b"v2"),
+            (200, Vec::new(), b"OK".to_vec())
+        );
+        assert_eq!(request(&repo, "GET", upload, b"").2, b"v2");
+        assert_eq!(
+            std::fs::read(root.join("fw/board-a/v2.bin")).unwrap(),
+            b"v2"
+        );
+        let (status, _, body) = request(&repo, "PUT", "/raw-prod/fw/board%20b/v1.bin", b"b");
+        assert_eq!((status, body.as_slice()), (200, b"OK".as_slice()));
+        assert!(root.join("fw/board b/v1.bin").is_file());
+
+        // Stored files are immutable, and a path is either a file or a directory
+        for conflict in [
+            upload,
+            "/raw-prod/fw/board-a",
+            "/raw-prod/fw/board-a/v1.bin/x",
+        ] {
+            assert_eq!(request(&repo, "PUT", conflict, b"x").0, 409, "{conflict}");
+        }
+        assert_eq!(
+            std::fs::read(root.join("fw/board-a/v2.bin")).unwrap(),
+            b"v2"
+        );
+
+        // Temporary files cannot collide with a name a client can upload
+        for name in ["a.bin.tmp", "a.bin"] {
+            let url = format!("/raw-prod/tmp/{name}");
+            assert_eq!(request(&repo, "PUT", &url, name.as_bytes()).0, 200);
+        }
+        let mut names: Vec<String> = std::fs::read_dir(root.join("tmp"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a.bin", "a.bin.tmp"]);
+        assert_eq!(
+            request(&repo, "GET", "/raw-prod/tmp/a.bin.tmp", b"").2,
+            b"a.bin.tmp"
+        );
+
+        // Failed writes are server errors, not conflicts
+        std::fs::write(root.join("late"), b"not a directory").unwrap();
+        assert!(repo
+            .handle_http2_request("PUT", "/raw-prod/late/x.bin", &[], b"x")
+            .is_err());
+
+        for invalid in [
+            "/raw-prod/",
+            "/raw-prod/fw/",
+            "/raw-prod/../escape.bin",
+            "/raw-prod/fw/%2e%2e/%2e%2e/escape.bin",
+            "/raw-prod/fw/..%2f..%2fescape.bin",
+            "/raw-prod/fw/.hidden",
+            "/raw-prod/fw//x.bin",
+        ] {
+            assert_eq!(request(&repo, "PUT", invalid, b"x").0, 400, "{invalid}");
+        }
+        assert!(!dir.path().parent().unwrap().join("escape.bin").exists());
+
+        let id = ArtifactId {
+            namespace: None,
+            name: "fw/board-c/v1.bin".into(),
+            version: String::new(),
+        };
+        repo.put(&id, b"c").unwrap();
+        assert_eq!(repo.fetch(&id).unwrap().unwrap(), b"c");
+        let escape = ArtifactId {
+            name: "../escape.bin".into(),
+            ..id
+        };
+        assert!(repo.put(&escape, b"x").is_err());
+
+        // Uploads survive a restart
+        let reopened = open(root);
+        assert_eq!(reopened.artifacts.len(), 7);
+    }
+}
@@ -0,0 +1,165 @@
+use holger_traits::util::{format_rfc3339, percent_decode, percent_encode};
+use serde::Serialize;
+
+use crate::index::Entry;
+
+/// Percent-decode the URL segments of a stored path and check them: no empty,
+/// `.`-prefixed or traversal segments and no separators inside a segment.
+/// `Some("")` is the repository root.
+pub fn decode_path(segments: &[&str]) -> Option<String> {
+    let mut decoded = Vec::with_capacity(segments.len());
+    for segment in segments {
+        let segment = percent_decode(segment)?;
+        if segment.is_empty() || segment.starts_with('.') || segment.contains(['/', '\\', '\0']) {
+            return None;
+        }
+        decoded.push(segment);
+    }
+    Some(decoded.join("/"))
+}
+
+/// Content type guessed from the file name.
+pub fn content_type(filename: &str) -> &'static str {
+    let lower = filename.to_ascii_lowercase();
+    if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        return "application/gzip";
+    }
+    match lower.rsplit('.').next().unwrap_or_default() {
+        "txt" | "md" | "sha1" | "sha256" | "sha512" | "md5" | "asc" | "sig" => {
+            "text/plain; charset=utf-8"
+        }
+        "html" | "htm" => "text/html; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "svg" => "image/svg+xml",
+        "gz" => "application/gzip",
+        "xz" => "application/x-xz",
+        "bz2" => "application/x-bzip2",
+        "zst" => "application/zstd",
+        "tar" => "application/x-tar",
+        "zip" => "application/zip",
+        "7z" => "application/x-7z-compressed",
+        "jar" => "application/java-archive",
+        "deb" => "application/vnd.debian.binary-package",
+        "rpm" => "application/x-rpm",
+        "msi" => "application/x-msi",
+        "exe" | "dll" => "application/vnd.microsoft.portable-executable",
+        "dmg" => "application/x-apple-diskimage",
+        "iso" => "application/x-iso9660-image",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Whether a listing request asks for JSON, by `Accept` header or `?format=json`.
+pub fn wants_json(accept: Option<&str>, query: &str) -> bool {
+    query.split('&').any(|pair| pair == "format=json")
+        || accept.is_some_and(|accept| {
+            accept.split(',').any(|range| {
+                range.split(';').next().unwrap_or_default().trim() == "application/json"
+            })
+        })
+}
+
+#[derive(Serialize)]
+struct JsonListing<'a> {
+    path: String,
+    entries: Vec<JsonEntry<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonEntry<'a> {
+    Directory {
+        name: &'a str,
+        url: String,
+    },
+    File {
+        name: &'a str,
+        url: String,
+        size: u64,
+        modified: String,
+    },
+}
+
+/// JSON listing of the directory `dir` below `base_url`.
+pub fn listing_json(base_url: &str, dir: &str, entries: &[Entry<'_>]) -> anyhow::Result<Vec<u8>> {
+    let dir_url = dir_url(base_url, dir);
+    let listing = JsonListing {
+        path: display_path(dir),
+        entries: entries
+            .iter()
+            .map(|entry| match entry {
+                Entry::Directory(name) => JsonEntry::Directory {
+                    name,
+                    url: format!("{dir_url}{}/", percent_encode(name)),
+                },
+                Entry::File(name, file) => JsonEntry::File {
+                    name,
+                    url: format!("{dir_url}{}", percent_encode(name)),
+                    size: file.size,
+                    modified: format_rfc3339(file.modified),
+                },
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_vec_pretty(&listing)?)
+}
+
+/// HTML listing of the directory `dir`, with links relative to it.
+pub fn listing_html(dir: &str, entries: &[Entry<'_>]) -> String {
+    let title = format!("Index of {}", escape_html(&display_path(dir)));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n  <head>\n    <title>{title}</title>\n  </head>\n  <body>\n    <h1>{title}</h1>\n    <ul>\n"
+    );
+    if !dir.is_empty() {
+        html.push_str("      <li><a href=\"../\">../</a></li>\n");
+    }
+    for entry in entries {
+        let line = match entry {
+            Entry::Directory(name) => format!(
+                "      <li><a href=\"{}/\">{}/</a></li>\n",
+                percent_encode(name),
+                escape_html(name)
+            ),
+            Entry::File(name, file) => format!(
+                "      <li><a href=\"{}\">{}</a> {} bytes, {}</li>\n",
+                percent_encode(name),
+                escape_html(name),
+                file.size,
+                format_rfc3339(file.modified)
+            ),
+        };
+        html.push_str(&line);
+    }
+    html.push_str("    </ul>\n  </body>\n</html>\n");
+    html
+}
+
+/// `/tools/cmake/` for `tools/cmake`, `/` for the root.
+fn display_path(dir: &str) -> String {
+    if dir.is_empty() {
+        "/".into()
+    } else {
+        format!("/{dir}/")
+    }
+}
+
+fn dir_url(base_url: &str, dir: &str) -> String {
+    let mut url = format!("{}/", base_url.trim_end_matches('/'));
+    for segment in dir.split('/').filter(|s| !s.is_empty()) {
+        url.push_str(&percent_encode(segment));
+        url.push('/');
+    }
+    url
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
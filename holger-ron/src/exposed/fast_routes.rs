@@ -1,6 +1,5 @@
 use std::sync::Arc;
-use holger_traits::RepositoryBackendTrait;
-use crate::exposed::http2_backend::Http2Backend;
+use holger_traits::{ArtifactFormat, RepositoryBackendTrait};
 
 
 #[derive(Clone)]
@@ -73,4 +72,15 @@ impl FastRoutes {
         }
         None
     }
+
+    /// The first route serving `format`, for requests that name no repository.
+    pub fn first_of_format(
+        &self,
+        format: ArtifactFormat,
+    ) -> Option<&Arc<dyn RepositoryBackendTrait>> {
+        self.routes
+            .iter()
+            .find(|r| r.backend.format() == format)
+            .map(|r| &r.backend)
+    }
 }
\ No newline at end of file
@@ -22,8 +22,8 @@ use bytes::Bytes;
 use http_body_util::combinators::BoxBody;
 use http_body_util::Full;
 use hyper::{Request, Response, StatusCode};
-use hyper::server::conn::http2;
 use hyper_util::rt::TokioIo;
+use hyper_util::server::conn::auto;
 
 /// HTTP2 backend holding routing to repository backends
 #[derive(Clone)]
@@ -67,7 +67,6 @@ impl Http2Backend {
         }
     }
     /// Inject FastRoutes after construction (2‑pass wiring)
-
     pub(crate) fn set_fast_routes(&mut self, routes: FastRoutes) {
         self.fast_routes = Some(routes);
     }
@@ -138,7 +137,8 @@ impl Http2Backend {
                         return;
                     };
                     let io = TokioIo::new(tls_stream);
-                    let builder = http2::Builder::new(hyper_util::rt::TokioExecutor::new());
+                    // HTTP/2 for cargo, HTTP/1.1 for clients like pip and twine
+                    let builder = auto::Builder::new(hyper_util::rt::TokioExecutor::new());
 
                     if let Err(err) = builder
                         .serve_connection(io, {
@@ -173,14 +173,37 @@ impl Http2Backend {
         self: Arc<Self>,
         req: Request<Body>,
     ) -> Result<Response<BoxBody<Bytes, std::convert::Infallible>>, hyper::Error> {
-        let path = req.uri().path().to_string();
+        let method = req.method().as_str().to_string();
+        let is_head = req.method() == hyper::Method::HEAD;
+        let headers: Vec<(String, String)> = req
+            .headers()
+            .iter()
+            .filter_map(|(k, v)| Some((k.as_str().to_string(), v.to_str().ok()?.to_string())))
+            .collect();
+        // Keep the query string, backends such as cargo search need it
+        let path = req
+            .uri()
+            .path_and_query()
+            .map(|pq| pq.as_str())
+            .unwrap_or("/")
+            .to_string();
         let suburl = path.trim_start_matches('/');
 
         let body_bytes = req.into_body().collect().await?.to_bytes();
         let body_vec = body_bytes.to_vec();
-        let repo_key = suburl.split('/').next().unwrap_or("");
+        let mut segments = suburl.split(['/', '?']);
+        let mut repo_key = segments.next().unwrap_or("");
+        // OCI clients put the API version first: /v2/<repo>/<image>/manifests/<ref>
+        if repo_key == "v2" {
+            repo_key = segments.next().unwrap_or("");
+        }
         println!("Repo key: {}", repo_key);
-        if let Some(repo) = self.fast_routes.as_ref().and_then(|routes| routes.lookup(repo_key)) {
+        let route = self.fast_routes.as_ref().and_then(|routes| match repo_key {
+            // The /v2/ version check names no repository
+            "" if suburl == "v2" || suburl == "v2/" => routes.first_of_format(ArtifactFormat::Oci),
+            _ => routes.lookup(repo_key),
+        });
+        if let Some(repo) = route {
             println!("routing to repo.name={}", repo.name());
 
 
@@ -191,7 +214,7 @@ impl Http2Backend {
             let repo_clone = Arc::clone(repo);
 
             let result = tokio::task::spawn_blocking(move || {
-                repo_clone.handle_http2_request(&suburl_owned, &body_owned)
+                repo_clone.handle_http2_request(&method, &suburl_owned, &headers, &body_owned)
             })
                 .await
                 .unwrap();
@@ -203,6 +226,11 @@ impl Http2Backend {
                     for (k, v) in headers {
                         response = response.header(k, v);
                     }
+                    // HEAD answers carry the length of the body they leave out
+                    if is_head {
+                        response = response.header("content-length", data.len());
+                        return Ok(response.body(Full::new(Bytes::new()).boxed()).unwrap());
+                    }
                     Ok(response.body(Full::new(Bytes::from(data)).boxed()).unwrap())
                 }
                 Err(_) => Ok(Response::builder()
@@ -252,10 +280,9 @@ fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>> {
 use rustls_pemfile::{read_all, Item};
 use tokio::task::JoinHandle;
 use tokio_rustls::rustls::pki_types::{PrivatePkcs8KeyDer, PrivateSec1KeyDer};
-use holger_traits::RepositoryBackendTrait;
 use crate::exposed::ExposedEndpoint;
 use crate::exposed::fast_routes::FastRoutes;
-use crate::Repository;
+use holger_traits::ArtifactFormat;
 
 fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
     let file = File::open(path)?;
@@ -283,12 +310,3 @@ fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>> {
 
     Err(anyhow::anyhow!("no private key found"))
 }
-
-pub(crate) fn parse_ip_port(url: &str) -> (String, u16) {
-    let clean = url.trim_end_matches('/');
-    let without_scheme = clean.split("://").nth(1).unwrap_or(clean);
-    let mut parts = without_scheme.split(':');
-    let ip = parts.next().unwrap_or("127.0.0.1").to_string();
-    let port = parts.next().and_then(|p| p.parse().ok()).unwrap_or(443);
-    (ip, port)
-}
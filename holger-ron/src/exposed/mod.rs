@@ -1,11 +1,4 @@
-use async_trait::async_trait;
-use bytes::Bytes;
-use hyper::{body::Incoming as Body, Request, Response};
-use http_body_util::combinators::BoxBody;
-
-use std::any::Any;
 use std::sync::Arc;
-use derivative::Derivative;
 use serde::{Deserialize, Serialize};
 use fast_routes::FastRoutes;
 use crate::exposed::http2_backend::Http2Backend;
@@ -19,6 +12,9 @@ pub mod fast_routes;
 pub struct ExposedEndpoint {
     pub ron_name: String,
     pub ron_url: String, // Parsed internally to ip/port
+    /// Public URL clients use when Holger sits behind a reverse proxy
+    #[serde(default)]
+    pub ron_external_url: Option<String>,
     pub ron_cert: String,
     pub ron_key: String,
     #[serde(skip_serializing, skip_deserializing, default)]
@@ -33,9 +29,16 @@ pub struct ExposedEndpoint {
 }
 
 impl ExposedEndpoint {
+    /// Base URL handed out to clients: the external override if set, else `ron_url`.
+    pub fn public_url(&self) -> &str {
+        self.ron_external_url
+            .as_deref()
+            .unwrap_or(&self.ron_url)
+            .trim_end_matches('/')
+    }
 
     pub fn backend_from_config(&mut self) -> anyhow::Result<()> {
-        let mut backend = Http2Backend::backend_from_config(self)?;
+        let backend = Http2Backend::backend_from_config(self)?;
 
         self.backend_http2 = Arc::new(backend);
         Ok(())
@@ -4,14 +4,12 @@ use serde::{Deserialize, Serialize};
 //use hyper::body::{BoxBody, Bytes};
 
 
-use http_body_util::BodyExt;
 use std::{
     fs::File,
     io::BufReader,
     path::Path,
 };
 
-use anyhow::Context;
 use anyhow::Result;
 use ron::de::from_reader;
 
@@ -77,6 +75,12 @@ pub fn wire_holger(holger: &mut Holger) -> Result<()> {
         }
     }
 
+    // ========================= PASS 2a: Instantiate repository backends =========================
+    // Backends learn their storage path and public URL through the IO wired above.
+    for repo in &mut holger.repositories {
+        repo.backend_from_config()?;
+    }
+
     // ========================= PASS 2b: Wire reverse links =========================
     for exp in &mut holger.exposed_endpoints {
         for repo in &holger.repositories {
@@ -219,9 +223,7 @@ impl Holger {
         for se in &mut self.storage_endpoints {
             se.backend_from_config()?;
         }
-        for repo in &mut self.repositories {
-            repo.backend_from_config()?;
-        }
+        // Repository backends need wired IO and are instantiated by wire_holger
         Ok(())
     }
 }
@@ -1,8 +1,14 @@
-mod types;
-
+use std::path::PathBuf;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use holger_rust_repository::RustRepo;
+use holger_go_file_repository::GoRepoFile;
+use holger_maven_file_repository::MavenRepoFile;
+use holger_npm_file_repository::NpmRepoFile;
+use holger_oci_file_repository::OciRepoFile;
+use holger_pip_file_repository::PipRepoFile;
+use holger_raw_file_repository::RawRepoFile;
+use holger_rust_file_repository::RustRepoFile;
+use holger_rust_znippy_repository::RustRepoZnippy;
 use holger_traits::RepositoryBackendTrait;
 use crate::{ExposedEndpoint, StorageEndpoint};
 
@@ -25,16 +31,99 @@ pub struct Repository {
     pub wired_upstreams: Vec<*const Repository>, // or &Repository pinned after build
 }
 impl Repository {
+    /// Instantiate the backend. Must run after `wire_holger` has resolved the IO pointers.
     pub fn backend_from_config(&mut self) -> anyhow::Result<()> {
-        match self.ron_repo_type.as_str() {
-            "rust" => {
-                self.backend_repository = Some(Arc::new(RustRepo {
-                    name: self.ron_name.clone(),
-                    artifacts: vec![],
-                }));
+        let io = self
+            .ron_out
+            .as_ref()
+            .or(self.ron_in.as_ref())
+            .ok_or_else(|| anyhow::anyhow!("Repository {} has neither ron_in nor ron_out", self.ron_name))?;
+        let storage = io
+            .storage()
+            .ok_or_else(|| anyhow::anyhow!("Repository {} storage endpoint not wired", self.ron_name))?;
+        let exposed = io
+            .exposed()
+            .ok_or_else(|| anyhow::anyhow!("Repository {} exposed endpoint not wired", self.ron_name))?;
+        let public_url = format!("{}/{}", exposed.public_url(), self.ron_name);
+
+        match (self.ron_repo_type.as_str(), storage.ron_storage_type.as_str()) {
+            ("rust", "znippy") => {
+                self.backend_repository = Some(Arc::new(RustRepoZnippy::open(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?));
+                Ok(())
+            }
+            ("rust", _) => {
+                let mut repo = RustRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?;
+                // Only repositories wired with an input accept `cargo publish`
+                repo.writable = self.ron_in.is_some();
+                self.backend_repository = Some(Arc::new(repo));
+                Ok(())
+            }
+            ("pip", _) => {
+                let mut repo = PipRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?;
+                // Only repositories wired with an input accept `twine upload`
+                repo.writable = self.ron_in.is_some();
+                self.backend_repository = Some(Arc::new(repo));
                 Ok(())
             }
-            other => anyhow::bail!("Unsupported repository type: {}", other),
+            ("maven3", _) => {
+                let mut repo = MavenRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?;
+                // Only repositories wired with an input accept `mvn deploy`
+                repo.writable = self.ron_in.is_some();
+                self.backend_repository = Some(Arc::new(repo));
+                Ok(())
+            }
+            ("go", _) => {
+                self.backend_repository = Some(Arc::new(GoRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?));
+                Ok(())
+            }
+            ("npm", _) => {
+                self.backend_repository = Some(Arc::new(NpmRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?));
+                Ok(())
+            }
+            ("oci", _) => {
+                self.backend_repository = Some(Arc::new(OciRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?));
+                Ok(())
+            }
+            ("raw", _) => {
+                let mut repo = RawRepoFile::new(
+                    self.ron_name.clone(),
+                    PathBuf::from(&storage.ron_path),
+                    public_url,
+                )?;
+                // Only repositories wired with an input accept PUT uploads
+                repo.writable = self.ron_in.is_some();
+                self.backend_repository = Some(Arc::new(repo));
+                Ok(())
+            }
+            (other, _) => anyhow::bail!("Unsupported repository type: {}", other),
         }
     }
 
@@ -54,3 +143,13 @@ pub struct RepositoryIO {
     #[serde(skip_serializing, skip_deserializing, default = "std::ptr::null")]
     pub wired_exposed: *const ExposedEndpoint,
 }
+
+impl RepositoryIO {
+    pub fn storage(&self) -> Option<&StorageEndpoint> {
+        unsafe { self.wired_storage.as_ref() }
+    }
+
+    pub fn exposed(&self) -> Option<&ExposedEndpoint> {
+        unsafe { self.wired_exposed.as_ref() }
+    }
+}
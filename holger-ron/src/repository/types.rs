@@ -1,8 +0,0 @@
-use std::any::Any;
-use std::collections::HashMap;
-use std::sync::Arc;
-use serde::{Deserialize, Serialize};
-
-use async_trait::async_trait;
-
-
@@ -2,14 +2,13 @@ mod raf_backend;
 
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use crate::exposed::http2_backend::Http2Backend;
 use crate::repository::Repository;
 use crate::storage::raf_backend::RafBackend;
 
 #[derive(Serialize, Deserialize)]
 pub struct StorageEndpoint {
     pub ron_name: String,
-    pub ron_storage_type: String, // "znippy" | "rocksdb"
+    pub ron_storage_type: String, // "znippy" | "rocksdb" | "file"
     pub ron_path: String,
 
     #[serde(skip_deserializing, skip_serializing, default)]
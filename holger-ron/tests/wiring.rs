@@ -0,0 +1,55 @@
+use holger_ron::{wire_holger, Holger};
+
+fn holger_from_ron(storage_path: &str) -> Holger {
+    let config = format!(
+        r#"(
+    exposed_endpoints: [
+        (
+            ron_name: "prod",
+            ron_url: "https://127.0.0.1:8443",
+            ron_external_url: Some("https://holger.example.com/"),
+            ron_cert: "tests/certs/cert.pem",
+            ron_key: "tests/certs/key.pem",
+        ),
+    ],
+    storage_endpoints: [
+        (
+            ron_name: "artifact-prod",
+            ron_storage_type: "file",
+            ron_path: "{storage_path}",
+        ),
+    ],
+    repositories: [
+        (
+            ron_name: "rust-prod",
+            ron_repo_type: "rust",
+            ron_upstreams: [],
+            ron_in: None,
+            ron_out: Some((
+                ron_storage_endpoint: "artifact-prod",
+                ron_exposed_endpoint: "prod",
+            )),
+        ),
+    ],
+)"#
+    );
+    ron::from_str(&config).unwrap()
+}
+
+#[test]
+fn rust_config_json_uses_exposed_endpoint_url() {
+    let storage = tempfile::tempdir().unwrap();
+    let mut holger = holger_from_ron(storage.path().to_str().unwrap());
+    holger.instantiate_backends().unwrap();
+    wire_holger(&mut holger).unwrap();
+
+    let backend = holger.repositories[0].backend_repository.clone().unwrap();
+    let (status, _, body) = backend
+        .handle_http2_request("GET", "/rust-prod/index/config.json", &[], b"")
+        .unwrap();
+    assert_eq!(status, 200);
+
+    let body = String::from_utf8(body).unwrap();
+    assert!(body.contains(r#""dl":"https://holger.example.com/rust-prod/crates/{crate}/{version}/download""#));
+    assert!(body.contains(r#""api":"https://holger.example.com/rust-prod""#));
+}
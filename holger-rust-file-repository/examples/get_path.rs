@@ -1,15 +1,15 @@
-use holger_rust_file_repository::RustRepo;
+use holger_rust_file_repository::RustRepoFile;
 
 type RPath<'a> = (&'a str, &'a str, &'a str);
 
 fn main() {
-    //let repo = RustRepo::new("kalle".to_string());
-    println!("path: {:?}", RustRepo::sparse_path("a"));
-    println!("path: {:?}", RustRepo::sparse_path("ab"));
-    println!("path: {:?}", RustRepo::sparse_path("abc"));
-    println!("path: {:?}", RustRepo::sparse_path("tokio"));
+    //let repo = RustRepoFile::new("kalle".to_string(), "/var/lib/holger/kalle".into(), "https://127.0.0.1:8443/kalle".into());
+    println!("path: {:?}", RustRepoFile::sparse_path("a"));
+    println!("path: {:?}", RustRepoFile::sparse_path("ab"));
+    println!("path: {:?}", RustRepoFile::sparse_path("abc"));
+    println!("path: {:?}", RustRepoFile::sparse_path("tokio"));
 
-    let tuple_path: RPath = RustRepo::sparse_path("tokio").into();
+    let tuple_path: RPath = RustRepoFile::sparse_path("tokio").into();
 
     println!("Tuple: {:?}", tuple_path);
 }
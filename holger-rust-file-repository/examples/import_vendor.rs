@@ -0,0 +1,36 @@
+use holger_rust_file_repository::RustRepoFile;
+
+/// cargo run --example import_vendor -- /var/lib/holger/rust-prod /airgap/rust/vendor
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let (Some(root), Some(vendor_dir)) = (args.next(), args.next()) else {
+        anyhow::bail!("usage: import_vendor <repository root> <vendor dir>");
+    };
+    let repo = RustRepoFile::new(
+        "rust-prod".to_string(),
+        root.into(),
+        "https://127.0.0.1:8443/rust-prod".into(),
+    )?;
+    let report = repo.import_vendor(vendor_dir.as_ref())?;
+
+    for id in &report.imported {
+        println!("imported {}@{}", id.name, id.version);
+    }
+    for id in &report.existing {
+        println!("already stored {}@{}", id.name, id.version);
+    }
+    for (dir, reason) in &report.rejected {
+        println!("rejected {}: {}", dir.display(), reason);
+    }
+    for (dir, reason) in &report.unreproducible {
+        println!("unreproducible {}: {}", dir.display(), reason);
+    }
+    println!(
+        "{} imported, {} already stored, {} rejected, {} unreproducible",
+        report.imported.len(),
+        report.existing.len(),
+        report.rejected.len(),
+        report.unreproducible.len()
+    );
+    Ok(())
+}
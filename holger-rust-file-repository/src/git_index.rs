@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sha1::{Digest, Sha1};
+
+use crate::index::CrateIndex;
+use crate::RustRepoFile;
+
+/// Fixed identity and timestamp, so the commit id depends only on the index
+/// content: the stored crates and `config.json`, which carries the public URL.
+const COMMIT_SIGNATURE: &str = "Holger <holger@localhost> 0 +0000";
+const BRANCH: &str = "refs/heads/master";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ObjectKind {
+    Commit = 1,
+    Tree = 2,
+    Blob = 3,
+}
+
+impl ObjectKind {
+    fn name(self) -> &'static str {
+        match self {
+            ObjectKind::Commit => "commit",
+            ObjectKind::Tree => "tree",
+            ObjectKind::Blob => "blob",
+        }
+    }
+}
+
+type ObjectId = [u8; 20];
+
+/// A single-commit git repository holding a Cargo index snapshot.
+pub struct GitIndexSnapshot {
+    pub head: ObjectId,
+    objects: Vec<(ObjectKind, ObjectId, Vec<u8>)>,
+}
+
+/// Files of a git index: `config.json` plus one file per crate at its index path.
+pub fn index_files(
+    index: &CrateIndex,
+    public_url: &str,
+) -> anyhow::Result<BTreeMap<String, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    files.insert(
+        "config.json".to_string(),
+        crate::config_json(public_url).into_bytes(),
+    );
+    for versions in index.crates() {
+        let Some(first) = versions.first() else {
+            continue;
+        };
+        let name = &first.entry.name;
+        let Some(path) = RustRepoFile::sparse_index_path(name) else {
+            continue;
+        };
+        if let Some(lines) = index.index_lines(name)? {
+            files.insert(path, lines.into_bytes());
+        }
+    }
+    Ok(files)
+}
+
+impl GitIndexSnapshot {
+    /// Build blobs, trees and a root commit for `files` (slash separated paths).
+    pub fn from_files(files: &BTreeMap<String, Vec<u8>>) -> Self {
+        let mut snapshot = GitIndexSnapshot {
+            head: [0; 20],
+            objects: Vec::new(),
+        };
+        let root = snapshot.write_tree(files, "");
+        let commit = format!(
+            "tree {}\nauthor {COMMIT_SIGNATURE}\ncommitter {COMMIT_SIGNATURE}\n\nHolger index snapshot\n",
+            hex::encode(root)
+        );
+        snapshot.head = snapshot.add(ObjectKind::Commit, commit.into_bytes());
+        snapshot
+    }
+
+    pub fn head_hex(&self) -> String {
+        hex::encode(self.head)
+    }
+
+    fn add(&mut self, kind: ObjectKind, data: Vec<u8>) -> ObjectId {
+        let mut hasher = Sha1::new();
+        hasher.update(format!("{} {}\0", kind.name(), data.len()));
+        hasher.update(&data);
+        let id: ObjectId = hasher.finalize().into();
+        if !self.objects.iter().any(|(_, existing, _)| *existing == id) {
+            self.objects.push((kind, id, data));
+        }
+        id
+    }
+
+    /// Write the tree for the directory `prefix` (empty for the root).
+    fn write_tree(&mut self, files: &BTreeMap<String, Vec<u8>>, prefix: &str) -> ObjectId {
+        // (sort key, mode, name, id); git orders directories as if named `name/`
+        let mut entries: Vec<(String, &str, String, ObjectId)> = Vec::new();
+        let mut subdirs: Vec<String> = Vec::new();
+        for (path, data) in files.range(prefix.to_string()..) {
+            let Some(rest) = path.strip_prefix(prefix) else {
+                break;
+            };
+            match rest.split_once('/') {
+                Some((dir, _)) => {
+                    if subdirs.last().map(String::as_str) != Some(dir) {
+                        subdirs.push(dir.to_string());
+                    }
+                }
+                None => {
+                    let id = self.add(ObjectKind::Blob, data.clone());
+                    entries.push((rest.to_string(), "100644", rest.to_string(), id));
+                }
+            }
+        }
+        for dir in subdirs {
+            let id = self.write_tree(files, &format!("{prefix}{dir}/"));
+            entries.push((format!("{dir}/"), "40000", dir, id));
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut tree = Vec::new();
+        for (_, mode, name, id) in entries {
+            tree.extend_from_slice(format!("{mode} {name}\0").as_bytes());
+            tree.extend_from_slice(&id);
+        }
+        self.add(ObjectKind::Tree, tree)
+    }
+
+    /// `info/refs?service=git-upload-pack` advertisement (protocol v0).
+    pub fn advertise_refs(&self) -> Vec<u8> {
+        let head = self.head_hex();
+        let mut out = pkt_line(b"# service=git-upload-pack\n");
+        out.extend_from_slice(b"0000");
+        out.extend(pkt_line(
+            format!("{head} HEAD\0symref=HEAD:{BRANCH} agent=holger\n").as_bytes(),
+        ));
+        out.extend(pkt_line(format!("{head} {BRANCH}\n").as_bytes()));
+        out.extend_from_slice(b"0000");
+        out
+    }
+
+    /// Answer a `git-upload-pack` request. Only the snapshot head can be
+    /// wanted; the whole snapshot is sent once the client says `done`.
+    pub fn upload_pack(&self, request: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut wants = Vec::new();
+        let mut haves = Vec::new();
+        let mut done = false;
+        for line in parse_pkt_lines(request)? {
+            let line = String::from_utf8_lossy(line);
+            let line = line.trim_end();
+            if let Some(rest) = line.strip_prefix("want ") {
+                wants.push(rest.split(' ').next().unwrap_or_default().to_string());
+            } else if let Some(rest) = line.strip_prefix("have ") {
+                haves.push(rest.to_string());
+            } else if line == "done" {
+                done = true;
+            }
+        }
+
+        let head = self.head_hex();
+        if let Some(other) = wants.iter().find(|w| **w != head) {
+            return Ok(pkt_line(
+                format!("ERR upload-pack: not our ref {other}\n").as_bytes(),
+            ));
+        }
+        // Negotiation round without `done`: nothing in common with a single-commit history
+        if !done && !haves.is_empty() {
+            return Ok(pkt_line(b"NAK\n"));
+        }
+        let mut out = if haves.contains(&head) {
+            pkt_line(format!("ACK {head}\n").as_bytes())
+        } else {
+            pkt_line(b"NAK\n")
+        };
+        out.extend(self.pack()?);
+        Ok(out)
+    }
+
+    /// Version 2 packfile holding every object, undeltified.
+    pub fn pack(&self) -> anyhow::Result<Vec<u8>> {
+        let mut pack = Vec::new();
+        pack.extend_from_slice(b"PACK");
+        pack.extend_from_slice(&2u32.to_be_bytes());
+        pack.extend_from_slice(&(self.objects.len() as u32).to_be_bytes());
+        for (kind, _, data) in &self.objects {
+            // Header: type in bits 4-6 of the first byte, size as a little-endian varint
+            let mut size = data.len();
+            let mut byte = ((*kind as u8) << 4) | (size & 0x0f) as u8;
+            size >>= 4;
+            while size > 0 {
+                pack.push(byte | 0x80);
+                byte = (size & 0x7f) as u8;
+                size >>= 7;
+            }
+            pack.push(byte);
+
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(data)?;
+            pack.extend(encoder.finish()?);
+        }
+        let trailer: [u8; 20] = Sha1::digest(&pack).into();
+        pack.extend_from_slice(&trailer);
+        Ok(pack)
+    }
+}
+
+fn pkt_line(data: &[u8]) -> Vec<u8> {
+    let mut out = format!("{:04x}", data.len() + 4).into_bytes();
+    out.extend_from_slice(data);
+    out
+}
+
+/// Split a pkt-line stream into payloads, skipping flush packets.
+fn parse_pkt_lines(mut data: &[u8]) -> anyhow::Result<Vec<&[u8]>> {
+    let mut lines = Vec::new();
+    while data.len() >= 4 {
+        let len = usize::from_str_radix(std::str::from_utf8(&data[..4])?, 16)?;
+        if len == 0 {
+            data = &data[4..];
+            continue;
+        }
+        if len < 4 || len > data.len() {
+            anyhow::bail!("malformed pkt-line length {len}");
+        }
+        lines.push(&data[4..len]);
+        data = &data[len..];
+    }
+    Ok(lines)
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// One line of a Cargo sparse index file (index format v2).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub vers: String,
+    pub deps: Vec<IndexDependency>,
+    pub cksum: String,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub yanked: bool,
+    pub links: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub features2: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_version: Option<String>,
+}
+
+/// Dependency as published in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexDependency {
+    pub name: String,
+    pub req: String,
+    pub features: Vec<String>,
+    pub optional: bool,
+    pub default_features: bool,
+    pub target: Option<String>,
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+}
+
+/// A stored `.crate` file together with its index entry.
+#[derive(Debug, Clone)]
+pub struct StoredCrate {
+    pub entry: IndexEntry,
+    pub path: PathBuf,
+    // Manifest fields used by search, not part of the index line
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+}
+
+/// In-memory sparse index, keyed by lowercase crate name.
+#[derive(Debug, Default)]
+pub struct CrateIndex {
+    crates: BTreeMap<String, Vec<StoredCrate>>,
+}
+
+impl CrateIndex {
+    /// Add a stored crate, keeping versions in semver order.
+    pub fn insert(&mut self, stored: StoredCrate) {
+        let versions = self
+            .crates
+            .entry(stored.entry.name.to_lowercase())
+            .or_default();
+        versions.retain(|c| c.entry.vers != stored.entry.vers);
+        versions.push(stored);
+        versions.sort_by(|a, b| version_cmp(&a.entry.vers, &b.entry.vers));
+    }
+
+    /// All versions of a crate, looked up case-insensitively.
+    pub fn versions(&self, crate_name: &str) -> Option<&[StoredCrate]> {
+        self.crates
+            .get(&crate_name.to_lowercase())
+            .map(|v| v.as_slice())
+    }
+
+    pub fn get(&self, crate_name: &str, version: &str) -> Option<&StoredCrate> {
+        self.versions(crate_name)?
+            .iter()
+            .find(|c| c.entry.vers == version)
+    }
+
+    /// Flip the yanked flag of one version; false if the version is unknown.
+    pub fn set_yanked(&mut self, crate_name: &str, version: &str, yanked: bool) -> bool {
+        let Some(versions) = self.crates.get_mut(&crate_name.to_lowercase()) else {
+            return false;
+        };
+        match versions.iter_mut().find(|c| c.entry.vers == version) {
+            Some(stored) => {
+                stored.entry.yanked = yanked;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Every crate's versions, in name order.
+    pub fn crates(&self) -> impl Iterator<Item = &[StoredCrate]> {
+        self.crates.values().map(|v| v.as_slice())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StoredCrate> {
+        self.crates.values().flatten()
+    }
+
+    /// Render the index file for a crate: one JSON object per line.
+    pub fn index_lines(&self, crate_name: &str) -> anyhow::Result<Option<String>> {
+        let Some(versions) = self.versions(crate_name) else {
+            return Ok(None);
+        };
+        let mut out = String::new();
+        for stored in versions {
+            out.push_str(&serde_json::to_string(&stored.entry)?);
+            out.push('\n');
+        }
+        Ok(Some(out))
+    }
+}
+
+/// Split a `.crate` file name (`<name>-<version>.crate`) into name and version.
+///
+/// Crate names may contain `-` and versions may carry `-pre` suffixes, so the
+/// first split point whose remainder is a valid semver version wins.
+pub fn split_crate_file_name(file_name: &str) -> Option<(&str, &str)> {
+    let stem = file_name.strip_suffix(".crate")?;
+    stem.match_indices('-').find_map(|(i, _)| {
+        let (name, vers) = (&stem[..i], &stem[i + 1..]);
+        (!name.is_empty() && semver::Version::parse(vers).is_ok()).then_some((name, vers))
+    })
+}
+
+fn version_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    match (semver::Version::parse(a), semver::Version::parse(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.cmp(b),
+    }
+}
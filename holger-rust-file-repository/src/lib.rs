@@ -1,13 +1,33 @@
-use anyhow::anyhow;
-use holger_traits::{ArtifactFormat, ArtifactId, RepositoryBackendTrait};
-use sha2::{Digest, Sha256};
-use std::any::Any;
+use anyhow::bail;
+use holger_traits::util::{find_files, write_atomic};
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use std::path::PathBuf;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-/// Minimal RustRepo example
+pub mod git_index;
+pub mod index;
+pub mod manifest;
+pub mod publish;
+pub mod search;
+pub mod vendor;
+pub mod yank;
+
+use git_index::{index_files, GitIndexSnapshot};
+use index::{CrateIndex, StoredCrate};
+use manifest::index_entry_from_crate;
+use publish::{cargo_error, publish_ok, PublishRequest};
+use search::{search, SearchQuery};
+use yank::{append_event, is_yanked, read_events, requester, YankAction, YankEvent};
+
+/// Rust repository backed by `.crate` files in a directory tree
 pub struct RustRepoFile {
     pub name: String,
     //    pub format: ArtifactFormat,
     pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub root: PathBuf,
+    pub public_url: String, // e.g. https://10.101.1.9:8443/rust-prod
+    pub writable: bool,     // accepts `cargo publish`, set for repos wired with ron_in
+    pub index: RwLock<CrateIndex>,
 }
 
 #[derive(Debug, PartialEq, PartialOrd)]
@@ -24,14 +44,120 @@ impl<'a> From<RepoPath<'a>> for (&'a str, &'a str, &'a str) {
 }
 
 impl RustRepoFile {
-    pub fn new(name: String) -> Self {
-        RustRepoFile {
-            // initialize fields if any; if none, leave empty struct
-            // Example: name
-            // name,
+    /// Open a repository rooted at `root`, indexing every `.crate` file below it.
+    /// `public_url` is the repository's base URL as clients see it.
+    pub fn new(name: String, root: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let mut repo = RustRepoFile {
             name,
             artifacts: vec![],
+            root,
+            public_url,
+            writable: false,
+            index: RwLock::new(CrateIndex::default()),
+        };
+        repo.reload()?;
+        Ok(repo)
+    }
+
+    /// Rebuild the in-memory index from the `.crate` files on disk.
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        let mut index = CrateIndex::default();
+        for (rel, path) in find_files(&self.root)? {
+            if !rel.ends_with(".crate") {
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            match index_entry_from_crate(&data) {
+                Ok((manifest, mut entry)) => {
+                    entry.yanked = is_yanked(&read_events(&path)?);
+                    index.insert(StoredCrate::from_manifest(manifest, entry, path))
+                }
+                Err(e) => println!("Skipping unreadable crate {}: {:#}", path.display(), e),
+            }
         }
+        self.artifacts = index
+            .iter()
+            .map(|c| ArtifactId {
+                namespace: None,
+                name: c.entry.name.clone(),
+                version: c.entry.vers.clone(),
+            })
+            .collect();
+        self.index = RwLock::new(index);
+        Ok(())
+    }
+
+    pub fn index(&self) -> RwLockReadGuard<'_, CrateIndex> {
+        self.index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn index_mut(&self) -> RwLockWriteGuard<'_, CrateIndex> {
+        self.index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a yank or unyank event and flip the served `yanked` flag.
+    /// The stored `.crate` is left untouched. Returns false for unknown versions.
+    pub fn set_yanked(
+        &self,
+        crate_name: &str,
+        version: &str,
+        action: YankAction,
+        by: String,
+    ) -> anyhow::Result<bool> {
+        let mut index = self.index_mut();
+        let Some(path) = index.get(crate_name, version).map(|c| c.path.clone()) else {
+            return Ok(false);
+        };
+        append_event(&path, &YankEvent::now(action, by))?;
+        Ok(index.set_yanked(crate_name, version, action == YankAction::Yank))
+    }
+
+    /// Git view of the current index, served to toolchains without sparse support.
+    pub fn git_snapshot(&self) -> anyhow::Result<GitIndexSnapshot> {
+        let files = index_files(&self.index(), &self.public_url)?;
+        Ok(GitIndexSnapshot::from_files(&files))
+    }
+
+    /// Validate and store a `.crate`, then add it to the index. Existing
+    /// versions are never overwritten.
+    fn store(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
+        let (manifest, entry) = index_entry_from_crate(data)?;
+        if entry.name != id.name || entry.vers != id.version {
+            bail!(
+                "crate manifest declares {}@{}, expected {}@{}",
+                entry.name,
+                entry.vers,
+                id.name,
+                id.version
+            );
+        }
+
+        // Hold the write lock across the existence check, the write and the index update
+        let mut index = self.index_mut();
+        if let Some(existing) = index.versions(&id.name).and_then(|v| v.first()) {
+            if existing.entry.name != id.name {
+                bail!(
+                    "crate name `{}` conflicts with existing crate `{}`",
+                    id.name,
+                    existing.entry.name
+                );
+            }
+        }
+        if index.get(&id.name, &id.version).is_some() {
+            bail!("crate version `{}@{}` already exists", id.name, id.version);
+        }
+
+        let path = self.crate_file_path(&id.name, &id.version);
+        write_atomic(&path, data)?;
+        index.insert(StoredCrate::from_manifest(manifest, entry, path));
+        Ok(())
+    }
+
+    /// Where a published crate is stored below the repository root.
+    pub fn crate_file_path(&self, name: &str, version: &str) -> PathBuf {
+        self.root
+            .join(name.to_lowercase())
+            .join(format!("{name}-{version}.crate"))
     }
 
     /// Convert crate name to Cargo sparse 3-part path (p1, p2, name)
@@ -60,6 +186,19 @@ impl RustRepoFile {
         }
     }
 
+    /// Path of a crate's file inside the index, as cargo requests it
+    /// (`1/a`, `2/ab`, `3/a/abc`, `ab/cd/abcd`); `None` for an invalid crate name.
+    pub fn sparse_index_path(crate_name: &str) -> Option<String> {
+        publish::validate_crate_name(crate_name).ok()?;
+        let name = crate_name.to_lowercase();
+        Some(match name.len() {
+            1 => format!("1/{name}"),
+            2 => format!("2/{name}"),
+            3 => format!("3/{}/{name}", &name[0..1]),
+            _ => format!("{}/{}/{name}", &name[0..2], &name[2..4]),
+        })
+    }
+
     /// Reverse matcher: takes a sparse path slice ["xx","yy","name"] -> crate name
     #[inline]
     pub fn sparse_crate_from_parts<'a>(parts: &'a [&'a str]) -> Option<&'a str> {
@@ -72,7 +211,7 @@ impl RustRepoFile {
     #[inline]
     pub fn crate_sha256_hex(data: &[u8]) -> String {
         use hex::encode;
-        use sha2::{Digest, Sha256}; // Add `hex = "0.4"` to Cargo.toml
+        use sha2::{Digest, Sha256};
 
         let mut hasher = Sha256::new();
         hasher.update(data);
@@ -81,6 +220,19 @@ impl RustRepoFile {
     }
 }
 
+/// Sparse registry `config.json` for a repository served at `public_url`.
+///
+/// `dl` uses the `{crate}`/`{version}` markers so the download route stays under
+/// the repository prefix; `api` is the base cargo appends `/api/v1/...` to.
+pub fn config_json(public_url: &str) -> String {
+    let base = public_url.trim_end_matches('/');
+    serde_json::json!({
+        "dl": format!("{base}/crates/{{crate}}/{{version}}/download"),
+        "api": base,
+    })
+    .to_string()
+}
+
 impl RepositoryBackendTrait for RustRepoFile {
     fn name(&self) -> &str {
         &self.name
@@ -88,67 +240,197 @@ impl RepositoryBackendTrait for RustRepoFile {
 
     fn handle_http2_request(
         &self,
+        method: &str,
         suburl: &str,
+        headers: &[(String, String)],
         body: &[u8],
-    ) -> anyhow::Result<(u16, Vec<(String, String)>, Vec<u8>)> {
-        let _ = body;
+    ) -> anyhow::Result<Http2Response> {
         println!("Rust repo handle_http2_request.suburl={}", suburl);
 
-        let parts: Vec<&str> = suburl.trim_start_matches('/').split('/').collect();
+        let (path, query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
 
         match parts.as_slice() {
             // Sparse root config.json → /rust-prod/index/config.json
             [repo, "index", "config.json"] if *repo == self.name() => {
                 println!("Sparse config.json requested");
-                let json = format!(
-                    r#"{{
-                        "dl": "https://127.0.0.1:8443/{}/crates",
-                        "api": null
-                    }}"#,
-                    self.name()
-                );
-                return Ok((
+                let json = config_json(&self.public_url);
+                Ok((
                     200,
                     vec![("Content-Type".into(), "application/json".into())],
                     json.as_bytes().to_vec(),
-                ));
+                ))
             }
 
             // Sparse crate metadata → /rust-prod/index/se/rd/serde
-            [repo, "index", p1, p2, crate_name] if *repo == self.name() => {
-                if let Some(actual_name) = RustRepoFile::sparse_crate_from_parts(&[p1, p2, crate_name])
-                {
-                    println!(
-                        "Sparse crate metadata request: {}/{}/{}",
-                        p1, p2, actual_name
-                    );
-
-                    let fake_crate_data = b"FAKE_CRATE_CONTENT";
-                    let checksum_hex = RustRepoFile::crate_sha256_hex(fake_crate_data);
-
-                    let json = format!(
-                        r#"[{{"vers":"1.0.0","deps":[],"cksum":"{}"}}]"#,
-                        checksum_hex
-                    );
+            [repo, "index", prefix @ .., crate_name]
+                if *repo == self.name() && !prefix.is_empty() =>
+            {
+                let requested = format!("{}/{}", prefix.join("/"), crate_name);
+                if RustRepoFile::sparse_index_path(crate_name).as_ref() != Some(&requested) {
+                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                }
+                println!("Sparse crate metadata request: {}", requested);
 
-                    return Ok((
+                match self.index().index_lines(crate_name)? {
+                    Some(lines) => Ok((
                         200,
-                        vec![("Content-Type".into(), "application/json".into())],
-                        json.into_bytes(),
+                        vec![("Content-Type".into(), "text/plain".into())],
+                        lines.into_bytes(),
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            // Crate download → /rust-prod/crates/<crate>/<version>/download
+            [repo, "crates", crate_name, version, "download"] if *repo == self.name() => {
+                println!("Download request: crate={} version={}", crate_name, version);
+                let id = ArtifactId {
+                    namespace: None,
+                    name: crate_name.to_string(),
+                    version: version.to_string(),
+                };
+                match self.fetch(&id)? {
+                    Some(data) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "application/octet-stream".into())],
+                        data,
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            // Publish → /rust-prod/api/v1/crates/new
+            [repo, "api", "v1", "crates", "new"] if *repo == self.name() => {
+                if method != "PUT" {
+                    return Ok(cargo_error(405, "publishing requires PUT"));
+                }
+                if !self.is_writable() {
+                    return Ok(cargo_error(
+                        403,
+                        &format!("repository `{}` is read-only", self.name),
+                    ));
+                }
+                let request = match PublishRequest::parse(body) {
+                    Ok(request) => request,
+                    Err(e) => return Ok(cargo_error(400, &format!("{e:#}"))),
+                };
+                let id = request.artifact_id();
+                println!("Publish request: crate={} version={}", id.name, id.version);
+                if self.index().get(&id.name, &id.version).is_some() {
+                    return Ok(cargo_error(
+                        409,
+                        &format!(
+                            "crate version `{}@{}` is already uploaded",
+                            id.name, id.version
+                        ),
+                    ));
+                }
+                match self.put(&id, request.crate_bytes) {
+                    Ok(()) => Ok(publish_ok()),
+                    Err(e) => Ok(cargo_error(400, &format!("{e:#}"))),
+                }
+            }
+
+            // Git index ref advertisement → /rust-prod/git-index/info/refs?service=git-upload-pack
+            [repo, "git-index", "info", "refs"] if *repo == self.name() => {
+                if !query.split('&').any(|p| p == "service=git-upload-pack") {
+                    return Ok((
+                        403,
+                        Vec::new(),
+                        b"Only git-upload-pack is supported".to_vec(),
                     ));
+                }
+                Ok((
+                    200,
+                    vec![
+                        (
+                            "Content-Type".into(),
+                            "application/x-git-upload-pack-advertisement".into(),
+                        ),
+                        ("Cache-Control".into(), "no-cache".into()),
+                    ],
+                    self.git_snapshot()?.advertise_refs(),
+                ))
+            }
+
+            // Git index fetch → POST /rust-prod/git-index/git-upload-pack
+            [repo, "git-index", "git-upload-pack"] if *repo == self.name() => {
+                if method != "POST" {
+                    return Ok((405, Vec::new(), b"Method not allowed".to_vec()));
+                }
+                let gzipped = headers.iter().any(|(k, v)| {
+                    k.eq_ignore_ascii_case("content-encoding") && v.eq_ignore_ascii_case("gzip")
+                });
+                let mut request = Vec::new();
+                if gzipped {
+                    std::io::Read::read_to_end(
+                        &mut flate2::read::GzDecoder::new(body),
+                        &mut request,
+                    )?;
                 } else {
-                    return Ok((404, Vec::new(), b"Not found".to_vec()));
+                    request.extend_from_slice(body);
                 }
+                Ok((
+                    200,
+                    vec![
+                        (
+                            "Content-Type".into(),
+                            "application/x-git-upload-pack-result".into(),
+                        ),
+                        ("Cache-Control".into(), "no-cache".into()),
+                    ],
+                    self.git_snapshot()?.upload_pack(&request)?,
+                ))
             }
 
-            // Crate download → /crates/<crate>/<version>/download
-            ["crates", crate_name, version, "download"] => {
-                println!("Download request: crate={} version={}", crate_name, version);
-                return Ok((
+            // Search → /rust-prod/api/v1/crates?q=serde&per_page=10
+            [repo, "api", "v1", "crates"] if *repo == self.name() => {
+                let query = SearchQuery::parse(query);
+                println!("Search request: q={} per_page={}", query.q, query.per_page);
+                let response = search(&self.index(), &query);
+                Ok((
                     200,
-                    vec![("Content-Type".into(), "application/octet-stream".into())],
-                    b"FAKE_CRATE_CONTENT".to_vec(),
-                ));
+                    vec![("Content-Type".into(), "application/json".into())],
+                    serde_json::to_vec(&response)?,
+                ))
+            }
+
+            // Yank → DELETE /rust-prod/api/v1/crates/<crate>/<version>/yank
+            // Unyank → PUT /rust-prod/api/v1/crates/<crate>/<version>/unyank
+            [repo, "api", "v1", "crates", crate_name, version, op @ ("yank" | "unyank")]
+                if *repo == self.name() =>
+            {
+                let (action, expected_method) = match *op {
+                    "yank" => (YankAction::Yank, "DELETE"),
+                    _ => (YankAction::Unyank, "PUT"),
+                };
+                if method != expected_method {
+                    return Ok(cargo_error(
+                        405,
+                        &format!("{op} requires {expected_method}"),
+                    ));
+                }
+                if !self.is_writable() {
+                    return Ok(cargo_error(
+                        403,
+                        &format!("repository `{}` is read-only", self.name),
+                    ));
+                }
+                let by = requester(headers);
+                println!("{op} request: crate={crate_name} version={version} by={by}");
+                if self.set_yanked(crate_name, version, action, by)? {
+                    Ok((
+                        200,
+                        vec![("Content-Type".into(), "application/json".into())],
+                        br#"{"ok":true}"#.to_vec(),
+                    ))
+                } else {
+                    Ok(cargo_error(
+                        404,
+                        &format!("crate version `{crate_name}@{version}` does not exist"),
+                    ))
+                }
             }
 
             _ => {
@@ -162,21 +444,72 @@ impl RepositoryBackendTrait for RustRepoFile {
     }
 
     fn is_writable(&self) -> bool {
-        todo!()
+        self.writable
     }
 
     fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
-        todo!()
+        let path = match self.index().get(&id.name, &id.version) {
+            Some(stored) => stored.path.clone(),
+            None => return Ok(None),
+        };
+        Ok(Some(std::fs::read(path)?))
     }
 
+    /// Store a new `.crate`. Stored crates are immutable, so existing versions are refused.
     fn put(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
-        todo!()
+        if !self.writable {
+            bail!("repository {} is read-only", self.name);
+        }
+        self.store(id, data)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use index::{split_crate_file_name, IndexEntry};
+    use std::collections::BTreeMap;
+    use std::path::Path;
+
+    /// Build a gzipped `.crate` tarball whose manifest is `[package]` plus `extra`.
+    pub(crate) fn crate_file(name: &str, vers: &str, extra: &str) -> Vec<u8> {
+        let manifest = format!("[package]\nname = \"{name}\"\nversion = \"{vers}\"\n{extra}");
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        for (file, data) in [
+            ("Cargo.toml", manifest.as_bytes()),
+            ("src/lib.rs", b"".as_slice()),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("{name}-{vers}/{file}"), data)
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn open_repo(root: &Path) -> RustRepoFile {
+        RustRepoFile::new(
+            "rust-prod".into(),
+            root.to_path_buf(),
+            "https://10.101.1.9:8443/rust-prod".into(),
+        )
+        .unwrap()
+    }
+
+    /// Encode a `cargo publish` body around `crate_bytes`.
+    fn publish_body(name: &str, vers: &str, crate_bytes: &[u8]) -> Vec<u8> {
+        let json = serde_json::json!({ "name": name, "vers": vers, "deps": [] }).to_string();
+        let mut body = Vec::new();
+        body.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        body.extend_from_slice(json.as_bytes());
+        body.extend_from_slice(&(crate_bytes.len() as u32).to_le_bytes());
+        body.extend_from_slice(crate_bytes);
+        body
+    }
 
     #[test]
     fn sparse_path_test_1() {
@@ -235,4 +568,686 @@ mod tests {
         let path: (&str, &str, &str) = RustRepoFile::sparse_path("abcd").into();
         assert_eq!(path, ("ab", "cd", "abcd"));
     }
+
+    #[test]
+    fn sparse_index_path_layout() {
+        let path = |name| RustRepoFile::sparse_index_path(name);
+        assert_eq!(path("a").as_deref(), Some("1/a"));
+        assert_eq!(path("ab").as_deref(), Some("2/ab"));
+        assert_eq!(path("abc").as_deref(), Some("3/a/abc"));
+        assert_eq!(path("Serde").as_deref(), Some("se/rd/serde"));
+        assert_eq!(path("éab"), None);
+        assert_eq!(path("aé"), None);
+        assert_eq!(path(""), None);
+    }
+
+    #[test]
+    fn split_crate_file_names() {
+        assert_eq!(
+            split_crate_file_name("serde-1.0.219.crate"),
+            Some(("serde", "1.0.219"))
+        );
+        assert_eq!(
+            split_crate_file_name("foo-2d-0.1.0.crate"),
+            Some(("foo-2d", "0.1.0"))
+        );
+        assert_eq!(
+            split_crate_file_name("tokio-1.0.0-beta.1.crate"),
+            Some(("tokio", "1.0.0-beta.1"))
+        );
+        assert_eq!(split_crate_file_name("serde.crate"), None);
+        assert_eq!(split_crate_file_name("serde-1.0.219.tar.gz"), None);
+    }
+
+    #[test]
+    fn sparse_index_lists_every_stored_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let (one_zero, one_one) = (
+            crate_file("foo", "1.0.0", ""),
+            crate_file("foo", "1.1.0", ""),
+        );
+        std::fs::create_dir_all(dir.path().join("foo")).unwrap();
+        std::fs::write(dir.path().join("foo/foo-1.1.0.crate"), &one_one).unwrap();
+        std::fs::write(dir.path().join("foo-1.0.0.crate"), &one_zero).unwrap();
+        // Hidden entries, such as leftovers of an interrupted write, are skipped
+        std::fs::create_dir_all(dir.path().join(".tmpA1b2/foo")).unwrap();
+        std::fs::write(
+            dir.path().join(".tmpA1b2/foo/foo-1.2.0.crate"),
+            crate_file("foo", "1.2.0", ""),
+        )
+        .unwrap();
+
+        let repo = open_repo(dir.path());
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/index/3/f/foo", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+
+        let lines: Vec<IndexEntry> = String::from_utf8(body)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].vers, "1.0.0");
+        assert_eq!(lines[0].cksum, RustRepoFile::crate_sha256_hex(&one_zero));
+        assert_eq!(lines[1].vers, "1.1.0");
+        assert_eq!(lines[1].cksum, RustRepoFile::crate_sha256_hex(&one_one));
+
+        let (status, _, _) = repo
+            .handle_http2_request("GET", "/rust-prod/index/3/f/bar", &[], b"")
+            .unwrap();
+        assert_eq!(status, 404);
+        let (status, _, _) = repo
+            .handle_http2_request("GET", "/rust-prod/index/fo/o/foo", &[], b"")
+            .unwrap();
+        assert_eq!(status, 404);
+        let (status, _, _) = repo
+            .handle_http2_request("GET", "/rust-prod/index/3/x/éab", &[], b"")
+            .unwrap();
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn download_serves_stored_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let one_zero = crate_file("foo", "1.0.0", "");
+        std::fs::write(dir.path().join("foo-1.0.0.crate"), &one_zero).unwrap();
+        let repo = open_repo(dir.path());
+
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/crates/foo/1.0.0/download", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, one_zero);
+        let cksum = repo
+            .index()
+            .get("foo", "1.0.0")
+            .unwrap()
+            .entry
+            .cksum
+            .clone();
+        assert_eq!(RustRepoFile::crate_sha256_hex(&body), cksum);
+
+        let (status, _, _) = repo
+            .handle_http2_request("GET", "/rust-prod/crates/foo/2.0.0/download", &[], b"")
+            .unwrap();
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn index_entry_from_embedded_manifest() {
+        let data = crate_file(
+            "foo",
+            "0.3.0",
+            r#"links = "foo-sys"
+rust-version = "1.70"
+
+[dependencies]
+serde = { version = "1.0", default-features = false, features = ["derive"] }
+rand_core = { version = "0.6", optional = true, package = "rand-core" }
+internal = { version = "2", registry-index = "https://holger.example/rust-dev/index" }
+local = { path = "../local" }
+
+[dev-dependencies]
+tempfile = "3"
+
+[build-dependencies.cc]
+version = "1.0"
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[features]
+default = ["std"]
+std = []
+rand = ["dep:rand_core"]
+serde-std = ["serde?/std"]
+"#,
+        );
+        let (_, entry) = manifest::index_entry_from_crate(&data).unwrap();
+
+        assert_eq!(entry.name, "foo");
+        assert_eq!(entry.vers, "0.3.0");
+        assert_eq!(entry.cksum, RustRepoFile::crate_sha256_hex(&data));
+        assert_eq!(entry.links.as_deref(), Some("foo-sys"));
+        assert_eq!(entry.rust_version.as_deref(), Some("1.70"));
+        assert!(!entry.yanked);
+
+        let dep = |name: &str| entry.deps.iter().find(|d| d.name == name).unwrap();
+        assert_eq!(entry.deps.len(), 6, "path-only dependency must be dropped");
+        assert!(!dep("serde").default_features);
+        assert_eq!(dep("serde").features, vec!["derive"]);
+        assert_eq!(dep("serde").kind, "normal");
+        assert!(dep("rand_core").optional);
+        assert_eq!(dep("rand_core").package.as_deref(), Some("rand-core"));
+        assert_eq!(
+            dep("internal").registry.as_deref(),
+            Some("https://holger.example/rust-dev/index")
+        );
+        assert_eq!(dep("tempfile").kind, "dev");
+        assert_eq!(dep("cc").kind, "build");
+        assert_eq!(dep("cc").req, "1.0");
+        assert_eq!(dep("libc").target.as_deref(), Some("cfg(unix)"));
+
+        assert_eq!(
+            entry.features.keys().collect::<Vec<_>>(),
+            vec!["default", "std"]
+        );
+        let features2 = entry.features2.as_ref().unwrap();
+        assert_eq!(
+            features2.keys().collect::<Vec<_>>(),
+            vec!["rand", "serde-std"]
+        );
+        assert_eq!(entry.v, Some(2));
+    }
+
+    #[test]
+    fn config_json_points_at_public_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = RustRepoFile::new(
+            "rust-prod".into(),
+            dir.path().to_path_buf(),
+            "https://holger.example.com/rust-prod/".into(),
+        )
+        .unwrap();
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/index/config.json", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+
+        let config: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            config["dl"],
+            "https://holger.example.com/rust-prod/crates/{crate}/{version}/download"
+        );
+        assert_eq!(config["api"], "https://holger.example.com/rust-prod");
+    }
+
+    #[test]
+    fn publish_stores_crate_and_updates_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = open_repo(dir.path());
+        repo.writable = true;
+
+        let data = crate_file("fresh", "0.1.0", "");
+        let (status, _, _) = repo
+            .handle_http2_request(
+                "PUT",
+                "/rust-prod/api/v1/crates/new",
+                &[],
+                &publish_body("fresh", "0.1.0", &data),
+            )
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(
+            std::fs::read(dir.path().join("fresh/fresh-0.1.0.crate")).unwrap(),
+            data
+        );
+
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/index/fr/es/fresh", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        let entry: IndexEntry = serde_json::from_slice(&body).unwrap();
+        assert_eq!(entry.cksum, RustRepoFile::crate_sha256_hex(&data));
+
+        // Stored crates are immutable
+        let (status, _, body) = repo
+            .handle_http2_request(
+                "PUT",
+                "/rust-prod/api/v1/crates/new",
+                &[],
+                &publish_body("fresh", "0.1.0", &crate_file("fresh", "0.1.0", "# changed")),
+            )
+            .unwrap();
+        assert_eq!(status, 409);
+        assert!(String::from_utf8(body)
+            .unwrap()
+            .contains("already uploaded"));
+
+        // A reopened repository sees the published crate
+        let reopened = open_repo(dir.path());
+        assert!(reopened.index().get("fresh", "0.1.0").is_some());
+    }
+
+    #[test]
+    fn publish_rejects_invalid_requests() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repo = open_repo(dir.path());
+        let publish = |repo: &RustRepoFile, body: &[u8]| {
+            repo.handle_http2_request("PUT", "/rust-prod/api/v1/crates/new", &[], body)
+                .unwrap()
+                .0
+        };
+        let data = crate_file("fresh", "0.1.0", "");
+
+        // Repositories without ron_in refuse writes
+        assert_eq!(publish(&repo, &publish_body("fresh", "0.1.0", &data)), 403);
+
+        repo.writable = true;
+        assert_eq!(publish(&repo, &publish_body("1fresh", "0.1.0", &data)), 400);
+        assert_eq!(publish(&repo, &publish_body("fresh", "latest", &data)), 400);
+        assert_eq!(publish(&repo, &publish_body("fresh", "0.2.0", &data)), 400);
+        assert_eq!(
+            publish(&repo, &publish_body("fresh", "0.1.0", &data)[..20]),
+            400
+        );
+        assert!(repo.index().versions("fresh").is_none());
+    }
+
+    #[test]
+    fn yank_flips_index_flag_and_keeps_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = crate_file("foo", "1.0.0", "");
+        std::fs::write(dir.path().join("foo-1.0.0.crate"), &data).unwrap();
+        let mut repo = open_repo(dir.path());
+        let auth = [("authorization".to_string(), "s3cr3t".to_string())];
+        let yank_url = "/rust-prod/api/v1/crates/foo/1.0.0/yank";
+
+        assert_eq!(
+            repo.handle_http2_request("DELETE", yank_url, &auth, b"")
+                .unwrap()
+                .0,
+            403
+        );
+        repo.writable = true;
+        assert_eq!(
+            repo.handle_http2_request("GET", yank_url, &auth, b"")
+                .unwrap()
+                .0,
+            405
+        );
+        let missing = "/rust-prod/api/v1/crates/foo/9.9.9/yank";
+        assert_eq!(
+            repo.handle_http2_request("DELETE", missing, &auth, b"")
+                .unwrap()
+                .0,
+            404
+        );
+
+        let (status, _, body) = repo
+            .handle_http2_request("DELETE", yank_url, &auth, b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, br#"{"ok":true}"#);
+        assert!(repo.index().get("foo", "1.0.0").unwrap().entry.yanked);
+
+        // The blob is untouched and still downloadable
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/crates/foo/1.0.0/download", &[], b"")
+            .unwrap();
+        assert_eq!((status, body), (200, data));
+
+        let crate_path = dir.path().join("foo-1.0.0.crate");
+        let history = read_events(&crate_path).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].action, YankAction::Yank);
+        assert_eq!(history[0].by, requester(&auth));
+        assert!(!history[0].by.contains("s3cr3t"));
+
+        // State survives a restart by replaying the log
+        assert!(
+            open_repo(dir.path())
+                .index()
+                .get("foo", "1.0.0")
+                .unwrap()
+                .entry
+                .yanked
+        );
+
+        let unyank_url = "/rust-prod/api/v1/crates/foo/1.0.0/unyank";
+        assert_eq!(
+            repo.handle_http2_request("PUT", unyank_url, &[], b"")
+                .unwrap()
+                .0,
+            200
+        );
+        assert!(!repo.index().get("foo", "1.0.0").unwrap().entry.yanked);
+        let history = read_events(&crate_path).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].by, "anonymous");
+        assert!(
+            !open_repo(dir.path())
+                .index()
+                .get("foo", "1.0.0")
+                .unwrap()
+                .entry
+                .yanked
+        );
+    }
+
+    #[test]
+    fn search_matches_name_description_and_keywords() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            (
+                "serde",
+                "1.0.0",
+                "description = \"A serialization framework\"",
+            ),
+            (
+                "serde",
+                "1.0.1",
+                "description = \"A generic serialization framework\"",
+            ),
+            (
+                "serde_json",
+                "1.0.0",
+                "description = \"JSON support for serde\"",
+            ),
+            (
+                "bincode",
+                "1.3.0",
+                "keywords = [\"binary\", \"serialization\"]",
+            ),
+            ("tokio", "1.0.0", "description = \"An async runtime\""),
+        ];
+        for (name, vers, extra) in files {
+            std::fs::write(
+                dir.path().join(format!("{name}-{vers}.crate")),
+                crate_file(name, vers, extra),
+            )
+            .unwrap();
+        }
+        let repo = open_repo(dir.path());
+        let search = |query: &str| -> serde_json::Value {
+            let url = format!("/rust-prod/api/v1/crates?{query}");
+            let (status, _, body) = repo.handle_http2_request("GET", &url, &[], b"").unwrap();
+            assert_eq!(status, 200);
+            serde_json::from_slice(&body).unwrap()
+        };
+
+        let result = search("q=serde&per_page=10");
+        assert_eq!(result["meta"]["total"], 2);
+        assert_eq!(result["crates"][0]["name"], "serde");
+        assert_eq!(result["crates"][0]["max_version"], "1.0.1");
+        assert_eq!(
+            result["crates"][0]["description"],
+            "A generic serialization framework"
+        );
+        assert_eq!(result["crates"][1]["name"], "serde_json");
+
+        let result = search("q=serialization&per_page=1");
+        assert_eq!(result["meta"]["total"], 2);
+        assert_eq!(result["crates"].as_array().unwrap().len(), 1);
+        assert_eq!(result["crates"][0]["name"], "bincode");
+
+        let result = search("q=async+runtime");
+        assert_eq!(result["crates"][0]["name"], "tokio");
+        assert_eq!(search("q=nothing-here")["meta"]["total"], 0);
+    }
+
+    #[test]
+    fn git_index_snapshot_is_deterministic() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("serde-1.0.0.crate"),
+            crate_file("serde", "1.0.0", ""),
+        )
+        .unwrap();
+        let repo = open_repo(dir.path());
+        let files = git_index::index_files(&repo.index(), &repo.public_url).unwrap();
+        assert_eq!(
+            files.keys().collect::<Vec<_>>(),
+            ["config.json", "se/rd/serde"]
+        );
+
+        let head = repo.git_snapshot().unwrap().head_hex();
+        assert_eq!(
+            head,
+            open_repo(dir.path()).git_snapshot().unwrap().head_hex()
+        );
+
+        // Another instance with the same crates and URL serves the same commit
+        let copy = tempfile::tempdir().unwrap();
+        std::fs::copy(
+            dir.path().join("serde-1.0.0.crate"),
+            copy.path().join("serde-1.0.0.crate"),
+        )
+        .unwrap();
+        assert_eq!(
+            head,
+            open_repo(copy.path()).git_snapshot().unwrap().head_hex()
+        );
+
+        // config.json carries the public URL, so another URL is another commit
+        let elsewhere = RustRepoFile::new(
+            "rust-prod".into(),
+            copy.path().to_path_buf(),
+            "https://holger.example.com/rust-prod".into(),
+        )
+        .unwrap();
+        let other_files =
+            git_index::index_files(&elsewhere.index(), &elsewhere.public_url).unwrap();
+        assert_ne!(other_files["config.json"], files["config.json"]);
+        assert_eq!(other_files["se/rd/serde"], files["se/rd/serde"]);
+        assert_ne!(head, elsewhere.git_snapshot().unwrap().head_hex());
+
+        std::fs::write(
+            dir.path().join("serde-1.0.1.crate"),
+            crate_file("serde", "1.0.1", ""),
+        )
+        .unwrap();
+        assert_ne!(
+            head,
+            open_repo(dir.path()).git_snapshot().unwrap().head_hex()
+        );
+    }
+
+    #[test]
+    fn git_index_serves_smart_http_upload_pack() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("foo-1.0.0.crate"),
+            crate_file("foo", "1.0.0", ""),
+        )
+        .unwrap();
+        let repo = open_repo(dir.path());
+        let head = repo.git_snapshot().unwrap().head_hex();
+
+        let refs = "/rust-prod/git-index/info/refs?service=git-upload-pack";
+        let (status, headers, body) = repo.handle_http2_request("GET", refs, &[], b"").unwrap();
+        assert_eq!(status, 200);
+        assert!(headers.contains(&(
+            "Content-Type".into(),
+            "application/x-git-upload-pack-advertisement".into()
+        )));
+        assert!(body.starts_with(b"001e# service=git-upload-pack\n0000"));
+        assert!(String::from_utf8_lossy(&body).contains(&format!("{head} refs/heads/master\n")));
+        assert_eq!(
+            repo.handle_http2_request("GET", "/rust-prod/git-index/info/refs", &[], b"")
+                .unwrap()
+                .0,
+            403
+        );
+
+        let upload = "/rust-prod/git-index/git-upload-pack";
+        assert_eq!(
+            repo.handle_http2_request("GET", upload, &[], b"")
+                .unwrap()
+                .0,
+            405
+        );
+        let want = format!("want {head} ofs-delta\n");
+        let request = format!("{:04x}{want}00000009done\n", want.len() + 4);
+        let (status, _, body) = repo
+            .handle_http2_request("POST", upload, &[], request.as_bytes())
+            .unwrap();
+        assert_eq!(status, 200);
+        assert!(body.starts_with(b"0008NAK\nPACK"));
+        // Blobs for config.json and 3/f/foo, trees for /, 3 and 3/f, and the commit
+        assert_eq!(&body[12..20], &[0, 0, 0, 2, 0, 0, 0, 6]);
+
+        let stale = format!("{:04x}want {}\n00000009done\n", 50, "0".repeat(40));
+        let (_, _, body) = repo
+            .handle_http2_request("POST", upload, &[], stale.as_bytes())
+            .unwrap();
+        assert!(body.starts_with(b"004aERR upload-pack: not our ref"));
+    }
+
+    /// Package `files` the way cargo does and unpack them as `cargo vendor`
+    /// would, returning the vendored directory and the original `.crate`.
+    fn vendored_crate(vendor: &Path, name: &str, vers: &str) -> (PathBuf, Vec<u8>) {
+        let manifest = format!("[package]\nname = \"{name}\"\nversion = \"{vers}\"\n");
+        let files = [
+            ("Cargo.toml", manifest.as_bytes()),
+            ("Cargo.toml.orig", manifest.as_bytes()),
+            ("src/lib.rs", b"pub fn it() {}\n".as_slice()),
+        ];
+        let gz = flate2::GzBuilder::new()
+            .filename(format!("{name}-{vers}.crate"))
+            .write(Vec::new(), flate2::Compression::best());
+        let mut builder = tar::Builder::new(gz);
+        let dir = vendor.join(name);
+        let mut checksums = serde_json::Map::new();
+        for (file, data) in files {
+            std::fs::create_dir_all(dir.join(file).parent().unwrap()).unwrap();
+            std::fs::write(dir.join(file), data).unwrap();
+            let mut header = tar::Header::new_gnu();
+            if file == "Cargo.toml" {
+                // Generated by cargo rather than copied from disk
+                header.set_entry_type(tar::EntryType::file());
+                header.set_mode(0o644);
+                header.set_size(data.len() as u64);
+                header.set_mtime(1153704088);
+            } else {
+                let metadata = std::fs::metadata(dir.join(file)).unwrap();
+                header.set_metadata_in_mode(&metadata, tar::HeaderMode::Deterministic);
+            }
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("{name}-{vers}/{file}"), data)
+                .unwrap();
+            checksums.insert(file.into(), RustRepoFile::crate_sha256_hex(data).into());
+        }
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+        let checksum = serde_json::json!({
+            "files": checksums,
+            "package": RustRepoFile::crate_sha256_hex(&bytes),
+        });
+        std::fs::write(dir.join(".cargo-checksum.json"), checksum.to_string()).unwrap();
+        (dir, bytes)
+    }
+
+    #[test]
+    fn vendor_import_rebuilds_identical_crates() {
+        let vendor = tempfile::tempdir().unwrap();
+        let (_, original) = vendored_crate(vendor.path(), "foo", "1.2.3");
+        let dir = tempfile::tempdir().unwrap();
+        let repo = open_repo(dir.path());
+
+        let report = repo.import_vendor(vendor.path()).unwrap();
+        assert_eq!(report.imported.len(), 1);
+        assert!(report.rejected.is_empty());
+        assert_eq!(
+            std::fs::read(repo.crate_file_path("foo", "1.2.3")).unwrap(),
+            original
+        );
+        assert_eq!(
+            repo.index().get("foo", "1.2.3").unwrap().entry.cksum,
+            RustRepoFile::crate_sha256_hex(&original)
+        );
+
+        let again = repo.import_vendor(vendor.path()).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.existing.len(), 1);
+    }
+
+    #[test]
+    fn vendor_import_reports_crates_it_cannot_reproduce() {
+        let vendor = tempfile::tempdir().unwrap();
+        let (tampered, _) = vendored_crate(vendor.path(), "tampered", "1.0.0");
+        std::fs::write(tampered.join("src/lib.rs"), "pub fn other() {}\n").unwrap();
+        let (extra, _) = vendored_crate(vendor.path(), "extra", "1.0.0");
+        std::fs::write(extra.join("build.rs"), "fn main() {}\n").unwrap();
+        let (mismatch, _) = vendored_crate(vendor.path(), "mismatch", "1.0.0");
+        let checksum_path = mismatch.join(".cargo-checksum.json");
+        let mut checksum: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&checksum_path).unwrap()).unwrap();
+        checksum["package"] = "0".repeat(64).into();
+        std::fs::write(&checksum_path, checksum.to_string()).unwrap();
+        let (git, _) = vendored_crate(vendor.path(), "git", "1.0.0");
+        checksum["package"] = serde_json::Value::Null;
+        std::fs::write(git.join(".cargo-checksum.json"), checksum.to_string()).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = open_repo(dir.path());
+        let report = repo.import_vendor(vendor.path()).unwrap();
+        assert!(report.imported.is_empty());
+        let reasons: BTreeMap<_, _> = report
+            .rejected
+            .iter()
+            .map(|(dir, reason)| (dir.file_name().unwrap().to_str().unwrap(), reason.as_str()))
+            .collect();
+        assert!(reasons["tampered"].starts_with("src/lib.rs has checksum"));
+        assert!(reasons["extra"].starts_with("build.rs is not listed"));
+        assert!(reasons["git"].starts_with("no package checksum"));
+        assert_eq!(report.unreproducible.len(), 1);
+        assert!(report.unreproducible[0]
+            .1
+            .starts_with("could not reproduce mismatch@1.0.0"));
+        assert!(repo.index().crates().next().is_none());
+    }
+
+    /// subtle 2.6.1 from crates.io and what `cargo vendor` made of it.
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    fn copy_dir(from: &Path, to: &Path) {
+        std::fs::create_dir_all(to).unwrap();
+        for entry in std::fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &to.join(entry.file_name()));
+            } else {
+                std::fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+            }
+        }
+    }
+
+    /// Drop a vendored file and its checksum entry, as `cargo vendor` does
+    /// for files it skips.
+    fn drop_vendored_file(dir: &Path, rel: &str) {
+        std::fs::remove_file(dir.join(rel)).unwrap();
+        let checksum_path = dir.join(".cargo-checksum.json");
+        let mut checksum: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&checksum_path).unwrap()).unwrap();
+        checksum["files"].as_object_mut().unwrap().remove(rel);
+        std::fs::write(&checksum_path, checksum.to_string()).unwrap();
+    }
+
+    #[test]
+    fn vendor_import_rebuilds_real_crates() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = open_repo(dir.path());
+        let report = repo.import_vendor(&fixtures().join("vendor")).unwrap();
+        assert_eq!(report.imported.len(), 1, "{report:?}");
+        assert_eq!(report.imported[0].name, "subtle");
+        assert_eq!(
+            std::fs::read(repo.crate_file_path("subtle", "2.6.1")).unwrap(),
+            std::fs::read(fixtures().join("subtle-2.6.1.crate")).unwrap()
+        );
+
+        // Older cargo vendor skips Cargo.toml.orig, which is reported up front
+        let old_vendor = tempfile::tempdir().unwrap();
+        let subtle = old_vendor.path().join("subtle");
+        copy_dir(&fixtures().join("vendor/subtle"), &subtle);
+        drop_vendored_file(&subtle, "Cargo.toml.orig");
+        // A file cargo vendor skipped, like .gitignore, cannot be rebuilt either
+        let dotfile = old_vendor.path().join("subtle-dotfile");
+        copy_dir(&fixtures().join("vendor/subtle"), &dotfile);
+        drop_vendored_file(&dotfile, "CONTRIBUTING.md");
+
+        let report = repo.import_vendor(old_vendor.path()).unwrap();
+        assert!(report.imported.is_empty() && report.rejected.is_empty());
+        let reasons: BTreeMap<_, _> = report
+            .unreproducible
+            .iter()
+            .map(|(dir, reason)| (dir.file_name().unwrap().to_str().unwrap(), reason.as_str()))
+            .collect();
+        assert!(reasons["subtle"].contains("without Cargo.toml.orig"));
+        assert!(reasons["subtle-dotfile"].starts_with("could not reproduce subtle@2.6.1"));
+    }
 }
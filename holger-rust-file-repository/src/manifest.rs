@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+
+use crate::index::{IndexDependency, IndexEntry, StoredCrate};
+use crate::RustRepoFile;
+
+/// The parts of a normalized (published) `Cargo.toml` the index needs.
+#[derive(Debug, Deserialize)]
+pub struct CrateManifest {
+    pub package: ManifestPackage,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default, rename = "dev-dependencies", alias = "dev_dependencies")]
+    pub dev_dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default, rename = "build-dependencies", alias = "build_dependencies")]
+    pub build_dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default)]
+    pub target: BTreeMap<String, TargetDependencies>,
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: String,
+    pub links: Option<String>,
+    #[serde(rename = "rust-version", alias = "rust_version")]
+    pub rust_version: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TargetDependencies {
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default, rename = "dev-dependencies", alias = "dev_dependencies")]
+    pub dev_dependencies: BTreeMap<String, DependencySpec>,
+    #[serde(default, rename = "build-dependencies", alias = "build_dependencies")]
+    pub build_dependencies: BTreeMap<String, DependencySpec>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum DependencySpec {
+    Simple(String),
+    Detailed(DetailedDependency),
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DetailedDependency {
+    pub version: Option<String>,
+    pub path: Option<String>,
+    pub git: Option<String>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(rename = "default-features", alias = "default_features")]
+    pub default_features: Option<bool>,
+    pub package: Option<String>,
+    #[serde(rename = "registry-index")]
+    pub registry_index: Option<String>,
+}
+
+impl CrateManifest {
+    pub fn parse(manifest: &str) -> anyhow::Result<Self> {
+        toml::from_str(manifest).context("invalid Cargo.toml")
+    }
+
+    /// Build the index entry for this manifest, given the `.crate` checksum.
+    pub fn index_entry(&self, cksum: String) -> IndexEntry {
+        let mut deps = Vec::new();
+        push_deps(&mut deps, &self.dependencies, "normal", None);
+        push_deps(&mut deps, &self.dev_dependencies, "dev", None);
+        push_deps(&mut deps, &self.build_dependencies, "build", None);
+        for (target, table) in &self.target {
+            push_deps(&mut deps, &table.dependencies, "normal", Some(target));
+            push_deps(&mut deps, &table.dev_dependencies, "dev", Some(target));
+            push_deps(&mut deps, &table.build_dependencies, "build", Some(target));
+        }
+
+        // Features using `dep:` or `?/` syntax go to `features2` so older
+        // cargo versions that cannot parse them ignore them instead of failing.
+        let (features2, features): (BTreeMap<_, _>, BTreeMap<_, _>) =
+            self.features.clone().into_iter().partition(|(_, values)| {
+                values
+                    .iter()
+                    .any(|v| v.starts_with("dep:") || v.contains("?/"))
+            });
+
+        let v = (!features2.is_empty()).then_some(2);
+        IndexEntry {
+            name: self.package.name.clone(),
+            vers: self.package.version.clone(),
+            deps,
+            cksum,
+            features,
+            yanked: false,
+            links: self.package.links.clone(),
+            v,
+            features2: v.map(|_| features2),
+            rust_version: self.package.rust_version.clone(),
+        }
+    }
+}
+
+fn push_deps(
+    out: &mut Vec<IndexDependency>,
+    deps: &BTreeMap<String, DependencySpec>,
+    kind: &str,
+    target: Option<&String>,
+) {
+    for (name, spec) in deps {
+        let detailed;
+        let dep = match spec {
+            DependencySpec::Simple(req) => {
+                detailed = DetailedDependency {
+                    version: Some(req.clone()),
+                    ..Default::default()
+                };
+                &detailed
+            }
+            DependencySpec::Detailed(d) => d,
+        };
+        // Path and git dependencies without a version are stripped by cargo on publish.
+        if dep.version.is_none() && (dep.path.is_some() || dep.git.is_some()) {
+            continue;
+        }
+        out.push(IndexDependency {
+            name: name.clone(),
+            req: dep.version.clone().unwrap_or_else(|| "*".to_string()),
+            features: dep.features.clone(),
+            optional: dep.optional,
+            default_features: dep.default_features.unwrap_or(true),
+            target: target.cloned(),
+            kind: kind.to_string(),
+            registry: dep.registry_index.clone(),
+            package: dep.package.clone(),
+        });
+    }
+}
+
+/// Extract `<name>-<version>/Cargo.toml` from a gzipped `.crate` tarball.
+pub fn read_crate_manifest(crate_bytes: &[u8]) -> anyhow::Result<String> {
+    let mut archive = tar::Archive::new(GzDecoder::new(crate_bytes));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let mut components = path.components();
+        let (Some(_root), Some(file), None) =
+            (components.next(), components.next(), components.next())
+        else {
+            continue;
+        };
+        if file.as_os_str() == "Cargo.toml" {
+            let mut manifest = String::new();
+            entry.read_to_string(&mut manifest)?;
+            return Ok(manifest);
+        }
+    }
+    Err(anyhow!("no Cargo.toml found in crate"))
+}
+
+impl StoredCrate {
+    /// Pair a parsed manifest and its index entry with the file it came from.
+    pub fn from_manifest(manifest: CrateManifest, entry: IndexEntry, path: PathBuf) -> Self {
+        StoredCrate {
+            entry,
+            path,
+            description: manifest.package.description,
+            keywords: manifest.package.keywords,
+        }
+    }
+}
+
+/// Parse a `.crate` tarball into its manifest and index entry.
+pub fn index_entry_from_crate(crate_bytes: &[u8]) -> anyhow::Result<(CrateManifest, IndexEntry)> {
+    let manifest = CrateManifest::parse(&read_crate_manifest(crate_bytes)?)?;
+    let entry = manifest.index_entry(RustRepoFile::crate_sha256_hex(crate_bytes));
+    Ok((manifest, entry))
+}
@@ -0,0 +1,102 @@
+use anyhow::{anyhow, bail, Context};
+use holger_traits::{ArtifactId, Http2Response};
+use serde::Deserialize;
+
+/// Metadata block of a `cargo publish` request. Only the fields Holger checks
+/// are kept; the index entry is rebuilt from the crate's own manifest.
+#[derive(Debug, Deserialize)]
+pub struct PublishMetadata {
+    pub name: String,
+    pub vers: String,
+}
+
+/// A decoded `PUT /api/v1/crates/new` body.
+#[derive(Debug)]
+pub struct PublishRequest<'a> {
+    pub metadata: PublishMetadata,
+    pub crate_bytes: &'a [u8],
+}
+
+impl<'a> PublishRequest<'a> {
+    /// Decode the publish wire format: a little-endian u32 length followed by the
+    /// JSON metadata, then a little-endian u32 length followed by the `.crate`.
+    pub fn parse(body: &'a [u8]) -> anyhow::Result<Self> {
+        let (json, rest) = split_length_prefixed(body).context("malformed metadata block")?;
+        let (crate_bytes, rest) = split_length_prefixed(rest).context("malformed crate block")?;
+        if !rest.is_empty() {
+            bail!("trailing bytes after crate block");
+        }
+        let metadata: PublishMetadata =
+            serde_json::from_slice(json).context("invalid publish metadata")?;
+        validate_crate_name(&metadata.name)?;
+        semver::Version::parse(&metadata.vers)
+            .map_err(|e| anyhow!("invalid version `{}`: {}", metadata.vers, e))?;
+        Ok(PublishRequest {
+            metadata,
+            crate_bytes,
+        })
+    }
+
+    pub fn artifact_id(&self) -> ArtifactId {
+        ArtifactId {
+            namespace: None,
+            name: self.metadata.name.clone(),
+            version: self.metadata.vers.clone(),
+        }
+    }
+}
+
+fn split_length_prefixed(data: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+    let (len, rest) = data
+        .split_first_chunk::<4>()
+        .ok_or_else(|| anyhow!("missing length prefix"))?;
+    let len = u32::from_le_bytes(*len) as usize;
+    if rest.len() < len {
+        bail!(
+            "length prefix {} exceeds remaining {} bytes",
+            len,
+            rest.len()
+        );
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Apply the crates.io naming rules: ASCII alphanumerics, `-` and `_`,
+/// starting with a letter, at most 64 characters.
+pub fn validate_crate_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        bail!("crate name `{}` must be between 1 and 64 characters", name);
+    }
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        bail!("crate name `{}` must start with an ASCII letter", name);
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+    {
+        bail!("crate name `{}` contains invalid character `{}`", name, c);
+    }
+    Ok(())
+}
+
+/// Successful publish response, in the shape cargo expects.
+pub fn publish_ok() -> Http2Response {
+    let json = serde_json::json!({
+        "warnings": { "invalid_categories": [], "invalid_badges": [], "other": [] }
+    });
+    (
+        200,
+        vec![("Content-Type".into(), "application/json".into())],
+        json.to_string().into_bytes(),
+    )
+}
+
+/// Registry API error; cargo prints `detail` to the user.
+pub fn cargo_error(status: u16, detail: &str) -> Http2Response {
+    let json = serde_json::json!({ "errors": [{ "detail": detail }] });
+    (
+        status,
+        vec![("Content-Type".into(), "application/json".into())],
+        json.to_string().into_bytes(),
+    )
+}
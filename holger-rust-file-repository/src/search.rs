@@ -0,0 +1,108 @@
+use serde::Serialize;
+
+use crate::index::{CrateIndex, StoredCrate};
+
+const DEFAULT_PER_PAGE: usize = 10;
+const MAX_PER_PAGE: usize = 100;
+
+/// Body of `GET /api/v1/crates`, in the crates.io shape cargo parses.
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub crates: Vec<SearchHit>,
+    pub meta: SearchMeta,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub name: String,
+    pub max_version: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchMeta {
+    pub total: usize,
+}
+
+/// Search parameters taken from the query string (`q`, `per_page`).
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub q: String,
+    pub per_page: usize,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = SearchQuery {
+            q: String::new(),
+            per_page: DEFAULT_PER_PAGE,
+        };
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "q" => parsed.q = value.trim().to_string(),
+                "per_page" => {
+                    parsed.per_page = value
+                        .parse()
+                        .unwrap_or(DEFAULT_PER_PAGE)
+                        .clamp(1, MAX_PER_PAGE)
+                }
+                _ => {}
+            }
+        }
+        parsed
+    }
+}
+
+/// Match `q` against crate names, descriptions and keywords of the newest
+/// version. Exact name matches rank first, then name matches, then the rest.
+pub fn search(index: &CrateIndex, query: &SearchQuery) -> SearchResponse {
+    let needle = query.q.to_lowercase();
+    let mut hits: Vec<(u8, &StoredCrate)> = index
+        .crates()
+        .filter_map(|versions| {
+            let newest = newest_version(versions)?;
+            let name = newest.entry.name.to_lowercase();
+            let rank = if name == needle {
+                0
+            } else if name.contains(&needle) {
+                1
+            } else if newest
+                .description
+                .as_deref()
+                .is_some_and(|d| d.to_lowercase().contains(&needle))
+                || newest
+                    .keywords
+                    .iter()
+                    .any(|k| k.to_lowercase().contains(&needle))
+            {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, newest))
+        })
+        .collect();
+    hits.sort_by(|(ra, a), (rb, b)| ra.cmp(rb).then_with(|| a.entry.name.cmp(&b.entry.name)));
+
+    SearchResponse {
+        meta: SearchMeta { total: hits.len() },
+        crates: hits
+            .into_iter()
+            .take(query.per_page)
+            .map(|(_, c)| SearchHit {
+                name: c.entry.name.clone(),
+                max_version: c.entry.vers.clone(),
+                description: c.description.clone(),
+            })
+            .collect(),
+    }
+}
+
+/// Highest non-yanked version, or the highest version if all are yanked.
+fn newest_version(versions: &[StoredCrate]) -> Option<&StoredCrate> {
+    versions
+        .iter()
+        .rev()
+        .find(|c| !c.entry.yanked)
+        .or_else(|| versions.last())
+}
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use holger_traits::ArtifactId;
+use serde::Deserialize;
+use tar::{EntryType, Header, HeaderMode};
+
+use crate::manifest::CrateManifest;
+use crate::RustRepoFile;
+
+const CHECKSUM_FILE: &str = ".cargo-checksum.json";
+
+/// Files cargo generates while packaging.
+const GENERATED_FILES: [&str; 3] = ["Cargo.toml", "Cargo.lock", ".cargo_vcs_info.json"];
+
+/// mtime tar's `HeaderMode::Deterministic` writes; newer cargo uses it for generated files too.
+const DETERMINISTIC_MTIME: u64 = 1153704088;
+
+/// First line of the manifest `cargo package` writes; the original is kept
+/// next to it as `Cargo.toml.orig`.
+const GENERATED_MANIFEST_HEADER: &str = "# THIS FILE IS AUTOMATICALLY GENERATED BY CARGO";
+
+/// Tar header conventions cargo used for published crates over time, newest first.
+const HEADER_LAYOUTS: [HeaderLayout; 4] = [
+    HeaderLayout {
+        generated_mtime: Some(DETERMINISTIC_MTIME),
+        mtime: DETERMINISTIC_MTIME,
+    },
+    HeaderLayout {
+        generated_mtime: Some(1),
+        mtime: DETERMINISTIC_MTIME,
+    },
+    HeaderLayout {
+        generated_mtime: None,
+        mtime: DETERMINISTIC_MTIME,
+    },
+    // Older tar wrote mtime 0 in deterministic headers
+    HeaderLayout {
+        generated_mtime: Some(0),
+        mtime: 0,
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct HeaderLayout {
+    /// mtime of generated files, which get mode 0644 and no owner fields;
+    /// `None` when they get the same headers as every other file
+    generated_mtime: Option<u64>,
+    /// mtime of every other file
+    mtime: u64,
+}
+
+/// Deflate implementations cargo has packaged with. Both run at best
+/// compression but produce different streams, so both are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Deflater {
+    /// zlib-rs, used by current cargo
+    ZlibRs,
+    /// C zlib, used by older cargo
+    Zlib,
+}
+
+/// `.cargo-checksum.json` as written by `cargo vendor`.
+#[derive(Debug, Deserialize)]
+pub struct CargoChecksum {
+    /// sha256 of every vendored file, keyed by `/`-separated relative path
+    pub files: BTreeMap<String, String>,
+    /// sha256 of the original `.crate`; `null` for git and path sources
+    pub package: Option<String>,
+}
+
+/// A `.crate` rebuilt from a vendored directory, byte-identical to the original.
+#[derive(Debug)]
+pub struct RebuiltCrate {
+    pub id: ArtifactId,
+    pub bytes: Vec<u8>,
+    pub cksum: String,
+}
+
+/// Outcome of rebuilding one vendored directory whose files check out.
+#[derive(Debug)]
+pub enum Rebuild {
+    Crate(RebuiltCrate),
+    /// The published `.crate` cannot be reproduced from the vendored files
+    Unreproducible(String),
+}
+
+/// Outcome of importing a `cargo vendor` directory.
+#[derive(Debug, Default)]
+pub struct VendorReport {
+    pub imported: Vec<ArtifactId>,
+    /// Versions already stored with the same checksum
+    pub existing: Vec<ArtifactId>,
+    /// Vendored directories that were not stored, with the reason
+    pub rejected: Vec<(PathBuf, String)>,
+    /// Vendored directories whose files check out but cannot be packed back
+    /// into the published `.crate`; import those crates as `.crate` files
+    pub unreproducible: Vec<(PathBuf, String)>,
+}
+
+impl RustRepoFile {
+    /// Rebuild and store every crate below a `cargo vendor` directory.
+    ///
+    /// Only crates whose rebuilt tarball matches the recorded `package`
+    /// checksum are stored. Directories missing files the published crate
+    /// contains, such as the `Cargo.toml.orig` older `cargo vendor` leaves
+    /// out, are listed in `unreproducible` without an attempt; files that
+    /// fail their checksum end up in `rejected`.
+    pub fn import_vendor(&self, vendor_dir: &Path) -> anyhow::Result<VendorReport> {
+        let mut report = VendorReport::default();
+        let mut dirs = Vec::new();
+        for entry in std::fs::read_dir(vendor_dir)
+            .with_context(|| format!("reading vendor directory {}", vendor_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.join(CHECKSUM_FILE).is_file() {
+                dirs.push(path);
+            }
+        }
+        dirs.sort();
+
+        for dir in dirs {
+            let rebuilt = match rebuild_crate(&dir) {
+                Ok(Rebuild::Crate(rebuilt)) => rebuilt,
+                Ok(Rebuild::Unreproducible(reason)) => {
+                    report.unreproducible.push((dir, reason));
+                    continue;
+                }
+                Err(e) => {
+                    report.rejected.push((dir, format!("{e:#}")));
+                    continue;
+                }
+            };
+            let stored = self
+                .index()
+                .get(&rebuilt.id.name, &rebuilt.id.version)
+                .map(|c| c.entry.cksum.clone());
+            match stored {
+                Some(cksum) if cksum == rebuilt.cksum => report.existing.push(rebuilt.id),
+                Some(cksum) => report.rejected.push((
+                    dir,
+                    format!(
+                        "{}@{} is already stored with checksum {}",
+                        rebuilt.id.name, rebuilt.id.version, cksum
+                    ),
+                )),
+                None => match self.store(&rebuilt.id, &rebuilt.bytes) {
+                    Ok(()) => report.imported.push(rebuilt.id),
+                    Err(e) => report.rejected.push((dir, format!("{e:#}"))),
+                },
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Rebuild the `.crate` for one vendored package directory the way
+/// `cargo package` writes it, after checking every file against
+/// `.cargo-checksum.json`.
+pub fn rebuild_crate(dir: &Path) -> anyhow::Result<Rebuild> {
+    let checksum: CargoChecksum = serde_json::from_slice(&std::fs::read(dir.join(CHECKSUM_FILE))?)
+        .context("invalid .cargo-checksum.json")?;
+    let Some(package) = checksum.package else {
+        bail!("no package checksum recorded, not a registry crate");
+    };
+
+    let manifest_text = std::fs::read_to_string(dir.join("Cargo.toml"))?;
+    let manifest = CrateManifest::parse(&manifest_text)?;
+    let id = ArtifactId {
+        namespace: None,
+        name: manifest.package.name.clone(),
+        version: manifest.package.version.clone(),
+    };
+
+    let files = vendored_files(dir)?;
+    for rel in &files {
+        let key = rel_key(rel);
+        let Some(expected) = checksum.files.get(&key) else {
+            bail!("{key} is not listed in {CHECKSUM_FILE}");
+        };
+        let actual = RustRepoFile::crate_sha256_hex(&std::fs::read(dir.join(rel))?);
+        if actual != *expected {
+            bail!("{key} has checksum {actual}, expected {expected}");
+        }
+    }
+    if let Some(missing) = checksum
+        .files
+        .keys()
+        .find(|key| !files.iter().any(|rel| rel_key(rel) == **key))
+    {
+        bail!("{missing} is listed in {CHECKSUM_FILE} but missing");
+    }
+
+    if manifest_text.starts_with(GENERATED_MANIFEST_HEADER)
+        && !dir.join("Cargo.toml.orig").is_file()
+    {
+        return Ok(Rebuild::Unreproducible(format!(
+            "{}@{} was vendored without Cargo.toml.orig, which older cargo vendor leaves out",
+            id.name, id.version
+        )));
+    }
+
+    for layout in HEADER_LAYOUTS {
+        let tar = crate_tar(dir, &id, &files, layout)?;
+        for deflater in [Deflater::ZlibRs, Deflater::Zlib] {
+            let bytes = gzip(&tar, &format!("{}-{}.crate", id.name, id.version), deflater)?;
+            let cksum = RustRepoFile::crate_sha256_hex(&bytes);
+            if cksum == package {
+                return Ok(Rebuild::Crate(RebuiltCrate { id, bytes, cksum }));
+            }
+        }
+    }
+    Ok(Rebuild::Unreproducible(format!(
+        "could not reproduce {}@{} with checksum {}; the published crate may contain \
+         files cargo vendor leaves out, such as .gitignore",
+        id.name, id.version, package
+    )))
+}
+
+/// Relative paths of every vendored file except the checksum file, in the
+/// order cargo archives them.
+fn vendored_files(dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(rel_dir) = pending.pop() {
+        for entry in std::fs::read_dir(dir.join(&rel_dir))? {
+            let entry = entry?;
+            let rel = rel_dir.join(entry.file_name());
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(rel);
+            } else if file_type.is_file() {
+                if rel != Path::new(CHECKSUM_FILE) {
+                    found.push(rel);
+                }
+            } else {
+                bail!("{} is not a regular file", rel.display());
+            }
+        }
+    }
+    // Path ordering compares components, matching cargo's archive order
+    found.sort();
+    Ok(found)
+}
+
+fn rel_key(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write `<name>-<version>/...` entries with cargo's deterministic headers.
+fn crate_tar(
+    dir: &Path,
+    id: &ArtifactId,
+    files: &[PathBuf],
+    layout: HeaderLayout,
+) -> anyhow::Result<Vec<u8>> {
+    let base = format!("{}-{}", id.name, id.version);
+    let mut builder = tar::Builder::new(Vec::new());
+    for rel in files {
+        let data = std::fs::read(dir.join(rel))?;
+        let mut header = Header::new_gnu();
+        match layout.generated_mtime {
+            Some(mtime) if GENERATED_FILES.contains(&rel_key(rel).as_str()) => {
+                header.set_entry_type(EntryType::file());
+                header.set_mode(0o644);
+                header.set_size(data.len() as u64);
+                header.set_mtime(mtime);
+            }
+            _ => {
+                let metadata = std::fs::metadata(dir.join(rel))?;
+                header.set_metadata_in_mode(&metadata, HeaderMode::Deterministic);
+                header.set_mtime(layout.mtime);
+            }
+        }
+        header.set_cksum();
+        builder
+            .append_data(&mut header, Path::new(&base).join(rel), data.as_slice())
+            .map_err(|e| anyhow!("archiving {}: {}", rel.display(), e))?;
+    }
+    Ok(builder.into_inner()?)
+}
+
+/// Gzip `tar` the way cargo's `GzBuilder` does: original file name set,
+/// mtime 0, best compression, unknown OS.
+fn gzip(tar: &[u8], file_name: &str, deflater: Deflater) -> anyhow::Result<Vec<u8>> {
+    let mut out = vec![0x1f, 0x8b, 8, 0x08, 0, 0, 0, 0, 2, 0xff];
+    out.extend_from_slice(file_name.as_bytes());
+    out.push(0);
+    match deflater {
+        Deflater::Zlib => {
+            let mut encoder = DeflateEncoder::new(out, Compression::best());
+            encoder.write_all(tar)?;
+            out = encoder.finish()?;
+        }
+        Deflater::ZlibRs => {
+            let mut buf = vec![0; zlib_rs::compress_bound(tar.len())];
+            let config = zlib_rs::DeflateConfig {
+                // Negative window bits: raw deflate, the gzip framing is written here
+                window_bits: -15,
+                ..zlib_rs::DeflateConfig::best_compression()
+            };
+            let (deflated, code) = zlib_rs::compress_slice(&mut buf, tar, config);
+            if code != zlib_rs::ReturnCode::Ok {
+                bail!("deflate failed: {:?}", code);
+            }
+            out.extend_from_slice(deflated);
+        }
+    }
+    let mut crc = Crc::new();
+    crc.update(tar);
+    out.extend_from_slice(&crc.sum().to_le_bytes());
+    out.extend_from_slice(&(tar.len() as u32).to_le_bytes());
+    Ok(out)
+}
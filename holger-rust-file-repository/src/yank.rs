@@ -0,0 +1,84 @@
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::RustRepoFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum YankAction {
+    Yank,
+    Unyank,
+}
+
+/// One line of a crate's append-only event log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct YankEvent {
+    pub action: YankAction,
+    /// Seconds since the Unix epoch
+    pub at: u64,
+    /// Who asked for it, see [`requester`]
+    pub by: String,
+}
+
+impl YankEvent {
+    pub fn now(action: YankAction, by: String) -> Self {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        YankEvent { action, at, by }
+    }
+}
+
+/// Event log kept next to the stored `.crate`: `foo-1.0.0.crate` → `foo-1.0.0.events`.
+pub fn events_path(crate_path: &Path) -> PathBuf {
+    crate_path.with_extension("events")
+}
+
+/// Append one event. The log is never rewritten, the `.crate` is never touched.
+pub fn append_event(crate_path: &Path, event: &YankEvent) -> anyhow::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(events_path(crate_path))?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Full yank history of a stored crate, oldest first.
+pub fn read_events(crate_path: &Path) -> anyhow::Result<Vec<YankEvent>> {
+    let path = events_path(crate_path);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut events = Vec::new();
+    for line in BufReader::new(std::fs::File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            events.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(events)
+}
+
+/// A version is yanked when its most recent event is a yank.
+pub fn is_yanked(events: &[YankEvent]) -> bool {
+    events.last().is_some_and(|e| e.action == YankAction::Yank)
+}
+
+/// Identify the requester from cargo's `Authorization` header without storing
+/// the token itself: `token:<first 16 hex digits of its sha256>`.
+pub fn requester(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("authorization"))
+        .map(|(_, token)| {
+            let digest = RustRepoFile::crate_sha256_hex(token.trim().as_bytes());
+            format!("token:{}", &digest[..16])
+        })
+        .unwrap_or_else(|| "anonymous".to_string())
+}
@@ -1,25 +1,80 @@
-use anyhow::anyhow;
-use holger_traits::{ArtifactFormat, ArtifactId, RepositoryBackendTrait};
-use sha2::{Digest, Sha256};
-use std::any::Any;
+use std::path::{Path, PathBuf};
 
-/// Minimal RustRepo example
+use holger_rust_file_repository::index::{CrateIndex, StoredCrate};
+use holger_rust_file_repository::manifest::index_entry_from_crate;
+use holger_rust_file_repository::publish::cargo_error;
+use holger_rust_file_repository::RustRepoFile;
+use holger_traits::{ArtifactFormat, ArtifactId, Http2Response, RepositoryBackendTrait};
+use znippy_common::{ZnippyArchive, ZnippyReader};
+
+/// Archive entries below this prefix are served as crates
+const RUST_PREFIX: &str = "rust/";
+
+/// Read-only Rust repository served straight out of a `.znippy` archive
 pub struct RustRepoZnippy {
     pub name: String,
     //    pub format: ArtifactFormat,
     pub artifacts: Vec<ArtifactId>, // cached list of artifacts
+    pub public_url: String,         // e.g. https://10.101.1.9:8443/rust-prod
+    pub archive_path: PathBuf,
+    archive: ZnippyArchive,
+    // StoredCrate::path holds the entry path inside the archive
+    index: CrateIndex,
 }
 
-
 impl RustRepoZnippy {
-    pub fn new(name: String) -> Self {
-        RustRepoZnippy {
-            // initialize fields if any; if none, leave empty struct
-            // Example: name
-            // name,
-            name,
-            artifacts: vec![],
+    /// Open the archive and index every `rust/**.crate` entry from its embedded manifest.
+    pub fn open(name: String, archive_path: PathBuf, public_url: String) -> anyhow::Result<Self> {
+        let archive = ZnippyArchive::open(&archive_path).map_err(|e| {
+            anyhow::anyhow!("opening znippy archive {}: {:#}", archive_path.display(), e)
+        })?;
+
+        let mut entries: Vec<String> = archive
+            .list_files()?
+            .into_iter()
+            .filter(|p| p.starts_with(RUST_PREFIX) && p.ends_with(".crate"))
+            .collect();
+        entries.sort();
+
+        let mut index = CrateIndex::default();
+        for entry_path in entries {
+            let data = archive.extract_file_verified(&entry_path)?;
+            match index_entry_from_crate(&data) {
+                Ok((manifest, entry)) => index.insert(StoredCrate::from_manifest(
+                    manifest,
+                    entry,
+                    PathBuf::from(&entry_path),
+                )),
+                Err(e) => println!("Skipping unreadable crate {}: {:#}", entry_path, e),
+            }
         }
+        let artifacts = index
+            .iter()
+            .map(|c| ArtifactId {
+                namespace: None,
+                name: c.entry.name.clone(),
+                version: c.entry.vers.clone(),
+            })
+            .collect();
+
+        Ok(RustRepoZnippy {
+            name,
+            artifacts,
+            public_url,
+            archive_path,
+            archive,
+            index,
+        })
+    }
+
+    pub fn index(&self) -> &CrateIndex {
+        &self.index
+    }
+
+    fn archive_entry(&self, id: &ArtifactId) -> Option<&Path> {
+        self.index
+            .get(&id.name, &id.version)
+            .map(|c| c.path.as_path())
     }
 }
 
@@ -30,46 +85,74 @@ impl RepositoryBackendTrait for RustRepoZnippy {
 
     fn handle_http2_request(
         &self,
+        method: &str,
         suburl: &str,
+        headers: &[(String, String)],
         body: &[u8],
-    ) -> anyhow::Result<(u16, Vec<(String, String)>, Vec<u8>)> {
-        let _ = body;
+    ) -> anyhow::Result<Http2Response> {
+        let _ = (method, headers, body);
         println!("Rust repo znippy handle_http2_request.suburl={}", suburl);
 
-        let parts: Vec<&str> = suburl.trim_start_matches('/').split('/').collect();
+        let (path, _query) = suburl.split_once('?').unwrap_or((suburl, ""));
+        let parts: Vec<&str> = path.trim_start_matches('/').split('/').collect();
 
         match parts.as_slice() {
             // Sparse root config.json → /rust-prod/index/config.json
             [repo, "index", "config.json"] if *repo == self.name() => {
                 println!("Sparse config.json requested");
-                let json = format!(
-                    r#"{{
-                        "dl": "https://127.0.0.1:8443/{}/crates",
-                        "api": null
-                    }}"#,
-                    self.name()
-                );
-                return Ok((
+                let json = holger_rust_file_repository::config_json(&self.public_url);
+                Ok((
                     200,
                     vec![("Content-Type".into(), "application/json".into())],
                     json.as_bytes().to_vec(),
-                ));
+                ))
             }
 
             // Sparse crate metadata → /rust-prod/index/se/rd/serde
-            [repo, "index", p1, p2, crate_name] if *repo == self.name() => {
+            [repo, "index", prefix @ .., crate_name]
+                if *repo == self.name() && !prefix.is_empty() =>
+            {
+                let requested = format!("{}/{}", prefix.join("/"), crate_name);
+                if RustRepoFile::sparse_index_path(crate_name).as_ref() != Some(&requested) {
                     return Ok((404, Vec::new(), b"Not found".to_vec()));
-
+                }
+                match self.index.index_lines(crate_name)? {
+                    Some(lines) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "text/plain".into())],
+                        lines.into_bytes(),
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
             }
 
-            // Crate download → /crates/<crate>/<version>/download
-            ["crates", crate_name, version, "download"] => {
+            // Crate download → /rust-prod/crates/<crate>/<version>/download
+            [repo, "crates", crate_name, version, "download"] if *repo == self.name() => {
                 println!("Download request: crate={} version={}", crate_name, version);
-                return Ok((
-                    200,
-                    vec![("Content-Type".into(), "application/octet-stream".into())],
-                    b"FAKE_CRATE_CONTENT".to_vec(),
-                ));
+                let id = ArtifactId {
+                    namespace: None,
+                    name: crate_name.to_string(),
+                    version: version.to_string(),
+                };
+                match self.fetch(&id)? {
+                    Some(data) => Ok((
+                        200,
+                        vec![("Content-Type".into(), "application/octet-stream".into())],
+                        data,
+                    )),
+                    None => Ok((404, Vec::new(), b"Not found".to_vec())),
+                }
+            }
+
+            // Publish and yank → /rust-prod/api/v1/crates/...
+            [repo, "api", "v1", "crates", "new"]
+            | [repo, "api", "v1", "crates", _, _, "yank" | "unyank"]
+                if *repo == self.name() =>
+            {
+                Ok(cargo_error(
+                    403,
+                    &format!("repository `{}` is a read-only znippy archive", self.name),
+                ))
             }
 
             _ => {
@@ -83,15 +166,27 @@ impl RepositoryBackendTrait for RustRepoZnippy {
     }
 
     fn is_writable(&self) -> bool {
-        todo!()
+        false
     }
 
     fn fetch(&self, id: &ArtifactId) -> anyhow::Result<Option<Vec<u8>>> {
-        todo!()
+        let Some(entry_path) = self.archive_entry(id) else {
+            return Ok(None);
+        };
+        // Blake3-verified read, the archive is served to clients as-is
+        let data = self
+            .archive
+            .extract_file_verified(&entry_path.to_string_lossy())?;
+        Ok(Some(data))
     }
 
-    fn put(&self, id: &ArtifactId, data: &[u8]) -> anyhow::Result<()> {
-        todo!()
+    fn put(&self, id: &ArtifactId, _data: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!(
+            "repository {} is a read-only znippy archive, cannot store {}-{}",
+            self.name,
+            id.name,
+            id.version
+        )
     }
 }
 
@@ -99,5 +194,86 @@ impl RepositoryBackendTrait for RustRepoZnippy {
 mod tests {
     use super::*;
 
+    /// Gzipped `.crate` tarball holding only a manifest.
+    fn crate_file(name: &str, vers: &str) -> Vec<u8> {
+        let manifest = format!("[package]\nname = \"{name}\"\nversion = \"{vers}\"\n");
+        let gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(
+                &mut header,
+                format!("{name}-{vers}/Cargo.toml"),
+                manifest.as_bytes(),
+            )
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn open_archive(dir: &Path, files: &[(String, Vec<u8>)]) -> RustRepoZnippy {
+        let path = dir.join("airgap.znippy");
+        znippy_common::create_archive(&path, files, 3).unwrap();
+        RustRepoZnippy::open(
+            "rust-prod".into(),
+            path,
+            "https://10.101.1.9:8443/rust-prod".into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn serves_index_and_downloads_from_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let serde_1 = crate_file("serde", "1.0.0");
+        let repo = open_archive(
+            dir.path(),
+            &[
+                ("rust/serde/serde-1.0.0.crate".into(), serde_1.clone()),
+                (
+                    "rust/serde/serde-1.0.1.crate".into(),
+                    crate_file("serde", "1.0.1"),
+                ),
+                ("python/six-1.16.0.tar.gz".into(), b"not a crate".to_vec()),
+            ],
+        );
+        assert_eq!(repo.artifacts.len(), 2);
+
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/index/se/rd/serde", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        let lines: Vec<serde_json::Value> = String::from_utf8(body)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["vers"], "1.0.0");
+        assert_eq!(
+            lines[0]["cksum"],
+            RustRepoFile::crate_sha256_hex(&serde_1).as_str()
+        );
 
+        let (status, _, body) = repo
+            .handle_http2_request("GET", "/rust-prod/crates/serde/1.0.0/download", &[], b"")
+            .unwrap();
+        assert_eq!(status, 200);
+        assert_eq!(body, serde_1);
+
+        for missing in [
+            "/rust-prod/index/se/rd/serdex",
+            "/rust-prod/index/xx/rd/serde",
+            "/rust-prod/crates/serde/2.0.0/download",
+        ] {
+            let (status, _, _) = repo.handle_http2_request("GET", missing, &[], b"").unwrap();
+            assert_eq!(status, 404, "{missing}");
+        }
+        let (status, _, _) = repo
+            .handle_http2_request("PUT", "/rust-prod/api/v1/crates/new", &[], b"")
+            .unwrap();
+        assert_eq!(status, 403);
+    }
 }
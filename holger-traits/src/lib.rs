@@ -1,9 +1,10 @@
-use std::any::Any;
 use std::sync::Arc;
 use std::collections::HashMap;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+pub mod util;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct ArtifactId {
     pub namespace: Option<String>,
@@ -17,6 +18,9 @@ pub enum ArtifactFormat {
     Maven3,
     Pip,
     Rust,
+    Go,
+    Npm,
+    Oci,
     Raw,
 }
 
@@ -25,6 +29,7 @@ pub enum ArtifactFormat {
 pub enum StorageType {
     Znippy,
     Rocksdb,
+    File,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -33,6 +38,9 @@ pub enum RepositoryType {
     Rust,
     Pip,
     Maven3,
+    Go,
+    Npm,
+    Oci,
     Raw,
 }
 impl RepositoryType {
@@ -41,11 +49,16 @@ impl RepositoryType {
             RepositoryType::Rust => "rust",
             RepositoryType::Pip => "pip",
             RepositoryType::Maven3 => "maven3",
+            RepositoryType::Go => "go",
+            RepositoryType::Npm => "npm",
+            RepositoryType::Oci => "oci",
             RepositoryType::Raw => "raw",
         }
     }
 }
 
+/// Status code, headers and body returned by a repository backend.
+pub type Http2Response = (u16, Vec<(String, String)>, Vec<u8>);
 
 #[async_trait]
 pub trait RepositoryBackendTrait: Send + Sync {
@@ -80,9 +93,11 @@ pub trait RepositoryBackendTrait: Send + Sync {
     }
     fn handle_http2_request(
         &self,
+        method: &str,
         suburl: &str,
+        headers: &[(String, String)],
         body: &[u8],
-    ) -> anyhow::Result<(u16, Vec<(String, String)>, Vec<u8>)>;
+    ) -> anyhow::Result<Http2Response>;
 
 }
 
@@ -0,0 +1,164 @@
+//! File and formatting helpers shared by the file-backed repositories.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Write through a temporary file so readers never see partial content. The
+/// temporary file is hidden and uniquely named, so it never collides with a
+/// stored file and is skipped by [`find_files`].
+pub fn write_atomic(path: &Path, data: &[u8]) -> anyhow::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)?;
+    let mut tmp = tempfile::NamedTempFile::new_in(parent)?;
+    tmp.write_all(data)?;
+    tmp.as_file().sync_all()?;
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// `/`-separated path of `path` relative to `root`.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Recursively collect every file below `root` with its `/`-separated
+/// relative path, skipping hidden entries.
+pub fn find_files(root: &Path) -> anyhow::Result<Vec<(String, PathBuf)>> {
+    let mut found = Vec::new();
+    if !root.is_dir() {
+        return Ok(found);
+    }
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                found.push((relative_path(root, &path), path));
+            }
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+/// Decode `%XX` escapes in a URL segment; `None` if an escape is malformed
+/// or the result is not UTF-8.
+pub fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Percent-encode a URL segment, leaving only RFC 3986 unreserved characters.
+pub fn percent_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// A UTC calendar date and time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcDateTime {
+    pub year: u64,
+    pub month: u64,
+    pub day: u64,
+    pub hour: u64,
+    pub minute: u64,
+    pub second: u64,
+}
+
+impl UtcDateTime {
+    pub fn from_unix(secs: u64) -> Self {
+        let (days, rem) = (secs / 86_400, secs % 86_400);
+        // Civil-from-days, valid for any date after 1970
+        let z = days + 719_468;
+        let era = z / 146_097;
+        let doe = z % 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        UtcDateTime {
+            year: yoe + era * 400 + u64::from(month <= 2),
+            month,
+            day,
+            hour: rem / 3_600,
+            minute: rem % 3_600 / 60,
+            second: rem % 60,
+        }
+    }
+}
+
+/// Unix seconds as an RFC 3339 UTC timestamp, e.g. `2024-01-02T03:04:05Z`.
+pub fn format_rfc3339(secs: u64) -> String {
+    let t = UtcDateTime::from_unix(secs);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(format_rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_rfc3339(1_704_164_645), "2024-01-02T03:04:05Z");
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(
+            percent_decode("%40types%2Fnode").as_deref(),
+            Some("@types/node")
+        );
+        assert_eq!(
+            percent_decode("ninja%201.12").as_deref(),
+            Some("ninja 1.12")
+        );
+        assert_eq!(percent_decode("bad%2"), None);
+        assert_eq!(
+            percent_encode("torch-2.1.0+cpu-cp311-none-any.whl"),
+            "torch-2.1.0%2Bcpu-cp311-none-any.whl"
+        );
+        assert_eq!(
+            percent_decode(&percent_encode("a b/ü")).as_deref(),
+            Some("a b/ü")
+        );
+        assert_eq!(percent_decode("%ff"), None);
+    }
+}